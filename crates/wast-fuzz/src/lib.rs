@@ -25,26 +25,3 @@ pub fn wabt_may_disagree_on_binary(wast: &str) -> bool {
     }
     false
 }
-
-pub fn remove_name_section(bytes: &[u8]) -> Vec<u8> {
-    use wasmparser::*;
-
-    if let Ok(mut r) = ModuleReader::new(bytes) {
-        loop {
-            let start = r.current_position();
-            if let Ok(s) = r.read() {
-                match s.code {
-                    SectionCode::Custom { name: "name", .. } => {
-                        let mut bytes = bytes.to_vec();
-                        bytes.drain(start..s.range().end);
-                        return bytes;
-                    }
-                    _ => {}
-                }
-            } else {
-                break;
-            }
-        }
-    }
-    return bytes.to_vec();
-}
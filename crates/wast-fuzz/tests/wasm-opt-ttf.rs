@@ -44,7 +44,11 @@ fn run_iter(td: &Path) {
 }
 
 fn attempt_roundtrip(td: &Path, wat: &str) -> anyhow::Result<()> {
-    let binary = wat::parse_str(wat)?;
+    // `wat2wasm` doesn't emit a name section unless asked to, so we don't
+    // generate one either.
+    let binary = wat::Parser::new()
+        .generate_name_section(false)
+        .parse_str(wat)?;
     if wast_fuzz::wabt_may_disagree_on_binary(wat) {
         return Ok(());
     }
@@ -60,9 +64,7 @@ fn attempt_roundtrip(td: &Path, wat: &str) -> anyhow::Result<()> {
         .output()?;
     if output.status.success() {
         let wabt_bytes = std::fs::read(&wasm)?;
-        // see comments in the test suite for why we remove the name
-        // section
-        if wast_fuzz::remove_name_section(&binary) != wabt_bytes {
+        if binary != wabt_bytes {
             anyhow::bail!("binary encoding differs from wabt");
         }
     }
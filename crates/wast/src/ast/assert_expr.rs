@@ -8,7 +8,8 @@ use crate::parser::{Parse, Parser, Result};
 /// `AssertExpression` means that:
 /// - only constant values (e.g. `i32.const 4`) are used in the `assert_return` directive
 /// - the NaN patterns are allowed (they are not allowed in regular `Expression`s).
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum AssertExpression<'a> {
     I32(i32),
@@ -27,6 +28,24 @@ pub enum AssertExpression<'a> {
     LegacyCanonicalNaN,
 }
 
+impl AssertExpression<'_> {
+    #[allow(missing_docs)]
+    pub fn into_owned(self) -> AssertExpression<'static> {
+        match self {
+            AssertExpression::I32(v) => AssertExpression::I32(v),
+            AssertExpression::I64(v) => AssertExpression::I64(v),
+            AssertExpression::F32(v) => AssertExpression::F32(v),
+            AssertExpression::F64(v) => AssertExpression::F64(v),
+            AssertExpression::V128(v) => AssertExpression::V128(v),
+            AssertExpression::RefNull => AssertExpression::RefNull,
+            AssertExpression::RefHost(v) => AssertExpression::RefHost(v),
+            AssertExpression::RefFunc(i) => AssertExpression::RefFunc(i.into_owned()),
+            AssertExpression::LegacyArithmeticNaN => AssertExpression::LegacyArithmeticNaN,
+            AssertExpression::LegacyCanonicalNaN => AssertExpression::LegacyCanonicalNaN,
+        }
+    }
+}
+
 impl <'a> Parse<'a> for AssertExpression<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let keyword = parser.step(|c| {
@@ -51,7 +70,8 @@ impl <'a> Parse<'a> for AssertExpression<'a> {
 }
 
 /// Either a NaN pattern (`nan:canonical`, `nan:arithmetic`) or a value of type `T`.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum NanPattern<T> {
     CanonicalNan,
@@ -78,7 +98,8 @@ impl <'a, T> Parse<'a> for NanPattern<T> where T: Parse<'a> {
 ///
 /// This implementation is necessary because only float types can include NaN patterns; otherwise
 /// it is largely similar to the implementation of `V128Const`.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum V128Pattern {
     I8x16([i8; 16]),
@@ -0,0 +1,361 @@
+use crate::ast::*;
+use std::borrow::Cow;
+
+/// A fluent builder for constructing a [`Module`] programmatically.
+///
+/// Each method which defines an item returns an [`Index`] which can be used to
+/// refer to that item in instructions, exports, etc. The resulting module can
+/// be encoded with [`Module::encode`] like any parsed module.
+///
+/// Note that imports must all be added before any definitions so the indices
+/// returned for definitions don't change.
+///
+/// # Examples
+///
+/// ```
+/// use wast::{Instruction, ModuleBuilder, ValType};
+///
+/// # fn foo() -> Result<(), wast::Error> {
+/// let mut builder = ModuleBuilder::new();
+/// let add = builder
+///     .func("add", &[ValType::I32, ValType::I32], &[ValType::I32])
+///     .instr(Instruction::LocalGet(wast::Index::Num(0)))
+///     .instr(Instruction::LocalGet(wast::Index::Num(1)))
+///     .instr(Instruction::I32Add)
+///     .export("add")
+///     .finish();
+/// builder.start(add);
+/// let wasm = builder.build().encode()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct ModuleBuilder<'a> {
+    name: Option<Id<'a>>,
+    types: Vec<ModuleField<'a>>,
+    imports: Vec<ModuleField<'a>>,
+    fields: Vec<ModuleField<'a>>,
+    funcs: u32,
+    tables: u32,
+    memories: u32,
+    globals: u32,
+}
+
+/// A builder for a function definition, created by [`ModuleBuilder::func`].
+///
+/// The function is added to its module with [`FuncBuilder::finish`].
+pub struct FuncBuilder<'b, 'a> {
+    module: &'b mut ModuleBuilder<'a>,
+    name: Cow<'a, str>,
+    exports: InlineExport<'a>,
+    ty: TypeUse<'a>,
+    locals: Vec<(Option<Id<'a>>, ValType)>,
+    instrs: Vec<Instruction<'a>>,
+}
+
+fn span() -> Span {
+    Span { offset: 0 }
+}
+
+fn type_use<'a>(params: &[ValType], results: &[ValType]) -> TypeUse<'a> {
+    TypeUse {
+        index_span: None,
+        index: None,
+        ty: FunctionType {
+            params: params.iter().map(|ty| (None, *ty)).collect(),
+            results: results.to_vec(),
+        },
+    }
+}
+
+fn no_exports<'a>() -> InlineExport<'a> {
+    InlineExport { names: Vec::new() }
+}
+
+impl<'a> ModuleBuilder<'a> {
+    /// Creates a new empty module builder.
+    pub fn new() -> ModuleBuilder<'a> {
+        ModuleBuilder::default()
+    }
+
+    /// Sets the name of the module, which is emitted in the name section.
+    pub fn name(&mut self, name: impl Into<Cow<'a, str>>) -> &mut ModuleBuilder<'a> {
+        self.name = Some(Id::new(name, span()));
+        self
+    }
+
+    /// Defines a function type, returning its index.
+    ///
+    /// Function types are otherwise created automatically for functions and
+    /// imports, but an explicit index is useful for `call_indirect`.
+    pub fn ty(&mut self, params: &[ValType], results: &[ValType]) -> Index<'a> {
+        let index = Index::Num(self.types.len() as u32);
+        self.types.push(ModuleField::Type(Type {
+            name: None,
+            func: type_use(params, results).ty,
+        }));
+        index
+    }
+
+    /// Imports a function from `module` named `name`, returning its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a definition has already been added to this module.
+    pub fn import_func(
+        &mut self,
+        module: impl Into<Cow<'a, str>>,
+        name: impl Into<Cow<'a, str>>,
+        params: &[ValType],
+        results: &[ValType],
+    ) -> Index<'a> {
+        let kind = ImportKind::Func(type_use(params, results));
+        let index = self.funcs;
+        self.funcs += 1;
+        self.import(module.into(), name.into(), kind, index)
+    }
+
+    /// Imports a table from `module` named `name`, returning its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a definition has already been added to this module.
+    pub fn import_table(
+        &mut self,
+        module: impl Into<Cow<'a, str>>,
+        name: impl Into<Cow<'a, str>>,
+        ty: TableType,
+    ) -> Index<'a> {
+        let index = self.tables;
+        self.tables += 1;
+        self.import(module.into(), name.into(), ImportKind::Table(ty), index)
+    }
+
+    /// Imports a memory from `module` named `name`, returning its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a definition has already been added to this module.
+    pub fn import_memory(
+        &mut self,
+        module: impl Into<Cow<'a, str>>,
+        name: impl Into<Cow<'a, str>>,
+        ty: MemoryType,
+    ) -> Index<'a> {
+        let index = self.memories;
+        self.memories += 1;
+        self.import(module.into(), name.into(), ImportKind::Memory(ty), index)
+    }
+
+    /// Imports a global from `module` named `name`, returning its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a definition has already been added to this module.
+    pub fn import_global(
+        &mut self,
+        module: impl Into<Cow<'a, str>>,
+        name: impl Into<Cow<'a, str>>,
+        ty: GlobalType,
+    ) -> Index<'a> {
+        let index = self.globals;
+        self.globals += 1;
+        self.import(module.into(), name.into(), ImportKind::Global(ty), index)
+    }
+
+    fn import(
+        &mut self,
+        module: Cow<'a, str>,
+        name: Cow<'a, str>,
+        kind: ImportKind<'a>,
+        index: u32,
+    ) -> Index<'a> {
+        assert!(
+            self.fields.is_empty(),
+            "imports must be added before any definitions"
+        );
+        self.imports.push(ModuleField::Import(Import {
+            span: span(),
+            module,
+            name,
+            id: None,
+            kind,
+        }));
+        Index::Num(index)
+    }
+
+    /// Starts defining a function named `name` with the given signature.
+    ///
+    /// The parameters are the first locals of the function, and more can be
+    /// added with [`FuncBuilder::local`].
+    pub fn func<'b>(
+        &'b mut self,
+        name: impl Into<Cow<'a, str>>,
+        params: &[ValType],
+        results: &[ValType],
+    ) -> FuncBuilder<'b, 'a> {
+        FuncBuilder {
+            module: self,
+            name: name.into(),
+            exports: no_exports(),
+            ty: type_use(params, results),
+            locals: Vec::new(),
+            instrs: Vec::new(),
+        }
+    }
+
+    /// Defines a table, returning its index.
+    pub fn table(&mut self, ty: TableType) -> Index<'a> {
+        self.fields.push(ModuleField::Table(Table {
+            span: span(),
+            name: None,
+            exports: no_exports(),
+            kind: TableKind::Normal(ty),
+        }));
+        self.tables += 1;
+        Index::Num(self.tables - 1)
+    }
+
+    /// Defines a memory, returning its index.
+    pub fn memory(&mut self, ty: MemoryType) -> Index<'a> {
+        self.fields.push(ModuleField::Memory(Memory {
+            span: span(),
+            name: None,
+            exports: no_exports(),
+            kind: MemoryKind::Normal(ty),
+        }));
+        self.memories += 1;
+        Index::Num(self.memories - 1)
+    }
+
+    /// Defines a global with the constant expression `init` as its initial
+    /// value, returning its index.
+    pub fn global(&mut self, ty: GlobalType, init: Vec<Instruction<'a>>) -> Index<'a> {
+        self.fields.push(ModuleField::Global(Global {
+            span: span(),
+            name: None,
+            exports: no_exports(),
+            ty,
+            kind: GlobalKind::Inline(Expression { instrs: init }),
+        }));
+        self.globals += 1;
+        Index::Num(self.globals - 1)
+    }
+
+    /// Exports the item described by `kind` under `name`.
+    pub fn export(
+        &mut self,
+        name: impl Into<Cow<'a, str>>,
+        kind: ExportKind<'a>,
+    ) -> &mut ModuleBuilder<'a> {
+        self.fields.push(ModuleField::Export(Export {
+            span: span(),
+            name: name.into(),
+            kind,
+        }));
+        self
+    }
+
+    /// Sets the start function of the module.
+    pub fn start(&mut self, func: Index<'a>) -> &mut ModuleBuilder<'a> {
+        self.fields.push(ModuleField::Start(func));
+        self
+    }
+
+    /// Adds an active element segment initializing `table` at `offset` with
+    /// the functions `funcs`.
+    pub fn elem(
+        &mut self,
+        table: Index<'a>,
+        offset: Vec<Instruction<'a>>,
+        funcs: Vec<Index<'a>>,
+    ) -> &mut ModuleBuilder<'a> {
+        self.fields.push(ModuleField::Elem(Elem {
+            span: span(),
+            name: None,
+            kind: ElemKind::Active {
+                table,
+                offset: Expression { instrs: offset },
+            },
+            payload: ElemPayload::Indices(funcs),
+        }));
+        self
+    }
+
+    /// Adds an active data segment initializing `memory` at `offset` with
+    /// `data`.
+    pub fn data(
+        &mut self,
+        memory: Index<'a>,
+        offset: Vec<Instruction<'a>>,
+        data: impl Into<Cow<'a, [u8]>>,
+    ) -> &mut ModuleBuilder<'a> {
+        self.fields.push(ModuleField::Data(Data {
+            span: span(),
+            name: None,
+            kind: DataKind::Active {
+                memory,
+                offset: Expression { instrs: offset },
+            },
+            data: vec![data.into()],
+        }));
+        self
+    }
+
+    /// Finishes building, returning the resulting [`Module`].
+    pub fn build(self) -> Module<'a> {
+        let mut fields = self.types;
+        fields.extend(self.imports);
+        fields.extend(self.fields);
+        Module {
+            span: span(),
+            name: self.name,
+            kind: ModuleKind::Text(fields),
+        }
+    }
+}
+
+impl<'b, 'a> FuncBuilder<'b, 'a> {
+    /// Adds a local of type `ty` to this function.
+    pub fn local(mut self, ty: ValType) -> Self {
+        self.locals.push((None, ty));
+        self
+    }
+
+    /// Appends `instr` to the body of this function.
+    pub fn instr(mut self, instr: Instruction<'a>) -> Self {
+        self.instrs.push(instr);
+        self
+    }
+
+    /// Appends all of `instrs` to the body of this function.
+    pub fn instrs(mut self, instrs: impl IntoIterator<Item = Instruction<'a>>) -> Self {
+        self.instrs.extend(instrs);
+        self
+    }
+
+    /// Exports this function under `name`.
+    pub fn export(mut self, name: impl Into<Cow<'a, str>>) -> Self {
+        self.exports.names.push((span(), name.into()));
+        self
+    }
+
+    /// Adds this function to its module, returning its index.
+    pub fn finish(self) -> Index<'a> {
+        let module = self.module;
+        module.fields.push(ModuleField::Func(Func {
+            span: span(),
+            name: Some(Id::new(self.name, span())),
+            exports: self.exports,
+            kind: FuncKind::Inline {
+                locals: self.locals,
+                expression: Expression {
+                    instrs: self.instrs,
+                },
+            },
+            ty: self.ty,
+        }));
+        module.funcs += 1;
+        Index::Num(module.funcs - 1)
+    }
+}
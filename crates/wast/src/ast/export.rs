@@ -1,18 +1,23 @@
 use crate::ast::{self, kw};
 use crate::parser::{Parse, Parser, Result};
+use std::borrow::Cow;
 
 /// A entry in a WebAssembly module's export section.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Export<'a> {
+    /// Where this export was defined.
+    pub span: ast::Span,
     /// The name of this export from the module.
-    pub name: &'a str,
+    pub name: Cow<'a, str>,
     /// What's being exported from the module.
     pub kind: ExportKind<'a>,
 }
 
 /// Different kinds of elements that can be exported from a WebAssembly module,
 /// contained in an [`Export`].
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum ExportKind<'a> {
     Func(ast::Index<'a>),
@@ -21,9 +26,33 @@ pub enum ExportKind<'a> {
     Global(ast::Index<'a>),
 }
 
+impl Export<'_> {
+    /// Converts this export into one which no longer borrows from the
+    /// original source text.
+    pub fn into_owned(self) -> Export<'static> {
+        Export {
+            span: self.span,
+            name: Cow::Owned(self.name.into_owned()),
+            kind: self.kind.into_owned(),
+        }
+    }
+}
+
+impl ExportKind<'_> {
+    #[allow(missing_docs)]
+    pub fn into_owned(self) -> ExportKind<'static> {
+        match self {
+            ExportKind::Func(i) => ExportKind::Func(i.into_owned()),
+            ExportKind::Table(i) => ExportKind::Table(i.into_owned()),
+            ExportKind::Memory(i) => ExportKind::Memory(i.into_owned()),
+            ExportKind::Global(i) => ExportKind::Global(i.into_owned()),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for Export<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
-        parser.parse::<kw::export>()?;
+        let span = parser.parse::<kw::export>()?.0;
         let name = parser.parse()?;
         let kind = parser.parens(|parser| {
             let mut l = parser.lookahead1();
@@ -43,16 +72,31 @@ impl<'a> Parse<'a> for Export<'a> {
                 Err(l.error())
             }
         })?;
-        Ok(Export { name, kind })
+        Ok(Export { span, name, kind })
     }
 }
 
 /// A listing of inline `(export "foo")` statements on a WebAssembly item in
 /// its textual format.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InlineExport<'a> {
-    /// The extra names to export an item as, if any.
-    pub names: Vec<&'a str>,
+    /// The extra names to export an item as, if any, along with where each
+    /// `export` was written.
+    pub names: Vec<(ast::Span, Cow<'a, str>)>,
+}
+
+impl InlineExport<'_> {
+    #[allow(missing_docs)]
+    pub fn into_owned(self) -> InlineExport<'static> {
+        InlineExport {
+            names: self
+                .names
+                .into_iter()
+                .map(|(span, name)| (span, Cow::Owned(name.into_owned())))
+                .collect(),
+        }
+    }
 }
 
 impl<'a> Parse<'a> for InlineExport<'a> {
@@ -60,8 +104,8 @@ impl<'a> Parse<'a> for InlineExport<'a> {
         let mut names = Vec::new();
         while parser.peek2::<kw::export>() {
             names.push(parser.parens(|p| {
-                p.parse::<kw::export>()?;
-                p.parse::<&str>()
+                let span = p.parse::<kw::export>()?.0;
+                Ok((span, p.parse()?))
             })?);
         }
         Ok(InlineExport { names })
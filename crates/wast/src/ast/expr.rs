@@ -1,4 +1,4 @@
-use crate::ast::{self, kw};
+use crate::ast::{self, kw, Opcode, Proposal, StackSignature, ValType};
 use crate::parser::{Parse, Parser, Result};
 
 /// An expression, or a list of instructions, in the WebAssembly text format.
@@ -6,12 +6,27 @@ use crate::parser::{Parse, Parser, Result};
 /// This expression type will parse s-expression-folded instructions into a flat
 /// list of instructions for emission later on. The implicit `end` instruction
 /// at the end of an expression is not included in the `instrs` field.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub struct Expression<'a> {
     pub instrs: Vec<Instruction<'a>>,
 }
 
+impl Expression<'_> {
+    /// Converts this expression into one which no longer borrows from the
+    /// original source text.
+    pub fn into_owned(self) -> Expression<'static> {
+        Expression {
+            instrs: self
+                .instrs
+                .into_iter()
+                .map(Instruction::into_owned)
+                .collect(),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for Expression<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let mut instrs = Vec::new();
@@ -123,12 +138,14 @@ macro_rules! instructions {
     (pub enum Instruction<'a> {
         $(
             $(#[$doc:meta])*
-            $name:ident $(($($arg:tt)*))? : [$($binary:tt)*] : $instr:tt $( | $deprecated:tt )?,
+            $name:ident $(($($arg:tt)*))? : [$($binary:tt)*] : $instr:tt $( | $deprecated:tt )? : $visit:ident
+                : $proposal:ident $($memory:ident)? $( : [$($param:ident)*] -> [$($result:ident)*] )?,
         )*
     }) => (
         /// A listing of all WebAssembly instructions that can be in a module
         /// that this crate currently parses.
-        #[derive(Debug)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         #[allow(missing_docs)]
         pub enum Instruction<'a> {
             $(
@@ -138,6 +155,97 @@ macro_rules! instructions {
 
         }
 
+        impl Opcode {
+            /// Returns the proposal which introduced this opcode.
+            ///
+            /// Opcodes which aren't used by any instruction are considered to
+            /// be part of the original WebAssembly specification.
+            pub fn proposal(&self) -> Proposal {
+                match *self {
+                    // the `select` which lists its result types
+                    Opcode { prefix: None, code: 0x1c } => Proposal::ReferenceTypes,
+                    $(instructions!(@opcode $($binary)*) => Proposal::$proposal,)*
+                    _ => Proposal::Mvp,
+                }
+            }
+        }
+
+        impl Instruction<'_> {
+            /// Returns the name of this instruction in the text format, such
+            /// as `i32.add`.
+            ///
+            /// Deprecated aliases like `get_local` are never returned.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Instruction::$name { .. } => $instr,)*
+                }
+            }
+
+            /// Returns the opcode this instruction is encoded with in the
+            /// binary format.
+            pub fn opcode(&self) -> Opcode {
+                match self {
+                    // `select` is the only instruction with two encodings,
+                    // depending on whether it lists its result types.
+                    Instruction::Select(s) if !s.tys.is_empty() => Opcode {
+                        prefix: None,
+                        code: 0x1c,
+                    },
+                    $(Instruction::$name { .. } => instructions!(@opcode $($binary)*),)*
+                }
+            }
+
+            /// Returns the types this instruction pops from and pushes onto
+            /// the operand stack.
+            ///
+            /// Returns `None` if the signature depends on the context the
+            /// instruction is used in, such as for `call`, `local.get`,
+            /// `drop` or `block`.
+            pub fn signature(&self) -> Option<StackSignature> {
+                match self {
+                    $(
+                        Instruction::$name { .. } => {
+                            instructions!(@signature $([$($param)*] -> [$($result)*])?)
+                        }
+                    )*
+                }
+            }
+
+            /// Returns whether this instruction accesses the default memory,
+            /// which the module must then define or import.
+            pub(crate) fn uses_memory(&self) -> bool {
+                match self {
+                    $(Instruction::$name { .. } => instructions!(@memory $($memory)?),)*
+                }
+            }
+
+            /// Writes the immediates of this instruction to `dst`, in the
+            /// form used by [`Module::dump`](crate::Module::dump).
+            #[allow(non_snake_case)]
+            pub(crate) fn write_operands(&self, dst: &mut String) {
+                match self {
+                    $(
+                        Instruction::$name $((instructions!(@first $($arg)*)))? => {
+                            $(crate::dump::Operand::write(instructions!(@first $($arg)*), dst);)?
+                        }
+                    )*
+                }
+            }
+
+            /// Converts this instruction into one which no longer borrows
+            /// from the original source text.
+            #[allow(non_snake_case)]
+            pub fn into_owned(self) -> Instruction<'static> {
+                match self {
+                    $(
+                        Instruction::$name $((instructions!(@first $($arg)*)))? => {
+                            Instruction::$name $((instructions!(@owned $($arg)*)))?
+                        }
+                    )*
+                }
+            }
+        }
+
         #[allow(non_snake_case)]
         impl<'a> Parse<'a> for Instruction<'a> {
             fn parse(parser: Parser<'a>) -> Result<Self> {
@@ -148,17 +256,29 @@ macro_rules! instructions {
                         ))?)
                     }
                 )*
-                let parse_remainder = parser.step(|c| {
+                let (parse_remainder, kw, instr) = parser.step(|c| {
                     let (kw, rest) = match c.keyword() {
                         Some(pair) => pair,
                         None => return Err(c.error("expected an instruction")),
                     };
                     match kw {
-                        $($instr $( | $deprecated )?=> Ok(($name as fn(_) -> _, rest)),)*
-                        _ => return Err(c.error("unknown operator or unexpected token")),
+                        $($instr $( | $deprecated )?=> Ok((($name as fn(Parser<'a>) -> Result<Self>, kw, $instr), rest)),)*
+                        _ => {
+                            let mut err = c.error("unknown operator or unexpected token");
+                            let names = [$($instr,)*];
+                            if let Some(name) = crate::suggest::closest(kw, names.iter().cloned()) {
+                                err.add_help(format!("did you mean `{}`?", name));
+                            }
+                            return Err(err)
+                        }
                     }
                 })?;
-                parse_remainder(parser)
+                parse_remainder(parser).map_err(|mut e| {
+                    if kw != instr {
+                        e.add_help(format!("`{}` is a deprecated alias, use `{}` instead", kw, instr));
+                    }
+                    e
+                })
             }
         }
 
@@ -178,6 +298,28 @@ macro_rules! instructions {
                 }
             }
         }
+
+        impl<'a> crate::reader::Decode<'a> for Instruction<'a> {
+            #[allow(non_snake_case)]
+            fn decode(r: &mut crate::reader::Reader<'a>) -> crate::reader::Result<Self> {
+                $(
+                    fn $name<'a>(_r: &mut crate::reader::Reader<'a>) -> crate::reader::Result<Instruction<'a>> {
+                        instructions!(@reserved _r $($binary)*);
+                        Ok(Instruction::$name $((
+                            <instructions!(@ty $($arg)*) as crate::reader::Decode>::decode(_r)?
+                        ))?)
+                    }
+                )*
+                let start = r.pos();
+                match r.opcode()? {
+                    Opcode { prefix: None, code: 0x1c } => Ok(Instruction::Select(SelectTypes {
+                        tys: r.vec(<ast::ValType as crate::reader::Decode>::decode)?,
+                    })),
+                    $(instructions!(@opcode $($binary)*) => $name(r),)*
+                    _ => Err(r.error_at(start, "illegal opcode")),
+                }
+            }
+        }
     );
 
     (@ty MemArg<$amt:tt>) => (MemArg);
@@ -185,6 +327,43 @@ macro_rules! instructions {
 
     (@first $first:ident $($t:tt)*) => ($first);
 
+    (@owned $a:ident < ast :: Id $($rest:tt)*) => ($a.map(ast::Id::into_owned));
+    (@owned $a:ident :: Index $($rest:tt)*) => ($a.into_owned());
+    (@owned $a:ident < $l:lifetime >) => ($a.into_owned());
+    (@owned $a:ident $($rest:tt)*) => ($a);
+
+    (@opcode) => (Opcode { prefix: None, code: 0x1b });
+    (@opcode 0xfc, $code:tt $(, $rest:tt)*) => (Opcode { prefix: Some(0xfc), code: $code });
+    (@opcode 0xfd, $code:tt $(, $rest:tt)*) => (Opcode { prefix: Some(0xfd), code: $code });
+    (@opcode 0xfe, $code:tt $(, $rest:tt)*) => (Opcode { prefix: Some(0xfe), code: $code });
+    (@opcode $code:tt $(, $rest:tt)*) => (Opcode { prefix: None, code: $code });
+
+    (@signature) => (None);
+    (@signature [$($param:ident)*] -> [$($result:ident)*]) => (Some(StackSignature {
+        params: &[$(instructions!(@valtype $param)),*],
+        results: &[$(instructions!(@valtype $result)),*],
+    }));
+
+    (@memory) => (false);
+    (@memory memory) => (true);
+
+    (@valtype i32) => (ValType::I32);
+    (@valtype i64) => (ValType::I64);
+    (@valtype f32) => (ValType::F32);
+    (@valtype f64) => (ValType::F64);
+    (@valtype v128) => (ValType::V128);
+    (@valtype anyref) => (ValType::Anyref);
+    (@valtype funcref) => (ValType::Funcref);
+    (@valtype nullref) => (ValType::Nullref);
+
+    // bytes following the opcode, such as the memory index of `memory.size`,
+    // are reserved and must be decoded exactly
+    (@reserved $r:ident) => ();
+    (@reserved $r:ident 0xfc, $code:tt $(, $rest:tt)*) => ($($r.reserved($rest)?;)*);
+    (@reserved $r:ident 0xfd, $code:tt $(, $rest:tt)*) => ($($r.reserved($rest)?;)*);
+    (@reserved $r:ident 0xfe, $code:tt $(, $rest:tt)*) => ($($r.reserved($rest)?;)*);
+    (@reserved $r:ident $code:tt $(, $rest:tt)*) => ($($r.reserved($rest)?;)*);
+
     (@parse $parser:ident MemArg<$amt:tt>) => (MemArg::parse($parser, $amt));
     (@parse $parser:ident MemArg) => (compile_error!("must specify `MemArg` default"));
     (@parse $parser:ident $other:ty) => ($parser.parse::<$other>());
@@ -197,502 +376,526 @@ macro_rules! instructions {
     (@encode $dst:ident $($bytes:tt)*) => ($dst.extend_from_slice(&[$($bytes)*]););
 }
 
-instructions! {
-    pub enum Instruction<'a> {
-        Block(BlockType<'a>) : [0x02] : "block",
-        If(BlockType<'a>) : [0x04] : "if",
-        Else(Option<ast::Id<'a>>) : [0x05] : "else",
-        Loop(BlockType<'a>) : [0x03] : "loop",
-        End(Option<ast::Id<'a>>) : [0x0b] : "end",
-
-        Unreachable : [0x00] : "unreachable",
-        Nop : [0x01] : "nop",
-        Br(ast::Index<'a>) : [0x0c] : "br",
-        BrIf(ast::Index<'a>) : [0x0d] : "br_if",
-        BrTable(BrTableIndices<'a>) : [0x0e] : "br_table",
-        Return : [0x0f] : "return",
-        Call(ast::Index<'a>) : [0x10] : "call",
-        CallIndirect(CallIndirect<'a>) : [0x11] : "call_indirect",
-        ReturnCall(ast::Index<'a>) : [0x12] : "return_call",
-        ReturnCallIndirect(CallIndirect<'a>) : [0x13] : "return_call_indirect",
-        Drop : [0x1a] : "drop",
-        Select(SelectTypes) : [] : "select",
-        LocalGet(ast::Index<'a>) : [0x20] : "local.get" | "get_local",
-        LocalSet(ast::Index<'a>) : [0x21] : "local.set" | "set_local",
-        LocalTee(ast::Index<'a>) : [0x22] : "local.tee" | "tee_local",
-        GlobalGet(ast::Index<'a>) : [0x23] : "global.get" | "get_global",
-        GlobalSet(ast::Index<'a>) : [0x24] : "global.set" | "set_global",
-
-        TableGet(ast::Index<'a>) : [0x25] : "table.get",
-        TableSet(ast::Index<'a>) : [0x26] : "table.set",
-
-        I32Load(MemArg<4>) : [0x28] : "i32.load",
-        I64Load(MemArg<8>) : [0x29] : "i64.load",
-        F32Load(MemArg<4>) : [0x2a] : "f32.load",
-        F64Load(MemArg<8>) : [0x2b] : "f64.load",
-        I32Load8s(MemArg<1>) : [0x2c] : "i32.load8_s",
-        I32Load8u(MemArg<1>) : [0x2d] : "i32.load8_u",
-        I32Load16s(MemArg<2>) : [0x2e] : "i32.load16_s",
-        I32Load16u(MemArg<2>) : [0x2f] : "i32.load16_u",
-        I64Load8s(MemArg<1>) : [0x30] : "i64.load8_s",
-        I64Load8u(MemArg<1>) : [0x31] : "i64.load8_u",
-        I64Load16s(MemArg<2>) : [0x32] : "i64.load16_s",
-        I64Load16u(MemArg<2>) : [0x33] : "i64.load16_u",
-        I64Load32s(MemArg<4>) : [0x34] : "i64.load32_s",
-        I64Load32u(MemArg<4>) : [0x35] : "i64.load32_u",
-        I32Store(MemArg<4>) : [0x36] : "i32.store",
-        I64Store(MemArg<8>) : [0x37] : "i64.store",
-        F32Store(MemArg<4>) : [0x38] : "f32.store",
-        F64Store(MemArg<8>) : [0x39] : "f64.store",
-        I32Store8(MemArg<1>) : [0x3a] : "i32.store8",
-        I32Store16(MemArg<2>) : [0x3b] : "i32.store16",
-        I64Store8(MemArg<1>) : [0x3c] : "i64.store8",
-        I64Store16(MemArg<2>) : [0x3d] : "i64.store16",
-        I64Store32(MemArg<4>) : [0x3e] : "i64.store32",
-
-        // Lots of bulk memory proposal here as well
-        MemorySize : [0x3f, 0x00] : "memory.size" | "current_memory",
-        MemoryGrow : [0x40, 0x00] : "memory.grow" | "grow_memory",
-        MemoryInit(MemoryInit<'a>) : [0xfc, 0x08] : "memory.init",
-        MemoryCopy : [0xfc, 0x0a, 0x00, 0x00] : "memory.copy",
-        MemoryFill : [0xfc, 0x0b, 0x00] : "memory.fill",
-        DataDrop(ast::Index<'a>) : [0xfc, 0x09] : "data.drop",
-        ElemDrop(ast::Index<'a>) : [0xfc, 0x0d] : "elem.drop",
-        TableInit(TableInit<'a>) : [0xfc, 0x0c] : "table.init",
-        TableCopy : [0xfc, 0x0e, 0x00, 0x00] : "table.copy",
-        TableFill(ast::Index<'a>) : [0xfc, 0x11] : "table.fill",
-        TableSize(ast::Index<'a>) : [0xfc, 0x10] : "table.size",
-        TableGrow(ast::Index<'a>) : [0xfc, 0x0f] : "table.grow",
-
-        RefNull : [0xd0] : "ref.null",
-        RefIsNull : [0xd1] : "ref.is_null",
-        RefHost(u32) : [0xff] : "ref.host", // only used in test harness
-        RefFunc(ast::Index<'a>) : [0xd2] : "ref.func", // only used in test harness
-
-        I32Const(i32) : [0x41] : "i32.const",
-        I64Const(i64) : [0x42] : "i64.const",
-        F32Const(ast::Float32) : [0x43] : "f32.const",
-        F64Const(ast::Float64) : [0x44] : "f64.const",
-
-        I32Clz : [0x67] : "i32.clz",
-        I32Ctz : [0x68] : "i32.ctz",
-        I32Popcnt : [0x69] : "i32.popcnt",
-        I32Add : [0x6a] : "i32.add",
-        I32Sub : [0x6b] : "i32.sub",
-        I32Mul : [0x6c] : "i32.mul",
-        I32DivS : [0x6d] : "i32.div_s",
-        I32DivU : [0x6e] : "i32.div_u",
-        I32RemS : [0x6f] : "i32.rem_s",
-        I32RemU : [0x70] : "i32.rem_u",
-        I32And : [0x71] : "i32.and",
-        I32Or : [0x72] : "i32.or",
-        I32Xor : [0x73] : "i32.xor",
-        I32Shl : [0x74] : "i32.shl",
-        I32ShrS : [0x75] : "i32.shr_s",
-        I32ShrU : [0x76] : "i32.shr_u",
-        I32Rotl : [0x77] : "i32.rotl",
-        I32Rotr : [0x78] : "i32.rotr",
-
-        I64Clz : [0x79] : "i64.clz",
-        I64Ctz : [0x7a] : "i64.ctz",
-        I64Popcnt : [0x7b] : "i64.popcnt",
-        I64Add : [0x7c] : "i64.add",
-        I64Sub : [0x7d] : "i64.sub",
-        I64Mul : [0x7e] : "i64.mul",
-        I64DivS : [0x7f] : "i64.div_s",
-        I64DivU : [0x80] : "i64.div_u",
-        I64RemS : [0x81] : "i64.rem_s",
-        I64RemU : [0x82] : "i64.rem_u",
-        I64And : [0x83] : "i64.and",
-        I64Or : [0x84] : "i64.or",
-        I64Xor : [0x85] : "i64.xor",
-        I64Shl : [0x86] : "i64.shl",
-        I64ShrS : [0x87] : "i64.shr_s",
-        I64ShrU : [0x88] : "i64.shr_u",
-        I64Rotl : [0x89] : "i64.rotl",
-        I64Rotr : [0x8a] : "i64.rotr",
-
-        F32Abs : [0x8b] : "f32.abs",
-        F32Neg : [0x8c] : "f32.neg",
-        F32Ceil : [0x8d] : "f32.ceil",
-        F32Floor : [0x8e] : "f32.floor",
-        F32Trunc : [0x8f] : "f32.trunc",
-        F32Nearest : [0x90] : "f32.nearest",
-        F32Sqrt : [0x91] : "f32.sqrt",
-        F32Add : [0x92] : "f32.add",
-        F32Sub : [0x93] : "f32.sub",
-        F32Mul : [0x94] : "f32.mul",
-        F32Div : [0x95] : "f32.div",
-        F32Min : [0x96] : "f32.min",
-        F32Max : [0x97] : "f32.max",
-        F32Copysign : [0x98] : "f32.copysign",
-
-        F64Abs : [0x99] : "f64.abs",
-        F64Neg : [0x9a] : "f64.neg",
-        F64Ceil : [0x9b] : "f64.ceil",
-        F64Floor : [0x9c] : "f64.floor",
-        F64Trunc : [0x9d] : "f64.trunc",
-        F64Nearest : [0x9e] : "f64.nearest",
-        F64Sqrt : [0x9f] : "f64.sqrt",
-        F64Add : [0xa0] : "f64.add",
-        F64Sub : [0xa1] : "f64.sub",
-        F64Mul : [0xa2] : "f64.mul",
-        F64Div : [0xa3] : "f64.div",
-        F64Min : [0xa4] : "f64.min",
-        F64Max : [0xa5] : "f64.max",
-        F64Copysign : [0xa6] : "f64.copysign",
-
-        I32Eqz : [0x45] : "i32.eqz",
-        I32Eq : [0x46] : "i32.eq",
-        I32Ne : [0x47] : "i32.ne",
-        I32LtS : [0x48] : "i32.lt_s",
-        I32LtU : [0x49] : "i32.lt_u",
-        I32GtS : [0x4a] : "i32.gt_s",
-        I32GtU : [0x4b] : "i32.gt_u",
-        I32LeS : [0x4c] : "i32.le_s",
-        I32LeU : [0x4d] : "i32.le_u",
-        I32GeS : [0x4e] : "i32.ge_s",
-        I32GeU : [0x4f] : "i32.ge_u",
-
-        I64Eqz : [0x50] : "i64.eqz",
-        I64Eq : [0x51] : "i64.eq",
-        I64Ne : [0x52] : "i64.ne",
-        I64LtS : [0x53] : "i64.lt_s",
-        I64LtU : [0x54] : "i64.lt_u",
-        I64GtS : [0x55] : "i64.gt_s",
-        I64GtU : [0x56] : "i64.gt_u",
-        I64LeS : [0x57] : "i64.le_s",
-        I64LeU : [0x58] : "i64.le_u",
-        I64GeS : [0x59] : "i64.ge_s",
-        I64GeU : [0x5a] : "i64.ge_u",
-
-        F32Eq : [0x5b] : "f32.eq",
-        F32Ne : [0x5c] : "f32.ne",
-        F32Lt : [0x5d] : "f32.lt",
-        F32Gt : [0x5e] : "f32.gt",
-        F32Le : [0x5f] : "f32.le",
-        F32Ge : [0x60] : "f32.ge",
-
-        F64Eq : [0x61] : "f64.eq",
-        F64Ne : [0x62] : "f64.ne",
-        F64Lt : [0x63] : "f64.lt",
-        F64Gt : [0x64] : "f64.gt",
-        F64Le : [0x65] : "f64.le",
-        F64Ge : [0x66] : "f64.ge",
-
-        I32WrapI64 : [0xa7] : "i32.wrap_i64" | "i32.wrap/i64",
-        I32TruncF32S : [0xa8] : "i32.trunc_f32_s" | "i32.trunc_s/f32",
-        I32TruncF32U : [0xa9] : "i32.trunc_f32_u" | "i32.trunc_u/f32",
-        I32TruncF64S : [0xaa] : "i32.trunc_f64_s" | "i32.trunc_s/f64",
-        I32TruncF64U : [0xab] : "i32.trunc_f64_u" | "i32.trunc_u/f64",
-        I64ExtendI32S : [0xac] : "i64.extend_i32_s" | "i64.extend_s/i32",
-        I64ExtendI32U : [0xad] : "i64.extend_i32_u" | "i64.extend_u/i32",
-        I64TruncF32S : [0xae] : "i64.trunc_f32_s" | "i64.trunc_s/f32",
-        I64TruncF32U : [0xaf] : "i64.trunc_f32_u" | "i64.trunc_u/f32",
-        I64TruncF64S : [0xb0] : "i64.trunc_f64_s" | "i64.trunc_s/f64",
-        I64TruncF64U : [0xb1] : "i64.trunc_f64_u" | "i64.trunc_u/f64",
-        F32ConvertI32S : [0xb2] : "f32.convert_i32_s" | "f32.convert_s/i32",
-        F32ConvertI32U : [0xb3] : "f32.convert_i32_u" | "f32.convert_u/i32",
-        F32ConvertI64S : [0xb4] : "f32.convert_i64_s" | "f32.convert_s/i64",
-        F32ConvertI64U : [0xb5] : "f32.convert_i64_u" | "f32.convert_u/i64",
-        F32DemoteF64 : [0xb6] : "f32.demote_f64" | "f32.demote/f64",
-        F64ConvertI32S : [0xb7] : "f64.convert_i32_s" | "f64.convert_s/i32",
-        F64ConvertI32U : [0xb8] : "f64.convert_i32_u" | "f64.convert_u/i32",
-        F64ConvertI64S : [0xb9] : "f64.convert_i64_s" | "f64.convert_s/i64",
-        F64ConvertI64U : [0xba] : "f64.convert_i64_u" | "f64.convert_u/i64",
-        F64PromoteF32 : [0xbb] : "f64.promote_f32" | "f64.promote/f32",
-        I32ReinterpretF32 : [0xbc] : "i32.reinterpret_f32" | "i32.reinterpret/f32",
-        I64ReinterpretF64 : [0xbd] : "i64.reinterpret_f64" | "i64.reinterpret/f64",
-        F32ReinterpretI32 : [0xbe] : "f32.reinterpret_i32" | "f32.reinterpret/i32",
-        F64ReinterpretI64 : [0xbf] : "f64.reinterpret_i64" | "f64.reinterpret/i64",
-
-        // non-trapping float to int
-        I32TruncSatF32S : [0xfc, 0x00] : "i32.trunc_sat_f32_s" | "i32.trunc_s:sat/f32",
-        I32TruncSatF32U : [0xfc, 0x01] : "i32.trunc_sat_f32_u" | "i32.trunc_u:sat/f32",
-        I32TruncSatF64S : [0xfc, 0x02] : "i32.trunc_sat_f64_s" | "i32.trunc_s:sat/f64",
-        I32TruncSatF64U : [0xfc, 0x03] : "i32.trunc_sat_f64_u" | "i32.trunc_u:sat/f64",
-        I64TruncSatF32S : [0xfc, 0x04] : "i64.trunc_sat_f32_s" | "i64.trunc_s:sat/f32",
-        I64TruncSatF32U : [0xfc, 0x05] : "i64.trunc_sat_f32_u" | "i64.trunc_u:sat/f32",
-        I64TruncSatF64S : [0xfc, 0x06] : "i64.trunc_sat_f64_s" | "i64.trunc_s:sat/f64",
-        I64TruncSatF64U : [0xfc, 0x07] : "i64.trunc_sat_f64_u" | "i64.trunc_u:sat/f64",
-
-        // sign extension proposal
-        I32Extend8S : [0xc0] : "i32.extend8_s",
-        I32Extend16S : [0xc1] : "i32.extend16_s",
-        I64Extend8S : [0xc2] : "i64.extend8_s",
-        I64Extend16S : [0xc3] : "i64.extend16_s",
-        I64Extend32S : [0xc4] : "i64.extend32_s",
-
-        // atomics proposal
-        AtomicNotify(MemArg<4>) : [0xfe, 0x00] : "atomic.notify",
-        I32AtomicWait(MemArg<4>) : [0xfe, 0x01] : "i32.atomic.wait",
-        I64AtomicWait(MemArg<8>) : [0xfe, 0x02] : "i64.atomic.wait",
-        AtomicFence : [0xfe, 0x03] : "atomic.fence",
-
-        I32AtomicLoad(MemArg<4>) : [0xfe, 0x10] : "i32.atomic.load",
-        I64AtomicLoad(MemArg<8>) : [0xfe, 0x11] : "i64.atomic.load",
-        I32AtomicLoad8u(MemArg<1>) : [0xfe, 0x12] : "i32.atomic.load8_u",
-        I32AtomicLoad16u(MemArg<2>) : [0xfe, 0x13] : "i32.atomic.load16_u",
-        I64AtomicLoad8u(MemArg<1>) : [0xfe, 0x14] : "i64.atomic.load8_u",
-        I64AtomicLoad16u(MemArg<2>) : [0xfe, 0x15] : "i64.atomic.load16_u",
-        I64AtomicLoad32u(MemArg<4>) : [0xfe, 0x16] : "i64.atomic.load32_u",
-        I32AtomicStore(MemArg<4>) : [0xfe, 0x17] : "i32.atomic.store",
-        I64AtomicStore(MemArg<8>) : [0xfe, 0x18] : "i64.atomic.store",
-        I32AtomicStore8(MemArg<1>) : [0xfe, 0x19] : "i32.atomic.store8",
-        I32AtomicStore16(MemArg<2>) : [0xfe, 0x1a] : "i32.atomic.store16",
-        I64AtomicStore8(MemArg<1>) : [0xfe, 0x1b] : "i64.atomic.store8",
-        I64AtomicStore16(MemArg<2>) : [0xfe, 0x1c] : "i64.atomic.store16",
-        I64AtomicStore32(MemArg<4>) : [0xfe, 0x1d] : "i64.atomic.store32",
-
-        I32AtomicRmwAdd(MemArg<4>) : [0xfe, 0x1e] : "i32.atomic.rmw.add",
-        I64AtomicRmwAdd(MemArg<8>) : [0xfe, 0x1f] : "i64.atomic.rmw.add",
-        I32AtomicRmw8AddU(MemArg<1>) : [0xfe, 0x20] : "i32.atomic.rmw8.add_u",
-        I32AtomicRmw16AddU(MemArg<2>) : [0xfe, 0x21] : "i32.atomic.rmw16.add_u",
-        I64AtomicRmw8AddU(MemArg<1>) : [0xfe, 0x22] : "i64.atomic.rmw8.add_u",
-        I64AtomicRmw16AddU(MemArg<2>) : [0xfe, 0x23] : "i64.atomic.rmw16.add_u",
-        I64AtomicRmw32AddU(MemArg<4>) : [0xfe, 0x24] : "i64.atomic.rmw32.add_u",
-
-        I32AtomicRmwSub(MemArg<4>) : [0xfe, 0x25] : "i32.atomic.rmw.sub",
-        I64AtomicRmwSub(MemArg<8>) : [0xfe, 0x26] : "i64.atomic.rmw.sub",
-        I32AtomicRmw8SubU(MemArg<1>) : [0xfe, 0x27] : "i32.atomic.rmw8.sub_u",
-        I32AtomicRmw16SubU(MemArg<2>) : [0xfe, 0x28] : "i32.atomic.rmw16.sub_u",
-        I64AtomicRmw8SubU(MemArg<1>) : [0xfe, 0x29] : "i64.atomic.rmw8.sub_u",
-        I64AtomicRmw16SubU(MemArg<2>) : [0xfe, 0x2a] : "i64.atomic.rmw16.sub_u",
-        I64AtomicRmw32SubU(MemArg<4>) : [0xfe, 0x2b] : "i64.atomic.rmw32.sub_u",
-
-        I32AtomicRmwAnd(MemArg<4>) : [0xfe, 0x2c] : "i32.atomic.rmw.and",
-        I64AtomicRmwAnd(MemArg<8>) : [0xfe, 0x2d] : "i64.atomic.rmw.and",
-        I32AtomicRmw8AndU(MemArg<1>) : [0xfe, 0x2e] : "i32.atomic.rmw8.and_u",
-        I32AtomicRmw16AndU(MemArg<2>) : [0xfe, 0x2f] : "i32.atomic.rmw16.and_u",
-        I64AtomicRmw8AndU(MemArg<1>) : [0xfe, 0x30] : "i64.atomic.rmw8.and_u",
-        I64AtomicRmw16AndU(MemArg<2>) : [0xfe, 0x31] : "i64.atomic.rmw16.and_u",
-        I64AtomicRmw32AndU(MemArg<4>) : [0xfe, 0x32] : "i64.atomic.rmw32.and_u",
-
-        I32AtomicRmwOr(MemArg<4>) : [0xfe, 0x33] : "i32.atomic.rmw.or",
-        I64AtomicRmwOr(MemArg<8>) : [0xfe, 0x34] : "i64.atomic.rmw.or",
-        I32AtomicRmw8OrU(MemArg<1>) : [0xfe, 0x35] : "i32.atomic.rmw8.or_u",
-        I32AtomicRmw16OrU(MemArg<2>) : [0xfe, 0x36] : "i32.atomic.rmw16.or_u",
-        I64AtomicRmw8OrU(MemArg<1>) : [0xfe, 0x37] : "i64.atomic.rmw8.or_u",
-        I64AtomicRmw16OrU(MemArg<2>) : [0xfe, 0x38] : "i64.atomic.rmw16.or_u",
-        I64AtomicRmw32OrU(MemArg<4>) : [0xfe, 0x39] : "i64.atomic.rmw32.or_u",
-
-        I32AtomicRmwXor(MemArg<4>) : [0xfe, 0x3a] : "i32.atomic.rmw.xor",
-        I64AtomicRmwXor(MemArg<8>) : [0xfe, 0x3b] : "i64.atomic.rmw.xor",
-        I32AtomicRmw8XorU(MemArg<1>) : [0xfe, 0x3c] : "i32.atomic.rmw8.xor_u",
-        I32AtomicRmw16XorU(MemArg<2>) : [0xfe, 0x3d] : "i32.atomic.rmw16.xor_u",
-        I64AtomicRmw8XorU(MemArg<1>) : [0xfe, 0x3e] : "i64.atomic.rmw8.xor_u",
-        I64AtomicRmw16XorU(MemArg<2>) : [0xfe, 0x3f] : "i64.atomic.rmw16.xor_u",
-        I64AtomicRmw32XorU(MemArg<4>) : [0xfe, 0x40] : "i64.atomic.rmw32.xor_u",
-
-        I32AtomicRmwXchg(MemArg<4>) : [0xfe, 0x41] : "i32.atomic.rmw.xchg",
-        I64AtomicRmwXchg(MemArg<8>) : [0xfe, 0x42] : "i64.atomic.rmw.xchg",
-        I32AtomicRmw8XchgU(MemArg<1>) : [0xfe, 0x43] : "i32.atomic.rmw8.xchg_u",
-        I32AtomicRmw16XchgU(MemArg<2>) : [0xfe, 0x44] : "i32.atomic.rmw16.xchg_u",
-        I64AtomicRmw8XchgU(MemArg<1>) : [0xfe, 0x45] : "i64.atomic.rmw8.xchg_u",
-        I64AtomicRmw16XchgU(MemArg<2>) : [0xfe, 0x46] : "i64.atomic.rmw16.xchg_u",
-        I64AtomicRmw32XchgU(MemArg<4>) : [0xfe, 0x47] : "i64.atomic.rmw32.xchg_u",
-
-        I32AtomicRmwCmpxchg(MemArg<4>) : [0xfe, 0x48] : "i32.atomic.rmw.cmpxchg",
-        I64AtomicRmwCmpxchg(MemArg<8>) : [0xfe, 0x49] : "i64.atomic.rmw.cmpxchg",
-        I32AtomicRmw8CmpxchgU(MemArg<1>) : [0xfe, 0x4a] : "i32.atomic.rmw8.cmpxchg_u",
-        I32AtomicRmw16CmpxchgU(MemArg<2>) : [0xfe, 0x4b] : "i32.atomic.rmw16.cmpxchg_u",
-        I64AtomicRmw8CmpxchgU(MemArg<1>) : [0xfe, 0x4c] : "i64.atomic.rmw8.cmpxchg_u",
-        I64AtomicRmw16CmpxchgU(MemArg<2>) : [0xfe, 0x4d] : "i64.atomic.rmw16.cmpxchg_u",
-        I64AtomicRmw32CmpxchgU(MemArg<4>) : [0xfe, 0x4e] : "i64.atomic.rmw32.cmpxchg_u",
-
-        V128Load(MemArg<16>) : [0xfd, 0x00] : "v128.load",
-        V128Store(MemArg<16>) : [0xfd, 0x01] : "v128.store",
-        V128Const(V128Const) : [0xfd, 0x02] : "v128.const",
-
-        I8x16Splat : [0xfd, 0x04] : "i8x16.splat",
-        I8x16ExtractLaneS(i32) : [0xfd, 0x05] : "i8x16.extract_lane_s",
-        I8x16ExtractLaneU(i32) : [0xfd, 0x06] : "i8x16.extract_lane_u",
-        I8x16ReplaceLane(i32) : [0xfd, 0x07] : "i8x16.replace_lane",
-        I16x8Splat : [0xfd, 0x08] : "i16x8.splat",
-        I16x8ExtractLaneS(i32) : [0xfd, 0x09] : "i16x8.extract_lane_s",
-        I16x8ExtractLaneU(i32) : [0xfd, 0x0a] : "i16x8.extract_lane_u",
-        I16x8ReplaceLane(i32) : [0xfd, 0x0b] : "i16x8.replace_lane",
-        I32x4Splat : [0xfd, 0x0c] : "i32x4.splat",
-        I32x4ExtractLane(i32) : [0xfd, 0x0d] : "i32x4.extract_lane",
-        I32x4ReplaceLane(i32) : [0xfd, 0x0e] : "i32x4.replace_lane",
-        I64x2Splat : [0xfd, 0x0f] : "i64x2.splat",
-        I64x2ExtractLane(i32) : [0xfd, 0x10] : "i64x2.extract_lane",
-        I64x2ReplaceLane(i32) : [0xfd, 0x11] : "i64x2.replace_lane",
-        F32x4Splat : [0xfd, 0x12] : "f32x4.splat",
-        F32x4ExtractLane(i32) : [0xfd, 0x13] : "f32x4.extract_lane",
-        F32x4ReplaceLane(i32) : [0xfd, 0x14] : "f32x4.replace_lane",
-        F64x2Splat : [0xfd, 0x15] : "f64x2.splat",
-        F64x2ExtractLane(i32) : [0xfd, 0x16] : "f64x2.extract_lane",
-        F64x2ReplaceLane(i32) : [0xfd, 0x17] : "f64x2.replace_lane",
-
-        I8x16Eq : [0xfd, 0x18] : "i8x16.eq",
-        I8x16Ne : [0xfd, 0x19] : "i8x16.ne",
-        I8x16LtS : [0xfd, 0x1a] : "i8x16.lt_s",
-        I8x16LtU : [0xfd, 0x1b] : "i8x16.lt_u",
-        I8x16GtS : [0xfd, 0x1c] : "i8x16.gt_s",
-        I8x16GtU : [0xfd, 0x1d] : "i8x16.gt_u",
-        I8x16LeS : [0xfd, 0x1e] : "i8x16.le_s",
-        I8x16LeU : [0xfd, 0x1f] : "i8x16.le_u",
-        I8x16GeS : [0xfd, 0x20] : "i8x16.ge_s",
-        I8x16GeU : [0xfd, 0x21] : "i8x16.ge_u",
-        I16x8Eq : [0xfd, 0x22] : "i16x8.eq",
-        I16x8Ne : [0xfd, 0x23] : "i16x8.ne",
-        I16x8LtS : [0xfd, 0x24] : "i16x8.lt_s",
-        I16x8LtU : [0xfd, 0x25] : "i16x8.lt_u",
-        I16x8GtS : [0xfd, 0x26] : "i16x8.gt_s",
-        I16x8GtU : [0xfd, 0x27] : "i16x8.gt_u",
-        I16x8LeS : [0xfd, 0x28] : "i16x8.le_s",
-        I16x8LeU : [0xfd, 0x29] : "i16x8.le_u",
-        I16x8GeS : [0xfd, 0x2a] : "i16x8.ge_s",
-        I16x8GeU : [0xfd, 0x2b] : "i16x8.ge_u",
-        I32x4Eq : [0xfd, 0x2c] : "i32x4.eq",
-        I32x4Ne : [0xfd, 0x2d] : "i32x4.ne",
-        I32x4LtS : [0xfd, 0x2e] : "i32x4.lt_s",
-        I32x4LtU : [0xfd, 0x2f] : "i32x4.lt_u",
-        I32x4GtS : [0xfd, 0x30] : "i32x4.gt_s",
-        I32x4GtU : [0xfd, 0x31] : "i32x4.gt_u",
-        I32x4LeS : [0xfd, 0x32] : "i32x4.le_s",
-        I32x4LeU : [0xfd, 0x33] : "i32x4.le_u",
-        I32x4GeS : [0xfd, 0x34] : "i32x4.ge_s",
-        I32x4GeU : [0xfd, 0x35] : "i32x4.ge_u",
-
-        F32x4Eq : [0xfd, 0x40] : "f32x4.eq",
-        F32x4Ne : [0xfd, 0x41] : "f32x4.ne",
-        F32x4Lt : [0xfd, 0x42] : "f32x4.lt",
-        F32x4Gt : [0xfd, 0x43] : "f32x4.gt",
-        F32x4Le : [0xfd, 0x44] : "f32x4.le",
-        F32x4Ge : [0xfd, 0x45] : "f32x4.ge",
-        F64x2Eq : [0xfd, 0x46] : "f64x2.eq",
-        F64x2Ne : [0xfd, 0x47] : "f64x2.ne",
-        F64x2Lt : [0xfd, 0x48] : "f64x2.lt",
-        F64x2Gt : [0xfd, 0x49] : "f64x2.gt",
-        F64x2Le : [0xfd, 0x4a] : "f64x2.le",
-        F64x2Ge : [0xfd, 0x4b] : "f64x2.ge",
-
-        V128Not : [0xfd, 0x4c] : "v128.not",
-        V128And : [0xfd, 0x4d] : "v128.and",
-        V128Or : [0xfd, 0x4e] : "v128.or",
-        V128Xor : [0xfd, 0x4f] : "v128.xor",
-        V128Bitselect : [0xfd, 0x50] : "v128.bitselect",
-
-        I8x16Neg : [0xfd, 0x51] : "i8x16.neg",
-        I8x16AnyTrue : [0xfd, 0x52] : "i8x16.any_true",
-        I8x16AllTrue : [0xfd, 0x53] : "i8x16.all_true",
-        I8x16Shl : [0xfd, 0x54] : "i8x16.shl",
-        I8x16ShrS : [0xfd, 0x55] : "i8x16.shr_s",
-        I8x16ShrU : [0xfd, 0x56] : "i8x16.shr_u",
-        I8x16Add : [0xfd, 0x57] : "i8x16.add",
-        I8x16AddSaturateS : [0xfd, 0x58] : "i8x16.add_saturate_s",
-        I8x16AddSaturateU : [0xfd, 0x59] : "i8x16.add_saturate_u",
-        I8x16Sub : [0xfd, 0x5a] : "i8x16.sub",
-        I8x16SubSaturateS : [0xfd, 0x5b] : "i8x16.sub_saturate_s",
-        I8x16SubSaturateU : [0xfd, 0x5c] : "i8x16.sub_saturate_u",
-        I8x16Mul : [0xfd, 0x5d] : "i8x16.mul",
-
-        I16x8Neg : [0xfd, 0x62] : "i16x8.neg",
-        I16x8AnyTrue : [0xfd, 0x63] : "i16x8.any_true",
-        I16x8AllTrue : [0xfd, 0x64] : "i16x8.all_true",
-        I16x8Shl : [0xfd, 0x65] : "i16x8.shl",
-        I16x8ShrS : [0xfd, 0x66] : "i16x8.shr_s",
-        I16x8ShrU : [0xfd, 0x67] : "i16x8.shr_u",
-        I16x8Add : [0xfd, 0x68] : "i16x8.add",
-        I16x8AddSaturateS : [0xfd, 0x69] : "i16x8.add_saturate_s",
-        I16x8AddSaturateU : [0xfd, 0x6a] : "i16x8.add_saturate_u",
-        I16x8Sub : [0xfd, 0x6b] : "i16x8.sub",
-        I16x8SubSaturateS : [0xfd, 0x6c] : "i16x8.sub_saturate_s",
-        I16x8SubSaturateU : [0xfd, 0x6d] : "i16x8.sub_saturate_u",
-        I16x8Mul : [0xfd, 0x6e] : "i16x8.mul",
-
-        I32x4Neg : [0xfd, 0x73] : "i32x4.neg",
-        I32x4AnyTrue : [0xfd, 0x74] : "i32x4.any_true",
-        I32x4AllTrue : [0xfd, 0x75] : "i32x4.all_true",
-        I32x4Shl : [0xfd, 0x76] : "i32x4.shl",
-        I32x4ShrS : [0xfd, 0x77] : "i32x4.shr_s",
-        I32x4ShrU : [0xfd, 0x78] : "i32x4.shr_u",
-        I32x4Add : [0xfd, 0x79] : "i32x4.add",
-        I32x4Sub : [0xfd, 0x7c] : "i32x4.sub",
-        I32x4Mul : [0xfd, 0x7f] : "i32x4.mul",
-
-        I64x2Neg : [0xfd, 0x84] : "i64x2.neg",
-        I64x2AnyTrue : [0xfd, 0x85] : "i64x2.any_true",
-        I64x2AllTrue : [0xfd, 0x86] : "i64x2.all_true",
-        I64x2Shl : [0xfd, 0x87] : "i64x2.shl",
-        I64x2ShrS : [0xfd, 0x88] : "i64x2.shr_s",
-        I64x2ShrU : [0xfd, 0x89] : "i64x2.shr_u",
-        I64x2Add : [0xfd, 0x8a] : "i64x2.add",
-        I64x2Sub : [0xfd, 0x8d] : "i64x2.sub",
-        I64x2Mul : [0xfd, 0x90] : "i64x2.mul",
-
-        F32x4Abs : [0xfd, 0x95] : "f32x4.abs",
-        F32x4Neg : [0xfd, 0x96] : "f32x4.neg",
-        F32x4Sqrt : [0xfd, 0x97] : "f32x4.sqrt",
-        F32x4Add : [0xfd, 0x9a] : "f32x4.add",
-        F32x4Sub : [0xfd, 0x9b] : "f32x4.sub",
-        F32x4Mul : [0xfd, 0x9c] : "f32x4.mul",
-        F32x4Div : [0xfd, 0x9d] : "f32x4.div",
-        F32x4Min : [0xfd, 0x9e] : "f32x4.min",
-        F32x4Max : [0xfd, 0x9f] : "f32x4.max",
-
-        F64x2Abs : [0xfd, 0xa0] : "f64x2.abs",
-        F64x2Neg : [0xfd, 0xa1] : "f64x2.neg",
-        F64x2Sqrt : [0xfd, 0xa2] : "f64x2.sqrt",
-        F64x2Add : [0xfd, 0xa5] : "f64x2.add",
-        F64x2Sub : [0xfd, 0xa6] : "f64x2.sub",
-        F64x2Mul : [0xfd, 0xa7] : "f64x2.mul",
-        F64x2Div : [0xfd, 0xa8] : "f64x2.div",
-        F64x2Min : [0xfd, 0xa9] : "f64x2.min",
-        F64x2Max : [0xfd, 0xaa] : "f64x2.max",
-
-        I32x4TruncSatF32x4S : [0xfd, 0xab] : "i32x4.trunc_sat_f32x4_s",
-        I32x4TruncSatF32x4U : [0xfd, 0xac] : "i32x4.trunc_sat_f32x4_u",
-        I64x2TruncSatF64x2S : [0xfd, 0xad] : "i64x2.trunc_sat_f64x2_s",
-        I64x2TruncSatF64x2U : [0xfd, 0xae] : "i64x2.trunc_sat_f64x2_u",
-        F32x4ConvertI32x4S : [0xfd, 0xaf] : "f32x4.convert_i32x4_s",
-        F32x4ConvertI32x4U : [0xfd, 0xb0] : "f32x4.convert_i32x4_u",
-        F64x2ConvertI64x2S : [0xfd, 0xb1] : "f64x2.convert_i64x2_s",
-        F64x2ConvertI64x2U : [0xfd, 0xb2] : "f64x2.convert_i64x2_u",
-        V8x16Swizzle : [0xfd, 0xc0] : "v8x16.swizzle",
-        V8x16Shuffle(V8x16Shuffle) : [0xfd, 0xc1] : "v8x16.shuffle",
-        V8x16LoadSplat(MemArg<1>) : [0xfd, 0xc2] : "v8x16.load_splat",
-        V16x8LoadSplat(MemArg<2>) : [0xfd, 0xc3] : "v16x8.load_splat",
-        V32x4LoadSplat(MemArg<4>) : [0xfd, 0xc4] : "v32x4.load_splat",
-        V64x2LoadSplat(MemArg<8>) : [0xfd, 0xc5] : "v64x2.load_splat",
-
-        I8x16NarrowI16x8S : [0xfd, 0xc6] : "i8x16.narrow_i16x8_s",
-        I8x16NarrowI16x8U : [0xfd, 0xc7] : "i8x16.narrow_i16x8_u",
-        I16x8NarrowI32x4S : [0xfd, 0xc8] : "i16x8.narrow_i32x4_s",
-        I16x8NarrowI32x4U : [0xfd, 0xc9] : "i16x8.narrow_i32x4_u",
-
-        I16x8WidenLowI8x16S : [0xfd, 0xca] : "i16x8.widen_low_i8x16_s",
-        I16x8WidenHighI8x16S : [0xfd, 0xcb] : "i16x8.widen_high_i8x16_s",
-        I16x8WidenLowI8x16U : [0xfd, 0xcc] : "i16x8.widen_low_i8x16_u",
-        I16x8WidenHighI8x16u : [0xfd, 0xcd] : "i16x8.widen_high_i8x16_u",
-        I32x4WidenLowI16x8S : [0xfd, 0xce] : "i32x4.widen_low_i16x8_s",
-        I32x4WidenHighI16x8S : [0xfd, 0xcf] : "i32x4.widen_high_i16x8_s",
-        I32x4WidenLowI16x8U : [0xfd, 0xd0] : "i32x4.widen_low_i16x8_u",
-        I32x4WidenHighI16x8u : [0xfd, 0xd1] : "i32x4.widen_high_i16x8_u",
-
-        I16x8Load8x8S(MemArg<1>) : [0xfd, 0xd2] : "i16x8.load8x8_s",
-        I16x8Load8x8U(MemArg<1>) : [0xfd, 0xd3] : "i16x8.load8x8_u",
-        I32x4Load16x4S(MemArg<2>) : [0xfd, 0xd4] : "i32x4.load16x4_s",
-        I32x4Load16x4U(MemArg<2>) : [0xfd, 0xd5] : "i32x4.load16x4_u",
-        I64x2Load32x2S(MemArg<4>) : [0xfd, 0xd6] : "i64x2.load32x2_s",
-        I64x2Load32x2U(MemArg<4>) : [0xfd, 0xd7] : "i64x2.load32x2_u",
-        V128Andnot : [0xfd, 0xd8] : "v128.andnot",
-    }
+// The full listing of instructions is passed to the `$mac` macro, which is
+// used both to define `Instruction` below and the visitor traits in
+// `crate::visit`.
+//
+// Each instruction is listed with its immediate, its opcode bytes, its name
+// followed by any deprecated alias, the name of its visitor method, the
+// `Proposal` which introduced it followed by `memory` if it accesses the
+// default memory, and, if it doesn't depend on where the instruction is used,
+// the types it pops and pushes on the operand stack.
+macro_rules! for_each_instruction {
+    ($mac:ident) => ($mac! {
+        pub enum Instruction<'a> {
+            Block(BlockType<'a>) : [0x02] : "block" : visit_block : Mvp,
+            If(BlockType<'a>) : [0x04] : "if" : visit_if : Mvp,
+            Else(Option<ast::Id<'a>>) : [0x05] : "else" : visit_else : Mvp,
+            Loop(BlockType<'a>) : [0x03] : "loop" : visit_loop : Mvp,
+            End(Option<ast::Id<'a>>) : [0x0b] : "end" : visit_end : Mvp,
+
+            Unreachable : [0x00] : "unreachable" : visit_unreachable : Mvp,
+            Nop : [0x01] : "nop" : visit_nop : Mvp,
+            Br(ast::Index<'a>) : [0x0c] : "br" : visit_br : Mvp,
+            BrIf(ast::Index<'a>) : [0x0d] : "br_if" : visit_br_if : Mvp,
+            BrTable(BrTableIndices<'a>) : [0x0e] : "br_table" : visit_br_table : Mvp,
+            Return : [0x0f] : "return" : visit_return : Mvp,
+            Call(ast::Index<'a>) : [0x10] : "call" : visit_call : Mvp,
+            CallIndirect(CallIndirect<'a>) : [0x11] : "call_indirect" : visit_call_indirect : Mvp,
+            ReturnCall(ast::Index<'a>) : [0x12] : "return_call" : visit_return_call : TailCall,
+            ReturnCallIndirect(CallIndirect<'a>) : [0x13] : "return_call_indirect" : visit_return_call_indirect : TailCall,
+            Drop : [0x1a] : "drop" : visit_drop : Mvp,
+            Select(SelectTypes) : [] : "select" : visit_select : Mvp,
+            LocalGet(ast::Index<'a>) : [0x20] : "local.get" | "get_local" : visit_local_get : Mvp,
+            LocalSet(ast::Index<'a>) : [0x21] : "local.set" | "set_local" : visit_local_set : Mvp,
+            LocalTee(ast::Index<'a>) : [0x22] : "local.tee" | "tee_local" : visit_local_tee : Mvp,
+            GlobalGet(ast::Index<'a>) : [0x23] : "global.get" | "get_global" : visit_global_get : Mvp,
+            GlobalSet(ast::Index<'a>) : [0x24] : "global.set" | "set_global" : visit_global_set : Mvp,
+
+            TableGet(ast::Index<'a>) : [0x25] : "table.get" : visit_table_get : ReferenceTypes,
+            TableSet(ast::Index<'a>) : [0x26] : "table.set" : visit_table_set : ReferenceTypes,
+
+            I32Load(MemArg<4>) : [0x28] : "i32.load" : visit_i32_load : Mvp memory : [i32] -> [i32],
+            I64Load(MemArg<8>) : [0x29] : "i64.load" : visit_i64_load : Mvp memory : [i32] -> [i64],
+            F32Load(MemArg<4>) : [0x2a] : "f32.load" : visit_f32_load : Mvp memory : [i32] -> [f32],
+            F64Load(MemArg<8>) : [0x2b] : "f64.load" : visit_f64_load : Mvp memory : [i32] -> [f64],
+            I32Load8s(MemArg<1>) : [0x2c] : "i32.load8_s" : visit_i32_load8_s : Mvp memory : [i32] -> [i32],
+            I32Load8u(MemArg<1>) : [0x2d] : "i32.load8_u" : visit_i32_load8_u : Mvp memory : [i32] -> [i32],
+            I32Load16s(MemArg<2>) : [0x2e] : "i32.load16_s" : visit_i32_load16_s : Mvp memory : [i32] -> [i32],
+            I32Load16u(MemArg<2>) : [0x2f] : "i32.load16_u" : visit_i32_load16_u : Mvp memory : [i32] -> [i32],
+            I64Load8s(MemArg<1>) : [0x30] : "i64.load8_s" : visit_i64_load8_s : Mvp memory : [i32] -> [i64],
+            I64Load8u(MemArg<1>) : [0x31] : "i64.load8_u" : visit_i64_load8_u : Mvp memory : [i32] -> [i64],
+            I64Load16s(MemArg<2>) : [0x32] : "i64.load16_s" : visit_i64_load16_s : Mvp memory : [i32] -> [i64],
+            I64Load16u(MemArg<2>) : [0x33] : "i64.load16_u" : visit_i64_load16_u : Mvp memory : [i32] -> [i64],
+            I64Load32s(MemArg<4>) : [0x34] : "i64.load32_s" : visit_i64_load32_s : Mvp memory : [i32] -> [i64],
+            I64Load32u(MemArg<4>) : [0x35] : "i64.load32_u" : visit_i64_load32_u : Mvp memory : [i32] -> [i64],
+            I32Store(MemArg<4>) : [0x36] : "i32.store" : visit_i32_store : Mvp memory : [i32 i32] -> [],
+            I64Store(MemArg<8>) : [0x37] : "i64.store" : visit_i64_store : Mvp memory : [i32 i64] -> [],
+            F32Store(MemArg<4>) : [0x38] : "f32.store" : visit_f32_store : Mvp memory : [i32 f32] -> [],
+            F64Store(MemArg<8>) : [0x39] : "f64.store" : visit_f64_store : Mvp memory : [i32 f64] -> [],
+            I32Store8(MemArg<1>) : [0x3a] : "i32.store8" : visit_i32_store8 : Mvp memory : [i32 i32] -> [],
+            I32Store16(MemArg<2>) : [0x3b] : "i32.store16" : visit_i32_store16 : Mvp memory : [i32 i32] -> [],
+            I64Store8(MemArg<1>) : [0x3c] : "i64.store8" : visit_i64_store8 : Mvp memory : [i32 i64] -> [],
+            I64Store16(MemArg<2>) : [0x3d] : "i64.store16" : visit_i64_store16 : Mvp memory : [i32 i64] -> [],
+            I64Store32(MemArg<4>) : [0x3e] : "i64.store32" : visit_i64_store32 : Mvp memory : [i32 i64] -> [],
+
+            // Lots of bulk memory proposal here as well
+            MemorySize : [0x3f, 0x00] : "memory.size" | "current_memory" : visit_memory_size : Mvp memory : [] -> [i32],
+            MemoryGrow : [0x40, 0x00] : "memory.grow" | "grow_memory" : visit_memory_grow : Mvp memory : [i32] -> [i32],
+            MemoryInit(MemoryInit<'a>) : [0xfc, 0x08] : "memory.init" : visit_memory_init : BulkMemory memory : [i32 i32 i32] -> [],
+            MemoryCopy : [0xfc, 0x0a, 0x00, 0x00] : "memory.copy" : visit_memory_copy : BulkMemory memory : [i32 i32 i32] -> [],
+            MemoryFill : [0xfc, 0x0b, 0x00] : "memory.fill" : visit_memory_fill : BulkMemory memory : [i32 i32 i32] -> [],
+            DataDrop(ast::Index<'a>) : [0xfc, 0x09] : "data.drop" : visit_data_drop : BulkMemory : [] -> [],
+            ElemDrop(ast::Index<'a>) : [0xfc, 0x0d] : "elem.drop" : visit_elem_drop : BulkMemory : [] -> [],
+            TableInit(TableInit<'a>) : [0xfc, 0x0c] : "table.init" : visit_table_init : BulkMemory : [i32 i32 i32] -> [],
+            TableCopy : [0xfc, 0x0e, 0x00, 0x00] : "table.copy" : visit_table_copy : BulkMemory : [i32 i32 i32] -> [],
+            TableFill(ast::Index<'a>) : [0xfc, 0x11] : "table.fill" : visit_table_fill : ReferenceTypes,
+            TableSize(ast::Index<'a>) : [0xfc, 0x10] : "table.size" : visit_table_size : ReferenceTypes : [] -> [i32],
+            TableGrow(ast::Index<'a>) : [0xfc, 0x0f] : "table.grow" : visit_table_grow : ReferenceTypes,
+
+            RefNull : [0xd0] : "ref.null" : visit_ref_null : ReferenceTypes : [] -> [nullref],
+            RefIsNull : [0xd1] : "ref.is_null" : visit_ref_is_null : ReferenceTypes : [anyref] -> [i32],
+            RefHost(u32) : [0xff] : "ref.host" : visit_ref_host : ReferenceTypes : [] -> [anyref], // only used in test harness
+            RefFunc(ast::Index<'a>) : [0xd2] : "ref.func" : visit_ref_func : ReferenceTypes : [] -> [funcref], // only used in test harness
+
+            I32Const(i32) : [0x41] : "i32.const" : visit_i32_const : Mvp : [] -> [i32],
+            I64Const(i64) : [0x42] : "i64.const" : visit_i64_const : Mvp : [] -> [i64],
+            F32Const(ast::Float32) : [0x43] : "f32.const" : visit_f32_const : Mvp : [] -> [f32],
+            F64Const(ast::Float64) : [0x44] : "f64.const" : visit_f64_const : Mvp : [] -> [f64],
+
+            I32Clz : [0x67] : "i32.clz" : visit_i32_clz : Mvp : [i32] -> [i32],
+            I32Ctz : [0x68] : "i32.ctz" : visit_i32_ctz : Mvp : [i32] -> [i32],
+            I32Popcnt : [0x69] : "i32.popcnt" : visit_i32_popcnt : Mvp : [i32] -> [i32],
+            I32Add : [0x6a] : "i32.add" : visit_i32_add : Mvp : [i32 i32] -> [i32],
+            I32Sub : [0x6b] : "i32.sub" : visit_i32_sub : Mvp : [i32 i32] -> [i32],
+            I32Mul : [0x6c] : "i32.mul" : visit_i32_mul : Mvp : [i32 i32] -> [i32],
+            I32DivS : [0x6d] : "i32.div_s" : visit_i32_div_s : Mvp : [i32 i32] -> [i32],
+            I32DivU : [0x6e] : "i32.div_u" : visit_i32_div_u : Mvp : [i32 i32] -> [i32],
+            I32RemS : [0x6f] : "i32.rem_s" : visit_i32_rem_s : Mvp : [i32 i32] -> [i32],
+            I32RemU : [0x70] : "i32.rem_u" : visit_i32_rem_u : Mvp : [i32 i32] -> [i32],
+            I32And : [0x71] : "i32.and" : visit_i32_and : Mvp : [i32 i32] -> [i32],
+            I32Or : [0x72] : "i32.or" : visit_i32_or : Mvp : [i32 i32] -> [i32],
+            I32Xor : [0x73] : "i32.xor" : visit_i32_xor : Mvp : [i32 i32] -> [i32],
+            I32Shl : [0x74] : "i32.shl" : visit_i32_shl : Mvp : [i32 i32] -> [i32],
+            I32ShrS : [0x75] : "i32.shr_s" : visit_i32_shr_s : Mvp : [i32 i32] -> [i32],
+            I32ShrU : [0x76] : "i32.shr_u" : visit_i32_shr_u : Mvp : [i32 i32] -> [i32],
+            I32Rotl : [0x77] : "i32.rotl" : visit_i32_rotl : Mvp : [i32 i32] -> [i32],
+            I32Rotr : [0x78] : "i32.rotr" : visit_i32_rotr : Mvp : [i32 i32] -> [i32],
+
+            I64Clz : [0x79] : "i64.clz" : visit_i64_clz : Mvp : [i64] -> [i64],
+            I64Ctz : [0x7a] : "i64.ctz" : visit_i64_ctz : Mvp : [i64] -> [i64],
+            I64Popcnt : [0x7b] : "i64.popcnt" : visit_i64_popcnt : Mvp : [i64] -> [i64],
+            I64Add : [0x7c] : "i64.add" : visit_i64_add : Mvp : [i64 i64] -> [i64],
+            I64Sub : [0x7d] : "i64.sub" : visit_i64_sub : Mvp : [i64 i64] -> [i64],
+            I64Mul : [0x7e] : "i64.mul" : visit_i64_mul : Mvp : [i64 i64] -> [i64],
+            I64DivS : [0x7f] : "i64.div_s" : visit_i64_div_s : Mvp : [i64 i64] -> [i64],
+            I64DivU : [0x80] : "i64.div_u" : visit_i64_div_u : Mvp : [i64 i64] -> [i64],
+            I64RemS : [0x81] : "i64.rem_s" : visit_i64_rem_s : Mvp : [i64 i64] -> [i64],
+            I64RemU : [0x82] : "i64.rem_u" : visit_i64_rem_u : Mvp : [i64 i64] -> [i64],
+            I64And : [0x83] : "i64.and" : visit_i64_and : Mvp : [i64 i64] -> [i64],
+            I64Or : [0x84] : "i64.or" : visit_i64_or : Mvp : [i64 i64] -> [i64],
+            I64Xor : [0x85] : "i64.xor" : visit_i64_xor : Mvp : [i64 i64] -> [i64],
+            I64Shl : [0x86] : "i64.shl" : visit_i64_shl : Mvp : [i64 i64] -> [i64],
+            I64ShrS : [0x87] : "i64.shr_s" : visit_i64_shr_s : Mvp : [i64 i64] -> [i64],
+            I64ShrU : [0x88] : "i64.shr_u" : visit_i64_shr_u : Mvp : [i64 i64] -> [i64],
+            I64Rotl : [0x89] : "i64.rotl" : visit_i64_rotl : Mvp : [i64 i64] -> [i64],
+            I64Rotr : [0x8a] : "i64.rotr" : visit_i64_rotr : Mvp : [i64 i64] -> [i64],
+
+            F32Abs : [0x8b] : "f32.abs" : visit_f32_abs : Mvp : [f32] -> [f32],
+            F32Neg : [0x8c] : "f32.neg" : visit_f32_neg : Mvp : [f32] -> [f32],
+            F32Ceil : [0x8d] : "f32.ceil" : visit_f32_ceil : Mvp : [f32] -> [f32],
+            F32Floor : [0x8e] : "f32.floor" : visit_f32_floor : Mvp : [f32] -> [f32],
+            F32Trunc : [0x8f] : "f32.trunc" : visit_f32_trunc : Mvp : [f32] -> [f32],
+            F32Nearest : [0x90] : "f32.nearest" : visit_f32_nearest : Mvp : [f32] -> [f32],
+            F32Sqrt : [0x91] : "f32.sqrt" : visit_f32_sqrt : Mvp : [f32] -> [f32],
+            F32Add : [0x92] : "f32.add" : visit_f32_add : Mvp : [f32 f32] -> [f32],
+            F32Sub : [0x93] : "f32.sub" : visit_f32_sub : Mvp : [f32 f32] -> [f32],
+            F32Mul : [0x94] : "f32.mul" : visit_f32_mul : Mvp : [f32 f32] -> [f32],
+            F32Div : [0x95] : "f32.div" : visit_f32_div : Mvp : [f32 f32] -> [f32],
+            F32Min : [0x96] : "f32.min" : visit_f32_min : Mvp : [f32 f32] -> [f32],
+            F32Max : [0x97] : "f32.max" : visit_f32_max : Mvp : [f32 f32] -> [f32],
+            F32Copysign : [0x98] : "f32.copysign" : visit_f32_copysign : Mvp : [f32 f32] -> [f32],
+
+            F64Abs : [0x99] : "f64.abs" : visit_f64_abs : Mvp : [f64] -> [f64],
+            F64Neg : [0x9a] : "f64.neg" : visit_f64_neg : Mvp : [f64] -> [f64],
+            F64Ceil : [0x9b] : "f64.ceil" : visit_f64_ceil : Mvp : [f64] -> [f64],
+            F64Floor : [0x9c] : "f64.floor" : visit_f64_floor : Mvp : [f64] -> [f64],
+            F64Trunc : [0x9d] : "f64.trunc" : visit_f64_trunc : Mvp : [f64] -> [f64],
+            F64Nearest : [0x9e] : "f64.nearest" : visit_f64_nearest : Mvp : [f64] -> [f64],
+            F64Sqrt : [0x9f] : "f64.sqrt" : visit_f64_sqrt : Mvp : [f64] -> [f64],
+            F64Add : [0xa0] : "f64.add" : visit_f64_add : Mvp : [f64 f64] -> [f64],
+            F64Sub : [0xa1] : "f64.sub" : visit_f64_sub : Mvp : [f64 f64] -> [f64],
+            F64Mul : [0xa2] : "f64.mul" : visit_f64_mul : Mvp : [f64 f64] -> [f64],
+            F64Div : [0xa3] : "f64.div" : visit_f64_div : Mvp : [f64 f64] -> [f64],
+            F64Min : [0xa4] : "f64.min" : visit_f64_min : Mvp : [f64 f64] -> [f64],
+            F64Max : [0xa5] : "f64.max" : visit_f64_max : Mvp : [f64 f64] -> [f64],
+            F64Copysign : [0xa6] : "f64.copysign" : visit_f64_copysign : Mvp : [f64 f64] -> [f64],
+
+            I32Eqz : [0x45] : "i32.eqz" : visit_i32_eqz : Mvp : [i32] -> [i32],
+            I32Eq : [0x46] : "i32.eq" : visit_i32_eq : Mvp : [i32 i32] -> [i32],
+            I32Ne : [0x47] : "i32.ne" : visit_i32_ne : Mvp : [i32 i32] -> [i32],
+            I32LtS : [0x48] : "i32.lt_s" : visit_i32_lt_s : Mvp : [i32 i32] -> [i32],
+            I32LtU : [0x49] : "i32.lt_u" : visit_i32_lt_u : Mvp : [i32 i32] -> [i32],
+            I32GtS : [0x4a] : "i32.gt_s" : visit_i32_gt_s : Mvp : [i32 i32] -> [i32],
+            I32GtU : [0x4b] : "i32.gt_u" : visit_i32_gt_u : Mvp : [i32 i32] -> [i32],
+            I32LeS : [0x4c] : "i32.le_s" : visit_i32_le_s : Mvp : [i32 i32] -> [i32],
+            I32LeU : [0x4d] : "i32.le_u" : visit_i32_le_u : Mvp : [i32 i32] -> [i32],
+            I32GeS : [0x4e] : "i32.ge_s" : visit_i32_ge_s : Mvp : [i32 i32] -> [i32],
+            I32GeU : [0x4f] : "i32.ge_u" : visit_i32_ge_u : Mvp : [i32 i32] -> [i32],
+
+            I64Eqz : [0x50] : "i64.eqz" : visit_i64_eqz : Mvp : [i64] -> [i32],
+            I64Eq : [0x51] : "i64.eq" : visit_i64_eq : Mvp : [i64 i64] -> [i32],
+            I64Ne : [0x52] : "i64.ne" : visit_i64_ne : Mvp : [i64 i64] -> [i32],
+            I64LtS : [0x53] : "i64.lt_s" : visit_i64_lt_s : Mvp : [i64 i64] -> [i32],
+            I64LtU : [0x54] : "i64.lt_u" : visit_i64_lt_u : Mvp : [i64 i64] -> [i32],
+            I64GtS : [0x55] : "i64.gt_s" : visit_i64_gt_s : Mvp : [i64 i64] -> [i32],
+            I64GtU : [0x56] : "i64.gt_u" : visit_i64_gt_u : Mvp : [i64 i64] -> [i32],
+            I64LeS : [0x57] : "i64.le_s" : visit_i64_le_s : Mvp : [i64 i64] -> [i32],
+            I64LeU : [0x58] : "i64.le_u" : visit_i64_le_u : Mvp : [i64 i64] -> [i32],
+            I64GeS : [0x59] : "i64.ge_s" : visit_i64_ge_s : Mvp : [i64 i64] -> [i32],
+            I64GeU : [0x5a] : "i64.ge_u" : visit_i64_ge_u : Mvp : [i64 i64] -> [i32],
+
+            F32Eq : [0x5b] : "f32.eq" : visit_f32_eq : Mvp : [f32 f32] -> [i32],
+            F32Ne : [0x5c] : "f32.ne" : visit_f32_ne : Mvp : [f32 f32] -> [i32],
+            F32Lt : [0x5d] : "f32.lt" : visit_f32_lt : Mvp : [f32 f32] -> [i32],
+            F32Gt : [0x5e] : "f32.gt" : visit_f32_gt : Mvp : [f32 f32] -> [i32],
+            F32Le : [0x5f] : "f32.le" : visit_f32_le : Mvp : [f32 f32] -> [i32],
+            F32Ge : [0x60] : "f32.ge" : visit_f32_ge : Mvp : [f32 f32] -> [i32],
+
+            F64Eq : [0x61] : "f64.eq" : visit_f64_eq : Mvp : [f64 f64] -> [i32],
+            F64Ne : [0x62] : "f64.ne" : visit_f64_ne : Mvp : [f64 f64] -> [i32],
+            F64Lt : [0x63] : "f64.lt" : visit_f64_lt : Mvp : [f64 f64] -> [i32],
+            F64Gt : [0x64] : "f64.gt" : visit_f64_gt : Mvp : [f64 f64] -> [i32],
+            F64Le : [0x65] : "f64.le" : visit_f64_le : Mvp : [f64 f64] -> [i32],
+            F64Ge : [0x66] : "f64.ge" : visit_f64_ge : Mvp : [f64 f64] -> [i32],
+
+            I32WrapI64 : [0xa7] : "i32.wrap_i64" | "i32.wrap/i64" : visit_i32_wrap_i64 : Mvp : [i64] -> [i32],
+            I32TruncF32S : [0xa8] : "i32.trunc_f32_s" | "i32.trunc_s/f32" : visit_i32_trunc_f32_s : Mvp : [f32] -> [i32],
+            I32TruncF32U : [0xa9] : "i32.trunc_f32_u" | "i32.trunc_u/f32" : visit_i32_trunc_f32_u : Mvp : [f32] -> [i32],
+            I32TruncF64S : [0xaa] : "i32.trunc_f64_s" | "i32.trunc_s/f64" : visit_i32_trunc_f64_s : Mvp : [f64] -> [i32],
+            I32TruncF64U : [0xab] : "i32.trunc_f64_u" | "i32.trunc_u/f64" : visit_i32_trunc_f64_u : Mvp : [f64] -> [i32],
+            I64ExtendI32S : [0xac] : "i64.extend_i32_s" | "i64.extend_s/i32" : visit_i64_extend_i32_s : Mvp : [i32] -> [i64],
+            I64ExtendI32U : [0xad] : "i64.extend_i32_u" | "i64.extend_u/i32" : visit_i64_extend_i32_u : Mvp : [i32] -> [i64],
+            I64TruncF32S : [0xae] : "i64.trunc_f32_s" | "i64.trunc_s/f32" : visit_i64_trunc_f32_s : Mvp : [f32] -> [i64],
+            I64TruncF32U : [0xaf] : "i64.trunc_f32_u" | "i64.trunc_u/f32" : visit_i64_trunc_f32_u : Mvp : [f32] -> [i64],
+            I64TruncF64S : [0xb0] : "i64.trunc_f64_s" | "i64.trunc_s/f64" : visit_i64_trunc_f64_s : Mvp : [f64] -> [i64],
+            I64TruncF64U : [0xb1] : "i64.trunc_f64_u" | "i64.trunc_u/f64" : visit_i64_trunc_f64_u : Mvp : [f64] -> [i64],
+            F32ConvertI32S : [0xb2] : "f32.convert_i32_s" | "f32.convert_s/i32" : visit_f32_convert_i32_s : Mvp : [i32] -> [f32],
+            F32ConvertI32U : [0xb3] : "f32.convert_i32_u" | "f32.convert_u/i32" : visit_f32_convert_i32_u : Mvp : [i32] -> [f32],
+            F32ConvertI64S : [0xb4] : "f32.convert_i64_s" | "f32.convert_s/i64" : visit_f32_convert_i64_s : Mvp : [i64] -> [f32],
+            F32ConvertI64U : [0xb5] : "f32.convert_i64_u" | "f32.convert_u/i64" : visit_f32_convert_i64_u : Mvp : [i64] -> [f32],
+            F32DemoteF64 : [0xb6] : "f32.demote_f64" | "f32.demote/f64" : visit_f32_demote_f64 : Mvp : [f64] -> [f32],
+            F64ConvertI32S : [0xb7] : "f64.convert_i32_s" | "f64.convert_s/i32" : visit_f64_convert_i32_s : Mvp : [i32] -> [f64],
+            F64ConvertI32U : [0xb8] : "f64.convert_i32_u" | "f64.convert_u/i32" : visit_f64_convert_i32_u : Mvp : [i32] -> [f64],
+            F64ConvertI64S : [0xb9] : "f64.convert_i64_s" | "f64.convert_s/i64" : visit_f64_convert_i64_s : Mvp : [i64] -> [f64],
+            F64ConvertI64U : [0xba] : "f64.convert_i64_u" | "f64.convert_u/i64" : visit_f64_convert_i64_u : Mvp : [i64] -> [f64],
+            F64PromoteF32 : [0xbb] : "f64.promote_f32" | "f64.promote/f32" : visit_f64_promote_f32 : Mvp : [f32] -> [f64],
+            I32ReinterpretF32 : [0xbc] : "i32.reinterpret_f32" | "i32.reinterpret/f32" : visit_i32_reinterpret_f32 : Mvp : [f32] -> [i32],
+            I64ReinterpretF64 : [0xbd] : "i64.reinterpret_f64" | "i64.reinterpret/f64" : visit_i64_reinterpret_f64 : Mvp : [f64] -> [i64],
+            F32ReinterpretI32 : [0xbe] : "f32.reinterpret_i32" | "f32.reinterpret/i32" : visit_f32_reinterpret_i32 : Mvp : [i32] -> [f32],
+            F64ReinterpretI64 : [0xbf] : "f64.reinterpret_i64" | "f64.reinterpret/i64" : visit_f64_reinterpret_i64 : Mvp : [i64] -> [f64],
+
+            // non-trapping float to int
+            I32TruncSatF32S : [0xfc, 0x00] : "i32.trunc_sat_f32_s" | "i32.trunc_s:sat/f32" : visit_i32_trunc_sat_f32_s : SaturatingFloatToInt : [f32] -> [i32],
+            I32TruncSatF32U : [0xfc, 0x01] : "i32.trunc_sat_f32_u" | "i32.trunc_u:sat/f32" : visit_i32_trunc_sat_f32_u : SaturatingFloatToInt : [f32] -> [i32],
+            I32TruncSatF64S : [0xfc, 0x02] : "i32.trunc_sat_f64_s" | "i32.trunc_s:sat/f64" : visit_i32_trunc_sat_f64_s : SaturatingFloatToInt : [f64] -> [i32],
+            I32TruncSatF64U : [0xfc, 0x03] : "i32.trunc_sat_f64_u" | "i32.trunc_u:sat/f64" : visit_i32_trunc_sat_f64_u : SaturatingFloatToInt : [f64] -> [i32],
+            I64TruncSatF32S : [0xfc, 0x04] : "i64.trunc_sat_f32_s" | "i64.trunc_s:sat/f32" : visit_i64_trunc_sat_f32_s : SaturatingFloatToInt : [f32] -> [i64],
+            I64TruncSatF32U : [0xfc, 0x05] : "i64.trunc_sat_f32_u" | "i64.trunc_u:sat/f32" : visit_i64_trunc_sat_f32_u : SaturatingFloatToInt : [f32] -> [i64],
+            I64TruncSatF64S : [0xfc, 0x06] : "i64.trunc_sat_f64_s" | "i64.trunc_s:sat/f64" : visit_i64_trunc_sat_f64_s : SaturatingFloatToInt : [f64] -> [i64],
+            I64TruncSatF64U : [0xfc, 0x07] : "i64.trunc_sat_f64_u" | "i64.trunc_u:sat/f64" : visit_i64_trunc_sat_f64_u : SaturatingFloatToInt : [f64] -> [i64],
+
+            // sign extension proposal
+            I32Extend8S : [0xc0] : "i32.extend8_s" : visit_i32_extend8_s : SignExtension : [i32] -> [i32],
+            I32Extend16S : [0xc1] : "i32.extend16_s" : visit_i32_extend16_s : SignExtension : [i32] -> [i32],
+            I64Extend8S : [0xc2] : "i64.extend8_s" : visit_i64_extend8_s : SignExtension : [i64] -> [i64],
+            I64Extend16S : [0xc3] : "i64.extend16_s" : visit_i64_extend16_s : SignExtension : [i64] -> [i64],
+            I64Extend32S : [0xc4] : "i64.extend32_s" : visit_i64_extend32_s : SignExtension : [i64] -> [i64],
+
+            // atomics proposal
+            AtomicNotify(MemArg<4>) : [0xfe, 0x00] : "atomic.notify" : visit_atomic_notify : Atomics memory : [i32 i32] -> [i32],
+            I32AtomicWait(MemArg<4>) : [0xfe, 0x01] : "i32.atomic.wait" : visit_i32_atomic_wait : Atomics memory : [i32 i32 i64] -> [i32],
+            I64AtomicWait(MemArg<8>) : [0xfe, 0x02] : "i64.atomic.wait" : visit_i64_atomic_wait : Atomics memory : [i32 i64 i64] -> [i32],
+            AtomicFence : [0xfe, 0x03] : "atomic.fence" : visit_atomic_fence : Atomics : [] -> [],
+
+            I32AtomicLoad(MemArg<4>) : [0xfe, 0x10] : "i32.atomic.load" : visit_i32_atomic_load : Atomics memory : [i32] -> [i32],
+            I64AtomicLoad(MemArg<8>) : [0xfe, 0x11] : "i64.atomic.load" : visit_i64_atomic_load : Atomics memory : [i32] -> [i64],
+            I32AtomicLoad8u(MemArg<1>) : [0xfe, 0x12] : "i32.atomic.load8_u" : visit_i32_atomic_load8_u : Atomics memory : [i32] -> [i32],
+            I32AtomicLoad16u(MemArg<2>) : [0xfe, 0x13] : "i32.atomic.load16_u" : visit_i32_atomic_load16_u : Atomics memory : [i32] -> [i32],
+            I64AtomicLoad8u(MemArg<1>) : [0xfe, 0x14] : "i64.atomic.load8_u" : visit_i64_atomic_load8_u : Atomics memory : [i32] -> [i64],
+            I64AtomicLoad16u(MemArg<2>) : [0xfe, 0x15] : "i64.atomic.load16_u" : visit_i64_atomic_load16_u : Atomics memory : [i32] -> [i64],
+            I64AtomicLoad32u(MemArg<4>) : [0xfe, 0x16] : "i64.atomic.load32_u" : visit_i64_atomic_load32_u : Atomics memory : [i32] -> [i64],
+            I32AtomicStore(MemArg<4>) : [0xfe, 0x17] : "i32.atomic.store" : visit_i32_atomic_store : Atomics memory : [i32 i32] -> [],
+            I64AtomicStore(MemArg<8>) : [0xfe, 0x18] : "i64.atomic.store" : visit_i64_atomic_store : Atomics memory : [i32 i64] -> [],
+            I32AtomicStore8(MemArg<1>) : [0xfe, 0x19] : "i32.atomic.store8" : visit_i32_atomic_store8 : Atomics memory : [i32 i32] -> [],
+            I32AtomicStore16(MemArg<2>) : [0xfe, 0x1a] : "i32.atomic.store16" : visit_i32_atomic_store16 : Atomics memory : [i32 i32] -> [],
+            I64AtomicStore8(MemArg<1>) : [0xfe, 0x1b] : "i64.atomic.store8" : visit_i64_atomic_store8 : Atomics memory : [i32 i64] -> [],
+            I64AtomicStore16(MemArg<2>) : [0xfe, 0x1c] : "i64.atomic.store16" : visit_i64_atomic_store16 : Atomics memory : [i32 i64] -> [],
+            I64AtomicStore32(MemArg<4>) : [0xfe, 0x1d] : "i64.atomic.store32" : visit_i64_atomic_store32 : Atomics memory : [i32 i64] -> [],
+
+            I32AtomicRmwAdd(MemArg<4>) : [0xfe, 0x1e] : "i32.atomic.rmw.add" : visit_i32_atomic_rmw_add : Atomics memory : [i32 i32] -> [i32],
+            I64AtomicRmwAdd(MemArg<8>) : [0xfe, 0x1f] : "i64.atomic.rmw.add" : visit_i64_atomic_rmw_add : Atomics memory : [i32 i64] -> [i64],
+            I32AtomicRmw8AddU(MemArg<1>) : [0xfe, 0x20] : "i32.atomic.rmw8.add_u" : visit_i32_atomic_rmw8_add_u : Atomics memory : [i32 i32] -> [i32],
+            I32AtomicRmw16AddU(MemArg<2>) : [0xfe, 0x21] : "i32.atomic.rmw16.add_u" : visit_i32_atomic_rmw16_add_u : Atomics memory : [i32 i32] -> [i32],
+            I64AtomicRmw8AddU(MemArg<1>) : [0xfe, 0x22] : "i64.atomic.rmw8.add_u" : visit_i64_atomic_rmw8_add_u : Atomics memory : [i32 i64] -> [i64],
+            I64AtomicRmw16AddU(MemArg<2>) : [0xfe, 0x23] : "i64.atomic.rmw16.add_u" : visit_i64_atomic_rmw16_add_u : Atomics memory : [i32 i64] -> [i64],
+            I64AtomicRmw32AddU(MemArg<4>) : [0xfe, 0x24] : "i64.atomic.rmw32.add_u" : visit_i64_atomic_rmw32_add_u : Atomics memory : [i32 i64] -> [i64],
+
+            I32AtomicRmwSub(MemArg<4>) : [0xfe, 0x25] : "i32.atomic.rmw.sub" : visit_i32_atomic_rmw_sub : Atomics memory : [i32 i32] -> [i32],
+            I64AtomicRmwSub(MemArg<8>) : [0xfe, 0x26] : "i64.atomic.rmw.sub" : visit_i64_atomic_rmw_sub : Atomics memory : [i32 i64] -> [i64],
+            I32AtomicRmw8SubU(MemArg<1>) : [0xfe, 0x27] : "i32.atomic.rmw8.sub_u" : visit_i32_atomic_rmw8_sub_u : Atomics memory : [i32 i32] -> [i32],
+            I32AtomicRmw16SubU(MemArg<2>) : [0xfe, 0x28] : "i32.atomic.rmw16.sub_u" : visit_i32_atomic_rmw16_sub_u : Atomics memory : [i32 i32] -> [i32],
+            I64AtomicRmw8SubU(MemArg<1>) : [0xfe, 0x29] : "i64.atomic.rmw8.sub_u" : visit_i64_atomic_rmw8_sub_u : Atomics memory : [i32 i64] -> [i64],
+            I64AtomicRmw16SubU(MemArg<2>) : [0xfe, 0x2a] : "i64.atomic.rmw16.sub_u" : visit_i64_atomic_rmw16_sub_u : Atomics memory : [i32 i64] -> [i64],
+            I64AtomicRmw32SubU(MemArg<4>) : [0xfe, 0x2b] : "i64.atomic.rmw32.sub_u" : visit_i64_atomic_rmw32_sub_u : Atomics memory : [i32 i64] -> [i64],
+
+            I32AtomicRmwAnd(MemArg<4>) : [0xfe, 0x2c] : "i32.atomic.rmw.and" : visit_i32_atomic_rmw_and : Atomics memory : [i32 i32] -> [i32],
+            I64AtomicRmwAnd(MemArg<8>) : [0xfe, 0x2d] : "i64.atomic.rmw.and" : visit_i64_atomic_rmw_and : Atomics memory : [i32 i64] -> [i64],
+            I32AtomicRmw8AndU(MemArg<1>) : [0xfe, 0x2e] : "i32.atomic.rmw8.and_u" : visit_i32_atomic_rmw8_and_u : Atomics memory : [i32 i32] -> [i32],
+            I32AtomicRmw16AndU(MemArg<2>) : [0xfe, 0x2f] : "i32.atomic.rmw16.and_u" : visit_i32_atomic_rmw16_and_u : Atomics memory : [i32 i32] -> [i32],
+            I64AtomicRmw8AndU(MemArg<1>) : [0xfe, 0x30] : "i64.atomic.rmw8.and_u" : visit_i64_atomic_rmw8_and_u : Atomics memory : [i32 i64] -> [i64],
+            I64AtomicRmw16AndU(MemArg<2>) : [0xfe, 0x31] : "i64.atomic.rmw16.and_u" : visit_i64_atomic_rmw16_and_u : Atomics memory : [i32 i64] -> [i64],
+            I64AtomicRmw32AndU(MemArg<4>) : [0xfe, 0x32] : "i64.atomic.rmw32.and_u" : visit_i64_atomic_rmw32_and_u : Atomics memory : [i32 i64] -> [i64],
+
+            I32AtomicRmwOr(MemArg<4>) : [0xfe, 0x33] : "i32.atomic.rmw.or" : visit_i32_atomic_rmw_or : Atomics memory : [i32 i32] -> [i32],
+            I64AtomicRmwOr(MemArg<8>) : [0xfe, 0x34] : "i64.atomic.rmw.or" : visit_i64_atomic_rmw_or : Atomics memory : [i32 i64] -> [i64],
+            I32AtomicRmw8OrU(MemArg<1>) : [0xfe, 0x35] : "i32.atomic.rmw8.or_u" : visit_i32_atomic_rmw8_or_u : Atomics memory : [i32 i32] -> [i32],
+            I32AtomicRmw16OrU(MemArg<2>) : [0xfe, 0x36] : "i32.atomic.rmw16.or_u" : visit_i32_atomic_rmw16_or_u : Atomics memory : [i32 i32] -> [i32],
+            I64AtomicRmw8OrU(MemArg<1>) : [0xfe, 0x37] : "i64.atomic.rmw8.or_u" : visit_i64_atomic_rmw8_or_u : Atomics memory : [i32 i64] -> [i64],
+            I64AtomicRmw16OrU(MemArg<2>) : [0xfe, 0x38] : "i64.atomic.rmw16.or_u" : visit_i64_atomic_rmw16_or_u : Atomics memory : [i32 i64] -> [i64],
+            I64AtomicRmw32OrU(MemArg<4>) : [0xfe, 0x39] : "i64.atomic.rmw32.or_u" : visit_i64_atomic_rmw32_or_u : Atomics memory : [i32 i64] -> [i64],
+
+            I32AtomicRmwXor(MemArg<4>) : [0xfe, 0x3a] : "i32.atomic.rmw.xor" : visit_i32_atomic_rmw_xor : Atomics memory : [i32 i32] -> [i32],
+            I64AtomicRmwXor(MemArg<8>) : [0xfe, 0x3b] : "i64.atomic.rmw.xor" : visit_i64_atomic_rmw_xor : Atomics memory : [i32 i64] -> [i64],
+            I32AtomicRmw8XorU(MemArg<1>) : [0xfe, 0x3c] : "i32.atomic.rmw8.xor_u" : visit_i32_atomic_rmw8_xor_u : Atomics memory : [i32 i32] -> [i32],
+            I32AtomicRmw16XorU(MemArg<2>) : [0xfe, 0x3d] : "i32.atomic.rmw16.xor_u" : visit_i32_atomic_rmw16_xor_u : Atomics memory : [i32 i32] -> [i32],
+            I64AtomicRmw8XorU(MemArg<1>) : [0xfe, 0x3e] : "i64.atomic.rmw8.xor_u" : visit_i64_atomic_rmw8_xor_u : Atomics memory : [i32 i64] -> [i64],
+            I64AtomicRmw16XorU(MemArg<2>) : [0xfe, 0x3f] : "i64.atomic.rmw16.xor_u" : visit_i64_atomic_rmw16_xor_u : Atomics memory : [i32 i64] -> [i64],
+            I64AtomicRmw32XorU(MemArg<4>) : [0xfe, 0x40] : "i64.atomic.rmw32.xor_u" : visit_i64_atomic_rmw32_xor_u : Atomics memory : [i32 i64] -> [i64],
+
+            I32AtomicRmwXchg(MemArg<4>) : [0xfe, 0x41] : "i32.atomic.rmw.xchg" : visit_i32_atomic_rmw_xchg : Atomics memory : [i32 i32] -> [i32],
+            I64AtomicRmwXchg(MemArg<8>) : [0xfe, 0x42] : "i64.atomic.rmw.xchg" : visit_i64_atomic_rmw_xchg : Atomics memory : [i32 i64] -> [i64],
+            I32AtomicRmw8XchgU(MemArg<1>) : [0xfe, 0x43] : "i32.atomic.rmw8.xchg_u" : visit_i32_atomic_rmw8_xchg_u : Atomics memory : [i32 i32] -> [i32],
+            I32AtomicRmw16XchgU(MemArg<2>) : [0xfe, 0x44] : "i32.atomic.rmw16.xchg_u" : visit_i32_atomic_rmw16_xchg_u : Atomics memory : [i32 i32] -> [i32],
+            I64AtomicRmw8XchgU(MemArg<1>) : [0xfe, 0x45] : "i64.atomic.rmw8.xchg_u" : visit_i64_atomic_rmw8_xchg_u : Atomics memory : [i32 i64] -> [i64],
+            I64AtomicRmw16XchgU(MemArg<2>) : [0xfe, 0x46] : "i64.atomic.rmw16.xchg_u" : visit_i64_atomic_rmw16_xchg_u : Atomics memory : [i32 i64] -> [i64],
+            I64AtomicRmw32XchgU(MemArg<4>) : [0xfe, 0x47] : "i64.atomic.rmw32.xchg_u" : visit_i64_atomic_rmw32_xchg_u : Atomics memory : [i32 i64] -> [i64],
+
+            I32AtomicRmwCmpxchg(MemArg<4>) : [0xfe, 0x48] : "i32.atomic.rmw.cmpxchg" : visit_i32_atomic_rmw_cmpxchg : Atomics memory : [i32 i32 i32] -> [i32],
+            I64AtomicRmwCmpxchg(MemArg<8>) : [0xfe, 0x49] : "i64.atomic.rmw.cmpxchg" : visit_i64_atomic_rmw_cmpxchg : Atomics memory : [i32 i64 i64] -> [i64],
+            I32AtomicRmw8CmpxchgU(MemArg<1>) : [0xfe, 0x4a] : "i32.atomic.rmw8.cmpxchg_u" : visit_i32_atomic_rmw8_cmpxchg_u : Atomics memory : [i32 i32 i32] -> [i32],
+            I32AtomicRmw16CmpxchgU(MemArg<2>) : [0xfe, 0x4b] : "i32.atomic.rmw16.cmpxchg_u" : visit_i32_atomic_rmw16_cmpxchg_u : Atomics memory : [i32 i32 i32] -> [i32],
+            I64AtomicRmw8CmpxchgU(MemArg<1>) : [0xfe, 0x4c] : "i64.atomic.rmw8.cmpxchg_u" : visit_i64_atomic_rmw8_cmpxchg_u : Atomics memory : [i32 i64 i64] -> [i64],
+            I64AtomicRmw16CmpxchgU(MemArg<2>) : [0xfe, 0x4d] : "i64.atomic.rmw16.cmpxchg_u" : visit_i64_atomic_rmw16_cmpxchg_u : Atomics memory : [i32 i64 i64] -> [i64],
+            I64AtomicRmw32CmpxchgU(MemArg<4>) : [0xfe, 0x4e] : "i64.atomic.rmw32.cmpxchg_u" : visit_i64_atomic_rmw32_cmpxchg_u : Atomics memory : [i32 i64 i64] -> [i64],
+
+            V128Load(MemArg<16>) : [0xfd, 0x00] : "v128.load" : visit_v128_load : Simd memory : [i32] -> [v128],
+            V128Store(MemArg<16>) : [0xfd, 0x01] : "v128.store" : visit_v128_store : Simd memory : [i32 v128] -> [],
+            V128Const(V128Const) : [0xfd, 0x02] : "v128.const" : visit_v128_const : Simd : [] -> [v128],
+
+            I8x16Splat : [0xfd, 0x04] : "i8x16.splat" : visit_i8x16_splat : Simd : [i32] -> [v128],
+            I8x16ExtractLaneS(i32) : [0xfd, 0x05] : "i8x16.extract_lane_s" : visit_i8x16_extract_lane_s : Simd : [v128] -> [i32],
+            I8x16ExtractLaneU(i32) : [0xfd, 0x06] : "i8x16.extract_lane_u" : visit_i8x16_extract_lane_u : Simd : [v128] -> [i32],
+            I8x16ReplaceLane(i32) : [0xfd, 0x07] : "i8x16.replace_lane" : visit_i8x16_replace_lane : Simd : [v128 i32] -> [v128],
+            I16x8Splat : [0xfd, 0x08] : "i16x8.splat" : visit_i16x8_splat : Simd : [i32] -> [v128],
+            I16x8ExtractLaneS(i32) : [0xfd, 0x09] : "i16x8.extract_lane_s" : visit_i16x8_extract_lane_s : Simd : [v128] -> [i32],
+            I16x8ExtractLaneU(i32) : [0xfd, 0x0a] : "i16x8.extract_lane_u" : visit_i16x8_extract_lane_u : Simd : [v128] -> [i32],
+            I16x8ReplaceLane(i32) : [0xfd, 0x0b] : "i16x8.replace_lane" : visit_i16x8_replace_lane : Simd : [v128 i32] -> [v128],
+            I32x4Splat : [0xfd, 0x0c] : "i32x4.splat" : visit_i32x4_splat : Simd : [i32] -> [v128],
+            I32x4ExtractLane(i32) : [0xfd, 0x0d] : "i32x4.extract_lane" : visit_i32x4_extract_lane : Simd : [v128] -> [i32],
+            I32x4ReplaceLane(i32) : [0xfd, 0x0e] : "i32x4.replace_lane" : visit_i32x4_replace_lane : Simd : [v128 i32] -> [v128],
+            I64x2Splat : [0xfd, 0x0f] : "i64x2.splat" : visit_i64x2_splat : Simd : [i64] -> [v128],
+            I64x2ExtractLane(i32) : [0xfd, 0x10] : "i64x2.extract_lane" : visit_i64x2_extract_lane : Simd : [v128] -> [i64],
+            I64x2ReplaceLane(i32) : [0xfd, 0x11] : "i64x2.replace_lane" : visit_i64x2_replace_lane : Simd : [v128 i64] -> [v128],
+            F32x4Splat : [0xfd, 0x12] : "f32x4.splat" : visit_f32x4_splat : Simd : [f32] -> [v128],
+            F32x4ExtractLane(i32) : [0xfd, 0x13] : "f32x4.extract_lane" : visit_f32x4_extract_lane : Simd : [v128] -> [f32],
+            F32x4ReplaceLane(i32) : [0xfd, 0x14] : "f32x4.replace_lane" : visit_f32x4_replace_lane : Simd : [v128 f32] -> [v128],
+            F64x2Splat : [0xfd, 0x15] : "f64x2.splat" : visit_f64x2_splat : Simd : [f64] -> [v128],
+            F64x2ExtractLane(i32) : [0xfd, 0x16] : "f64x2.extract_lane" : visit_f64x2_extract_lane : Simd : [v128] -> [f64],
+            F64x2ReplaceLane(i32) : [0xfd, 0x17] : "f64x2.replace_lane" : visit_f64x2_replace_lane : Simd : [v128 f64] -> [v128],
+
+            I8x16Eq : [0xfd, 0x18] : "i8x16.eq" : visit_i8x16_eq : Simd : [v128 v128] -> [v128],
+            I8x16Ne : [0xfd, 0x19] : "i8x16.ne" : visit_i8x16_ne : Simd : [v128 v128] -> [v128],
+            I8x16LtS : [0xfd, 0x1a] : "i8x16.lt_s" : visit_i8x16_lt_s : Simd : [v128 v128] -> [v128],
+            I8x16LtU : [0xfd, 0x1b] : "i8x16.lt_u" : visit_i8x16_lt_u : Simd : [v128 v128] -> [v128],
+            I8x16GtS : [0xfd, 0x1c] : "i8x16.gt_s" : visit_i8x16_gt_s : Simd : [v128 v128] -> [v128],
+            I8x16GtU : [0xfd, 0x1d] : "i8x16.gt_u" : visit_i8x16_gt_u : Simd : [v128 v128] -> [v128],
+            I8x16LeS : [0xfd, 0x1e] : "i8x16.le_s" : visit_i8x16_le_s : Simd : [v128 v128] -> [v128],
+            I8x16LeU : [0xfd, 0x1f] : "i8x16.le_u" : visit_i8x16_le_u : Simd : [v128 v128] -> [v128],
+            I8x16GeS : [0xfd, 0x20] : "i8x16.ge_s" : visit_i8x16_ge_s : Simd : [v128 v128] -> [v128],
+            I8x16GeU : [0xfd, 0x21] : "i8x16.ge_u" : visit_i8x16_ge_u : Simd : [v128 v128] -> [v128],
+            I16x8Eq : [0xfd, 0x22] : "i16x8.eq" : visit_i16x8_eq : Simd : [v128 v128] -> [v128],
+            I16x8Ne : [0xfd, 0x23] : "i16x8.ne" : visit_i16x8_ne : Simd : [v128 v128] -> [v128],
+            I16x8LtS : [0xfd, 0x24] : "i16x8.lt_s" : visit_i16x8_lt_s : Simd : [v128 v128] -> [v128],
+            I16x8LtU : [0xfd, 0x25] : "i16x8.lt_u" : visit_i16x8_lt_u : Simd : [v128 v128] -> [v128],
+            I16x8GtS : [0xfd, 0x26] : "i16x8.gt_s" : visit_i16x8_gt_s : Simd : [v128 v128] -> [v128],
+            I16x8GtU : [0xfd, 0x27] : "i16x8.gt_u" : visit_i16x8_gt_u : Simd : [v128 v128] -> [v128],
+            I16x8LeS : [0xfd, 0x28] : "i16x8.le_s" : visit_i16x8_le_s : Simd : [v128 v128] -> [v128],
+            I16x8LeU : [0xfd, 0x29] : "i16x8.le_u" : visit_i16x8_le_u : Simd : [v128 v128] -> [v128],
+            I16x8GeS : [0xfd, 0x2a] : "i16x8.ge_s" : visit_i16x8_ge_s : Simd : [v128 v128] -> [v128],
+            I16x8GeU : [0xfd, 0x2b] : "i16x8.ge_u" : visit_i16x8_ge_u : Simd : [v128 v128] -> [v128],
+            I32x4Eq : [0xfd, 0x2c] : "i32x4.eq" : visit_i32x4_eq : Simd : [v128 v128] -> [v128],
+            I32x4Ne : [0xfd, 0x2d] : "i32x4.ne" : visit_i32x4_ne : Simd : [v128 v128] -> [v128],
+            I32x4LtS : [0xfd, 0x2e] : "i32x4.lt_s" : visit_i32x4_lt_s : Simd : [v128 v128] -> [v128],
+            I32x4LtU : [0xfd, 0x2f] : "i32x4.lt_u" : visit_i32x4_lt_u : Simd : [v128 v128] -> [v128],
+            I32x4GtS : [0xfd, 0x30] : "i32x4.gt_s" : visit_i32x4_gt_s : Simd : [v128 v128] -> [v128],
+            I32x4GtU : [0xfd, 0x31] : "i32x4.gt_u" : visit_i32x4_gt_u : Simd : [v128 v128] -> [v128],
+            I32x4LeS : [0xfd, 0x32] : "i32x4.le_s" : visit_i32x4_le_s : Simd : [v128 v128] -> [v128],
+            I32x4LeU : [0xfd, 0x33] : "i32x4.le_u" : visit_i32x4_le_u : Simd : [v128 v128] -> [v128],
+            I32x4GeS : [0xfd, 0x34] : "i32x4.ge_s" : visit_i32x4_ge_s : Simd : [v128 v128] -> [v128],
+            I32x4GeU : [0xfd, 0x35] : "i32x4.ge_u" : visit_i32x4_ge_u : Simd : [v128 v128] -> [v128],
+
+            F32x4Eq : [0xfd, 0x40] : "f32x4.eq" : visit_f32x4_eq : Simd : [v128 v128] -> [v128],
+            F32x4Ne : [0xfd, 0x41] : "f32x4.ne" : visit_f32x4_ne : Simd : [v128 v128] -> [v128],
+            F32x4Lt : [0xfd, 0x42] : "f32x4.lt" : visit_f32x4_lt : Simd : [v128 v128] -> [v128],
+            F32x4Gt : [0xfd, 0x43] : "f32x4.gt" : visit_f32x4_gt : Simd : [v128 v128] -> [v128],
+            F32x4Le : [0xfd, 0x44] : "f32x4.le" : visit_f32x4_le : Simd : [v128 v128] -> [v128],
+            F32x4Ge : [0xfd, 0x45] : "f32x4.ge" : visit_f32x4_ge : Simd : [v128 v128] -> [v128],
+            F64x2Eq : [0xfd, 0x46] : "f64x2.eq" : visit_f64x2_eq : Simd : [v128 v128] -> [v128],
+            F64x2Ne : [0xfd, 0x47] : "f64x2.ne" : visit_f64x2_ne : Simd : [v128 v128] -> [v128],
+            F64x2Lt : [0xfd, 0x48] : "f64x2.lt" : visit_f64x2_lt : Simd : [v128 v128] -> [v128],
+            F64x2Gt : [0xfd, 0x49] : "f64x2.gt" : visit_f64x2_gt : Simd : [v128 v128] -> [v128],
+            F64x2Le : [0xfd, 0x4a] : "f64x2.le" : visit_f64x2_le : Simd : [v128 v128] -> [v128],
+            F64x2Ge : [0xfd, 0x4b] : "f64x2.ge" : visit_f64x2_ge : Simd : [v128 v128] -> [v128],
+
+            V128Not : [0xfd, 0x4c] : "v128.not" : visit_v128_not : Simd : [v128] -> [v128],
+            V128And : [0xfd, 0x4d] : "v128.and" : visit_v128_and : Simd : [v128 v128] -> [v128],
+            V128Or : [0xfd, 0x4e] : "v128.or" : visit_v128_or : Simd : [v128 v128] -> [v128],
+            V128Xor : [0xfd, 0x4f] : "v128.xor" : visit_v128_xor : Simd : [v128 v128] -> [v128],
+            V128Bitselect : [0xfd, 0x50] : "v128.bitselect" : visit_v128_bitselect : Simd : [v128 v128 v128] -> [v128],
+
+            I8x16Neg : [0xfd, 0x51] : "i8x16.neg" : visit_i8x16_neg : Simd : [v128] -> [v128],
+            I8x16AnyTrue : [0xfd, 0x52] : "i8x16.any_true" : visit_i8x16_any_true : Simd : [v128] -> [i32],
+            I8x16AllTrue : [0xfd, 0x53] : "i8x16.all_true" : visit_i8x16_all_true : Simd : [v128] -> [i32],
+            I8x16Shl : [0xfd, 0x54] : "i8x16.shl" : visit_i8x16_shl : Simd : [v128 i32] -> [v128],
+            I8x16ShrS : [0xfd, 0x55] : "i8x16.shr_s" : visit_i8x16_shr_s : Simd : [v128 i32] -> [v128],
+            I8x16ShrU : [0xfd, 0x56] : "i8x16.shr_u" : visit_i8x16_shr_u : Simd : [v128 i32] -> [v128],
+            I8x16Add : [0xfd, 0x57] : "i8x16.add" : visit_i8x16_add : Simd : [v128 v128] -> [v128],
+            I8x16AddSaturateS : [0xfd, 0x58] : "i8x16.add_saturate_s" : visit_i8x16_add_saturate_s : Simd : [v128 v128] -> [v128],
+            I8x16AddSaturateU : [0xfd, 0x59] : "i8x16.add_saturate_u" : visit_i8x16_add_saturate_u : Simd : [v128 v128] -> [v128],
+            I8x16Sub : [0xfd, 0x5a] : "i8x16.sub" : visit_i8x16_sub : Simd : [v128 v128] -> [v128],
+            I8x16SubSaturateS : [0xfd, 0x5b] : "i8x16.sub_saturate_s" : visit_i8x16_sub_saturate_s : Simd : [v128 v128] -> [v128],
+            I8x16SubSaturateU : [0xfd, 0x5c] : "i8x16.sub_saturate_u" : visit_i8x16_sub_saturate_u : Simd : [v128 v128] -> [v128],
+            I8x16Mul : [0xfd, 0x5d] : "i8x16.mul" : visit_i8x16_mul : Simd : [v128 v128] -> [v128],
+
+            I16x8Neg : [0xfd, 0x62] : "i16x8.neg" : visit_i16x8_neg : Simd : [v128] -> [v128],
+            I16x8AnyTrue : [0xfd, 0x63] : "i16x8.any_true" : visit_i16x8_any_true : Simd : [v128] -> [i32],
+            I16x8AllTrue : [0xfd, 0x64] : "i16x8.all_true" : visit_i16x8_all_true : Simd : [v128] -> [i32],
+            I16x8Shl : [0xfd, 0x65] : "i16x8.shl" : visit_i16x8_shl : Simd : [v128 i32] -> [v128],
+            I16x8ShrS : [0xfd, 0x66] : "i16x8.shr_s" : visit_i16x8_shr_s : Simd : [v128 i32] -> [v128],
+            I16x8ShrU : [0xfd, 0x67] : "i16x8.shr_u" : visit_i16x8_shr_u : Simd : [v128 i32] -> [v128],
+            I16x8Add : [0xfd, 0x68] : "i16x8.add" : visit_i16x8_add : Simd : [v128 v128] -> [v128],
+            I16x8AddSaturateS : [0xfd, 0x69] : "i16x8.add_saturate_s" : visit_i16x8_add_saturate_s : Simd : [v128 v128] -> [v128],
+            I16x8AddSaturateU : [0xfd, 0x6a] : "i16x8.add_saturate_u" : visit_i16x8_add_saturate_u : Simd : [v128 v128] -> [v128],
+            I16x8Sub : [0xfd, 0x6b] : "i16x8.sub" : visit_i16x8_sub : Simd : [v128 v128] -> [v128],
+            I16x8SubSaturateS : [0xfd, 0x6c] : "i16x8.sub_saturate_s" : visit_i16x8_sub_saturate_s : Simd : [v128 v128] -> [v128],
+            I16x8SubSaturateU : [0xfd, 0x6d] : "i16x8.sub_saturate_u" : visit_i16x8_sub_saturate_u : Simd : [v128 v128] -> [v128],
+            I16x8Mul : [0xfd, 0x6e] : "i16x8.mul" : visit_i16x8_mul : Simd : [v128 v128] -> [v128],
+
+            I32x4Neg : [0xfd, 0x73] : "i32x4.neg" : visit_i32x4_neg : Simd : [v128] -> [v128],
+            I32x4AnyTrue : [0xfd, 0x74] : "i32x4.any_true" : visit_i32x4_any_true : Simd : [v128] -> [i32],
+            I32x4AllTrue : [0xfd, 0x75] : "i32x4.all_true" : visit_i32x4_all_true : Simd : [v128] -> [i32],
+            I32x4Shl : [0xfd, 0x76] : "i32x4.shl" : visit_i32x4_shl : Simd : [v128 i32] -> [v128],
+            I32x4ShrS : [0xfd, 0x77] : "i32x4.shr_s" : visit_i32x4_shr_s : Simd : [v128 i32] -> [v128],
+            I32x4ShrU : [0xfd, 0x78] : "i32x4.shr_u" : visit_i32x4_shr_u : Simd : [v128 i32] -> [v128],
+            I32x4Add : [0xfd, 0x79] : "i32x4.add" : visit_i32x4_add : Simd : [v128 v128] -> [v128],
+            I32x4Sub : [0xfd, 0x7c] : "i32x4.sub" : visit_i32x4_sub : Simd : [v128 v128] -> [v128],
+            I32x4Mul : [0xfd, 0x7f] : "i32x4.mul" : visit_i32x4_mul : Simd : [v128 v128] -> [v128],
+
+            I64x2Neg : [0xfd, 0x84] : "i64x2.neg" : visit_i64x2_neg : Simd : [v128] -> [v128],
+            I64x2AnyTrue : [0xfd, 0x85] : "i64x2.any_true" : visit_i64x2_any_true : Simd : [v128] -> [i32],
+            I64x2AllTrue : [0xfd, 0x86] : "i64x2.all_true" : visit_i64x2_all_true : Simd : [v128] -> [i32],
+            I64x2Shl : [0xfd, 0x87] : "i64x2.shl" : visit_i64x2_shl : Simd : [v128 i32] -> [v128],
+            I64x2ShrS : [0xfd, 0x88] : "i64x2.shr_s" : visit_i64x2_shr_s : Simd : [v128 i32] -> [v128],
+            I64x2ShrU : [0xfd, 0x89] : "i64x2.shr_u" : visit_i64x2_shr_u : Simd : [v128 i32] -> [v128],
+            I64x2Add : [0xfd, 0x8a] : "i64x2.add" : visit_i64x2_add : Simd : [v128 v128] -> [v128],
+            I64x2Sub : [0xfd, 0x8d] : "i64x2.sub" : visit_i64x2_sub : Simd : [v128 v128] -> [v128],
+            I64x2Mul : [0xfd, 0x90] : "i64x2.mul" : visit_i64x2_mul : Simd : [v128 v128] -> [v128],
+
+            F32x4Abs : [0xfd, 0x95] : "f32x4.abs" : visit_f32x4_abs : Simd : [v128] -> [v128],
+            F32x4Neg : [0xfd, 0x96] : "f32x4.neg" : visit_f32x4_neg : Simd : [v128] -> [v128],
+            F32x4Sqrt : [0xfd, 0x97] : "f32x4.sqrt" : visit_f32x4_sqrt : Simd : [v128] -> [v128],
+            F32x4Add : [0xfd, 0x9a] : "f32x4.add" : visit_f32x4_add : Simd : [v128 v128] -> [v128],
+            F32x4Sub : [0xfd, 0x9b] : "f32x4.sub" : visit_f32x4_sub : Simd : [v128 v128] -> [v128],
+            F32x4Mul : [0xfd, 0x9c] : "f32x4.mul" : visit_f32x4_mul : Simd : [v128 v128] -> [v128],
+            F32x4Div : [0xfd, 0x9d] : "f32x4.div" : visit_f32x4_div : Simd : [v128 v128] -> [v128],
+            F32x4Min : [0xfd, 0x9e] : "f32x4.min" : visit_f32x4_min : Simd : [v128 v128] -> [v128],
+            F32x4Max : [0xfd, 0x9f] : "f32x4.max" : visit_f32x4_max : Simd : [v128 v128] -> [v128],
+
+            F64x2Abs : [0xfd, 0xa0] : "f64x2.abs" : visit_f64x2_abs : Simd : [v128] -> [v128],
+            F64x2Neg : [0xfd, 0xa1] : "f64x2.neg" : visit_f64x2_neg : Simd : [v128] -> [v128],
+            F64x2Sqrt : [0xfd, 0xa2] : "f64x2.sqrt" : visit_f64x2_sqrt : Simd : [v128] -> [v128],
+            F64x2Add : [0xfd, 0xa5] : "f64x2.add" : visit_f64x2_add : Simd : [v128 v128] -> [v128],
+            F64x2Sub : [0xfd, 0xa6] : "f64x2.sub" : visit_f64x2_sub : Simd : [v128 v128] -> [v128],
+            F64x2Mul : [0xfd, 0xa7] : "f64x2.mul" : visit_f64x2_mul : Simd : [v128 v128] -> [v128],
+            F64x2Div : [0xfd, 0xa8] : "f64x2.div" : visit_f64x2_div : Simd : [v128 v128] -> [v128],
+            F64x2Min : [0xfd, 0xa9] : "f64x2.min" : visit_f64x2_min : Simd : [v128 v128] -> [v128],
+            F64x2Max : [0xfd, 0xaa] : "f64x2.max" : visit_f64x2_max : Simd : [v128 v128] -> [v128],
+
+            I32x4TruncSatF32x4S : [0xfd, 0xab] : "i32x4.trunc_sat_f32x4_s" : visit_i32x4_trunc_sat_f32x4_s : Simd : [v128] -> [v128],
+            I32x4TruncSatF32x4U : [0xfd, 0xac] : "i32x4.trunc_sat_f32x4_u" : visit_i32x4_trunc_sat_f32x4_u : Simd : [v128] -> [v128],
+            I64x2TruncSatF64x2S : [0xfd, 0xad] : "i64x2.trunc_sat_f64x2_s" : visit_i64x2_trunc_sat_f64x2_s : Simd : [v128] -> [v128],
+            I64x2TruncSatF64x2U : [0xfd, 0xae] : "i64x2.trunc_sat_f64x2_u" : visit_i64x2_trunc_sat_f64x2_u : Simd : [v128] -> [v128],
+            F32x4ConvertI32x4S : [0xfd, 0xaf] : "f32x4.convert_i32x4_s" : visit_f32x4_convert_i32x4_s : Simd : [v128] -> [v128],
+            F32x4ConvertI32x4U : [0xfd, 0xb0] : "f32x4.convert_i32x4_u" : visit_f32x4_convert_i32x4_u : Simd : [v128] -> [v128],
+            F64x2ConvertI64x2S : [0xfd, 0xb1] : "f64x2.convert_i64x2_s" : visit_f64x2_convert_i64x2_s : Simd : [v128] -> [v128],
+            F64x2ConvertI64x2U : [0xfd, 0xb2] : "f64x2.convert_i64x2_u" : visit_f64x2_convert_i64x2_u : Simd : [v128] -> [v128],
+            V8x16Swizzle : [0xfd, 0xc0] : "v8x16.swizzle" : visit_v8x16_swizzle : Simd : [v128 v128] -> [v128],
+            V8x16Shuffle(V8x16Shuffle) : [0xfd, 0xc1] : "v8x16.shuffle" : visit_v8x16_shuffle : Simd : [v128 v128] -> [v128],
+            V8x16LoadSplat(MemArg<1>) : [0xfd, 0xc2] : "v8x16.load_splat" : visit_v8x16_load_splat : Simd memory : [i32] -> [v128],
+            V16x8LoadSplat(MemArg<2>) : [0xfd, 0xc3] : "v16x8.load_splat" : visit_v16x8_load_splat : Simd memory : [i32] -> [v128],
+            V32x4LoadSplat(MemArg<4>) : [0xfd, 0xc4] : "v32x4.load_splat" : visit_v32x4_load_splat : Simd memory : [i32] -> [v128],
+            V64x2LoadSplat(MemArg<8>) : [0xfd, 0xc5] : "v64x2.load_splat" : visit_v64x2_load_splat : Simd memory : [i32] -> [v128],
+
+            I8x16NarrowI16x8S : [0xfd, 0xc6] : "i8x16.narrow_i16x8_s" : visit_i8x16_narrow_i16x8_s : Simd : [v128 v128] -> [v128],
+            I8x16NarrowI16x8U : [0xfd, 0xc7] : "i8x16.narrow_i16x8_u" : visit_i8x16_narrow_i16x8_u : Simd : [v128 v128] -> [v128],
+            I16x8NarrowI32x4S : [0xfd, 0xc8] : "i16x8.narrow_i32x4_s" : visit_i16x8_narrow_i32x4_s : Simd : [v128 v128] -> [v128],
+            I16x8NarrowI32x4U : [0xfd, 0xc9] : "i16x8.narrow_i32x4_u" : visit_i16x8_narrow_i32x4_u : Simd : [v128 v128] -> [v128],
+
+            I16x8WidenLowI8x16S : [0xfd, 0xca] : "i16x8.widen_low_i8x16_s" : visit_i16x8_widen_low_i8x16_s : Simd : [v128] -> [v128],
+            I16x8WidenHighI8x16S : [0xfd, 0xcb] : "i16x8.widen_high_i8x16_s" : visit_i16x8_widen_high_i8x16_s : Simd : [v128] -> [v128],
+            I16x8WidenLowI8x16U : [0xfd, 0xcc] : "i16x8.widen_low_i8x16_u" : visit_i16x8_widen_low_i8x16_u : Simd : [v128] -> [v128],
+            I16x8WidenHighI8x16u : [0xfd, 0xcd] : "i16x8.widen_high_i8x16_u" : visit_i16x8_widen_high_i8x16_u : Simd : [v128] -> [v128],
+            I32x4WidenLowI16x8S : [0xfd, 0xce] : "i32x4.widen_low_i16x8_s" : visit_i32x4_widen_low_i16x8_s : Simd : [v128] -> [v128],
+            I32x4WidenHighI16x8S : [0xfd, 0xcf] : "i32x4.widen_high_i16x8_s" : visit_i32x4_widen_high_i16x8_s : Simd : [v128] -> [v128],
+            I32x4WidenLowI16x8U : [0xfd, 0xd0] : "i32x4.widen_low_i16x8_u" : visit_i32x4_widen_low_i16x8_u : Simd : [v128] -> [v128],
+            I32x4WidenHighI16x8u : [0xfd, 0xd1] : "i32x4.widen_high_i16x8_u" : visit_i32x4_widen_high_i16x8_u : Simd : [v128] -> [v128],
+
+            I16x8Load8x8S(MemArg<1>) : [0xfd, 0xd2] : "i16x8.load8x8_s" : visit_i16x8_load8x8_s : Simd memory : [i32] -> [v128],
+            I16x8Load8x8U(MemArg<1>) : [0xfd, 0xd3] : "i16x8.load8x8_u" : visit_i16x8_load8x8_u : Simd memory : [i32] -> [v128],
+            I32x4Load16x4S(MemArg<2>) : [0xfd, 0xd4] : "i32x4.load16x4_s" : visit_i32x4_load16x4_s : Simd memory : [i32] -> [v128],
+            I32x4Load16x4U(MemArg<2>) : [0xfd, 0xd5] : "i32x4.load16x4_u" : visit_i32x4_load16x4_u : Simd memory : [i32] -> [v128],
+            I64x2Load32x2S(MemArg<4>) : [0xfd, 0xd6] : "i64x2.load32x2_s" : visit_i64x2_load32x2_s : Simd memory : [i32] -> [v128],
+            I64x2Load32x2U(MemArg<4>) : [0xfd, 0xd7] : "i64x2.load32x2_u" : visit_i64x2_load32x2_u : Simd memory : [i32] -> [v128],
+            V128Andnot : [0xfd, 0xd8] : "v128.andnot" : visit_v128_andnot : Simd : [v128 v128] -> [v128],
+        }
+    });
 }
 
+for_each_instruction!(instructions);
+
 /// Extra information associated with block-related instructions.
 ///
 /// This is used to label blocks and also annotate what types are expected for
 /// the block.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub struct BlockType<'a> {
     pub label: Option<ast::Id<'a>>,
     pub ty: ast::TypeUse<'a>,
 }
 
+impl BlockType<'_> {
+    #[allow(missing_docs)]
+    pub fn into_owned(self) -> BlockType<'static> {
+        BlockType {
+            label: self.label.map(ast::Id::into_owned),
+            ty: self.ty.into_owned(),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for BlockType<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         Ok(BlockType {
@@ -704,12 +907,27 @@ impl<'a> Parse<'a> for BlockType<'a> {
 
 /// Extra information associated with the `br_table` instruction.
 #[allow(missing_docs)]
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BrTableIndices<'a> {
     pub labels: Vec<ast::Index<'a>>,
     pub default: ast::Index<'a>,
 }
 
+impl BrTableIndices<'_> {
+    #[allow(missing_docs)]
+    pub fn into_owned(self) -> BrTableIndices<'static> {
+        BrTableIndices {
+            labels: self
+                .labels
+                .into_iter()
+                .map(ast::Index::into_owned)
+                .collect(),
+            default: self.default.into_owned(),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for BrTableIndices<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let mut labels = Vec::new();
@@ -724,7 +942,8 @@ impl<'a> Parse<'a> for BrTableIndices<'a> {
 
 /// Payload for memory-related instructions indicating offset/alignment of
 /// memory accesses.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MemArg {
     /// The alignment of this access.
     ///
@@ -779,7 +998,8 @@ impl MemArg {
 }
 
 /// Extra data associated with the `call_indirect` instruction.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CallIndirect<'a> {
     /// The table that this call is going to be indexing.
     pub table: ast::Index<'a>,
@@ -787,6 +1007,16 @@ pub struct CallIndirect<'a> {
     pub ty: ast::TypeUse<'a>,
 }
 
+impl CallIndirect<'_> {
+    #[allow(missing_docs)]
+    pub fn into_owned(self) -> CallIndirect<'static> {
+        CallIndirect {
+            table: self.table.into_owned(),
+            ty: self.ty.into_owned(),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for CallIndirect<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let mut table: Option<_> = parser.parse()?;
@@ -805,12 +1035,22 @@ impl<'a> Parse<'a> for CallIndirect<'a> {
 }
 
 /// Extra data associated with the `table.init` instruction
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TableInit<'a> {
     /// The index of the element segment we're copying into a table.
     pub elem: ast::Index<'a>,
 }
 
+impl TableInit<'_> {
+    #[allow(missing_docs)]
+    pub fn into_owned(self) -> TableInit<'static> {
+        TableInit {
+            elem: self.elem.into_owned(),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for TableInit<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         Ok(TableInit {
@@ -820,12 +1060,22 @@ impl<'a> Parse<'a> for TableInit<'a> {
 }
 
 /// Extra data associated with the `memory.init` instruction
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MemoryInit<'a> {
     /// The index of the data segment we're copying into memory.
     pub data: ast::Index<'a>,
 }
 
+impl MemoryInit<'_> {
+    #[allow(missing_docs)]
+    pub fn into_owned(self) -> MemoryInit<'static> {
+        MemoryInit {
+            data: self.data.into_owned(),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for MemoryInit<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         Ok(MemoryInit {
@@ -835,7 +1085,8 @@ impl<'a> Parse<'a> for MemoryInit<'a> {
 }
 
 /// Different ways to specify a `v128.const` instruction
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[rustfmt::skip]
 #[allow(missing_docs)]
 pub enum V128Const {
@@ -1002,7 +1253,8 @@ impl<'a> Parse<'a> for V128Const {
 }
 
 /// Lanes being shuffled in the `v8x16.shuffle` instruction
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct V8x16Shuffle {
     #[allow(missing_docs)]
     pub lanes: [u8; 16],
@@ -1034,7 +1286,8 @@ impl<'a> Parse<'a> for V8x16Shuffle {
 }
 
 /// Payload of the `select` instructions
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SelectTypes {
     #[allow(missing_docs)]
     pub tys: Vec<ast::ValType>,
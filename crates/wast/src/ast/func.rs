@@ -1,10 +1,12 @@
 use crate::ast::{self, kw};
 use crate::parser::{Parse, Parser, Result};
+use std::borrow::Cow;
 
 /// A WebAssembly function to be inserted into a module.
 ///
 /// This is a member of both the function and code sections.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Func<'a> {
     /// Where this `func` was defined.
     pub span: ast::Span,
@@ -21,7 +23,8 @@ pub struct Func<'a> {
 }
 
 /// Possible ways to define a function in the text format.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FuncKind<'a> {
     /// A function which is actually defined as an import, such as:
     ///
@@ -30,9 +33,9 @@ pub enum FuncKind<'a> {
     /// ```
     Import {
         /// The module that this function is imported from
-        module: &'a str,
+        module: Cow<'a, str>,
         /// The module field name this function is imported from
-        name: &'a str,
+        name: Cow<'a, str>,
     },
 
     /// Almost all functions, those defined inline in a wasm module.
@@ -46,6 +49,33 @@ pub enum FuncKind<'a> {
     },
 }
 
+impl Func<'_> {
+    /// Converts this function into one which no longer borrows from the
+    /// original source text.
+    pub fn into_owned(self) -> Func<'static> {
+        let kind = match self.kind {
+            FuncKind::Import { module, name } => FuncKind::Import {
+                module: Cow::Owned(module.into_owned()),
+                name: Cow::Owned(name.into_owned()),
+            },
+            FuncKind::Inline { locals, expression } => FuncKind::Inline {
+                locals: locals
+                    .into_iter()
+                    .map(|(id, ty)| (id.map(ast::Id::into_owned), ty))
+                    .collect(),
+                expression: expression.into_owned(),
+            },
+        };
+        Func {
+            span: self.span,
+            name: self.name.map(ast::Id::into_owned),
+            exports: self.exports.into_owned(),
+            kind,
+            ty: self.ty.into_owned(),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for Func<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let span = parser.parse::<kw::func>()?.0;
@@ -1,8 +1,10 @@
 use crate::ast::{self, kw};
 use crate::parser::{Parse, Parser, Result};
+use std::borrow::Cow;
 
 /// A WebAssembly global in a module
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Global<'a> {
     /// Where this `global` was defined.
     pub span: ast::Span,
@@ -18,7 +20,8 @@ pub struct Global<'a> {
 }
 
 /// Different kinds of globals that can be defined in a module.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GlobalKind<'a> {
     /// A global which is actually defined as an import, such as:
     ///
@@ -27,15 +30,36 @@ pub enum GlobalKind<'a> {
     /// ```
     Import {
         /// The module that this function is imported from
-        module: &'a str,
+        module: Cow<'a, str>,
         /// The module field name this function is imported from
-        name: &'a str,
+        name: Cow<'a, str>,
     },
 
     /// A global defined inline in the module itself
     Inline(ast::Expression<'a>),
 }
 
+impl Global<'_> {
+    /// Converts this global into one which no longer borrows from the
+    /// original source text.
+    pub fn into_owned(self) -> Global<'static> {
+        let kind = match self.kind {
+            GlobalKind::Import { module, name } => GlobalKind::Import {
+                module: Cow::Owned(module.into_owned()),
+                name: Cow::Owned(name.into_owned()),
+            },
+            GlobalKind::Inline(e) => GlobalKind::Inline(e.into_owned()),
+        };
+        Global {
+            span: self.span,
+            name: self.name.map(ast::Id::into_owned),
+            exports: self.exports.into_owned(),
+            ty: self.ty,
+            kind,
+        }
+    }
+}
+
 impl<'a> Parse<'a> for Global<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let span = parser.parse::<kw::global>()?.0;
@@ -1,15 +1,17 @@
 use crate::ast::{self, kw};
 use crate::parser::{Parse, Parser, Result};
+use std::borrow::Cow;
 
 /// An `import` statement and entry in a WebAssembly module.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Import<'a> {
     /// Where this `import` was defined
     pub span: ast::Span,
     /// The module that this statement is importing from
-    pub module: &'a str,
+    pub module: Cow<'a, str>,
     /// The name of the field in the module this statement imports from.
-    pub name: &'a str,
+    pub name: Cow<'a, str>,
     /// An optional identifier to refer to this import as in the rest of the
     /// module.
     pub id: Option<ast::Id<'a>>,
@@ -18,7 +20,8 @@ pub struct Import<'a> {
 }
 
 /// All possible types of items that can be imported into a wasm module.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum ImportKind<'a> {
     Func(ast::TypeUse<'a>),
@@ -27,6 +30,26 @@ pub enum ImportKind<'a> {
     Global(ast::GlobalType),
 }
 
+impl Import<'_> {
+    /// Converts this import into one which no longer borrows from the
+    /// original source text.
+    pub fn into_owned(self) -> Import<'static> {
+        let kind = match self.kind {
+            ImportKind::Func(t) => ImportKind::Func(t.into_owned()),
+            ImportKind::Table(t) => ImportKind::Table(t),
+            ImportKind::Memory(t) => ImportKind::Memory(t),
+            ImportKind::Global(t) => ImportKind::Global(t),
+        };
+        Import {
+            span: self.span,
+            module: Cow::Owned(self.module.into_owned()),
+            name: Cow::Owned(self.name.into_owned()),
+            id: self.id.map(ast::Id::into_owned),
+            kind,
+        }
+    }
+}
+
 impl<'a> Parse<'a> for Import<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let span = parser.parse::<kw::import>()?.0;
@@ -1,8 +1,10 @@
 use crate::ast::{self, kw};
 use crate::parser::{Parse, Parser, Result};
+use std::borrow::Cow;
 
 /// A defined WebAssembly memory instance inside of a module.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Memory<'a> {
     /// Where this `memory` was defined
     pub span: ast::Span,
@@ -16,13 +18,14 @@ pub struct Memory<'a> {
 }
 
 /// Different syntactical ways a memory can be defined in a module.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MemoryKind<'a> {
     /// This memory is actually an inlined import definition.
     #[allow(missing_docs)]
     Import {
-        module: &'a str,
-        name: &'a str,
+        module: Cow<'a, str>,
+        name: Cow<'a, str>,
         ty: ast::MemoryType,
     },
 
@@ -30,7 +33,33 @@ pub enum MemoryKind<'a> {
     Normal(ast::MemoryType),
 
     /// The data of this memory, starting from 0, explicitly listed
-    Inline(Vec<&'a [u8]>),
+    Inline(Vec<Cow<'a, [u8]>>),
+}
+
+impl Memory<'_> {
+    /// Converts this memory into one which no longer borrows from the
+    /// original source text.
+    pub fn into_owned(self) -> Memory<'static> {
+        let kind = match self.kind {
+            MemoryKind::Import { module, name, ty } => MemoryKind::Import {
+                module: Cow::Owned(module.into_owned()),
+                name: Cow::Owned(name.into_owned()),
+                ty,
+            },
+            MemoryKind::Normal(ty) => MemoryKind::Normal(ty),
+            MemoryKind::Inline(data) => MemoryKind::Inline(
+                data.into_iter()
+                    .map(|d| Cow::Owned(d.into_owned()))
+                    .collect(),
+            ),
+        };
+        Memory {
+            span: self.span,
+            name: self.name.map(ast::Id::into_owned),
+            exports: self.exports.into_owned(),
+            kind,
+        }
+    }
 }
 
 impl<'a> Parse<'a> for Memory<'a> {
@@ -48,7 +77,7 @@ impl<'a> Parse<'a> for Memory<'a> {
         let kind = if l.peek::<ast::LParen>() {
             enum Which<'a, T> {
                 Inline(Vec<T>),
-                Import(&'a str, &'a str),
+                Import(Cow<'a, str>, Cow<'a, str>),
             }
             let result = parser.parens(|parser| {
                 let mut l = parser.lookahead1();
@@ -89,7 +118,8 @@ impl<'a> Parse<'a> for Memory<'a> {
 }
 
 /// A `data` directive in a WebAssembly module.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Data<'a> {
     /// Where this `data` was defined
     pub span: ast::Span,
@@ -102,11 +132,12 @@ pub struct Data<'a> {
 
     /// Bytes for this `Data` segment, viewed as the concatenation of all the
     /// contained slices.
-    pub data: Vec<&'a [u8]>,
+    pub data: Vec<Cow<'a, [u8]>>,
 }
 
 /// Different kinds of data segments, either passive or active.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataKind<'a> {
     /// A passive data segment which isn't associated with a memory and is
     /// referenced from various instructions.
@@ -123,6 +154,30 @@ pub enum DataKind<'a> {
     },
 }
 
+impl Data<'_> {
+    /// Converts this data segment into one which no longer borrows from the
+    /// original source text.
+    pub fn into_owned(self) -> Data<'static> {
+        let kind = match self.kind {
+            DataKind::Passive => DataKind::Passive,
+            DataKind::Active { memory, offset } => DataKind::Active {
+                memory: memory.into_owned(),
+                offset: offset.into_owned(),
+            },
+        };
+        Data {
+            span: self.span,
+            name: self.name.map(ast::Id::into_owned),
+            kind,
+            data: self
+                .data
+                .into_iter()
+                .map(|d| Cow::Owned(d.into_owned()))
+                .collect(),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for Data<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let span = parser.parse::<kw::data>()?.0;
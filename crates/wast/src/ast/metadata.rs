@@ -0,0 +1,49 @@
+use crate::ast::{Instruction, ValType};
+
+/// The opcode of an [`Instruction`] in the binary format.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Opcode {
+    /// The prefix byte of this opcode, if any, such as `0xfd` for SIMD
+    /// instructions.
+    pub prefix: Option<u8>,
+    /// The opcode itself, which follows `prefix` if one is present.
+    pub code: u32,
+}
+
+/// The WebAssembly proposals that instructions can belong to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Proposal {
+    /// The original WebAssembly specification.
+    Mvp,
+    /// The SIMD proposal.
+    Simd,
+    /// The threads proposal, which adds atomic instructions.
+    Atomics,
+    /// The bulk memory operations proposal.
+    BulkMemory,
+    /// The reference types proposal.
+    ReferenceTypes,
+    /// The tail call proposal.
+    TailCall,
+    /// The sign-extension operators proposal.
+    SignExtension,
+    /// The non-trapping float-to-int conversions proposal.
+    SaturatingFloatToInt,
+}
+
+/// The types that an [`Instruction`] pops from and pushes onto the operand
+/// stack, as returned by [`Instruction::signature`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StackSignature {
+    /// The types popped by the instruction, with the top of the stack last.
+    pub params: &'static [ValType],
+    /// The types pushed by the instruction.
+    pub results: &'static [ValType],
+}
+
+impl Instruction<'_> {
+    /// Returns the proposal which introduced this instruction.
+    pub fn proposal(&self) -> Proposal {
+        self.opcode().proposal()
+    }
+}
@@ -76,7 +76,7 @@ macro_rules! custom_keyword {
 }
 
 macro_rules! reexport {
-    ($(mod $name:ident;)*) => ($(mod $name; pub use self::$name::*;)*);
+    ($($(#[$attr:meta])* mod $name:ident;)*) => ($($(#[$attr])* mod $name; pub use self::$name::*;)*);
 }
 
 reexport! {
@@ -86,12 +86,15 @@ reexport! {
 #[cfg(feature = "wasm-module")]
 reexport! {
     mod assert_expr;
+    mod builder;
     mod export;
+    #[macro_use]
     mod expr;
     mod func;
     mod global;
     mod import;
     mod memory;
+    mod metadata;
     mod module;
     mod table;
     mod types;
@@ -1,18 +1,40 @@
 use crate::ast::{self, kw};
 use crate::parser::{Parse, Parser, Result};
+use crate::visit::VisitMut;
+use std::borrow::Cow;
 
-pub use crate::resolve::Names;
+pub use crate::binary::EncodeOptions;
+pub use crate::callgraph::{CallGraph, CallKind, Edge};
+pub use crate::cfg::{BasicBlock, Cfg, CfgEdge, FlowKind};
+pub use crate::features::WasmFeatures;
+pub use crate::link::Linker;
+pub use crate::minimize::{SectionSize, SizeReport};
+pub use crate::optimize::OptimizeOptions;
+pub use crate::resolve::{Names, ResolveOptions};
+pub use crate::stats::{FuncStats, InstructionCount, ItemCounts, SectionStats, Stats};
 
 /// A `*.wat` file parser, or a parser for one parenthesized module.
 ///
 /// This is the top-level type which you'll frequently parse when working with
 /// this crate. A `*.wat` file is either one `module` s-expression or a sequence
 /// of s-expressions that are module fields.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Wat<'a> {
     #[allow(missing_docs)]
     pub module: Module<'a>,
 }
 
+impl Wat<'_> {
+    /// Converts this [`Wat`] into one which no longer borrows from the
+    /// original source text, see [`Module::into_owned`].
+    pub fn into_owned(self) -> Wat<'static> {
+        Wat {
+            module: self.module.into_owned(),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for Wat<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let module = if !parser.peek2::<kw::module>() {
@@ -30,12 +52,21 @@ impl<'a> Parse<'a> for Wat<'a> {
         } else {
             parser.parens(|parser| parser.parse())?
         };
-        module.validate(parser)?;
+        module.validate_start(parser)?;
         Ok(Wat { module })
     }
 }
 
 /// A parsed WebAssembly module.
+///
+/// Methods which only inspect a module, such as [`Module::validate`],
+/// [`Module::call_graph`], and [`Module::cfgs`], expect it to have been
+/// resolved already, for example with [`Module::resolve`], and the analyses
+/// also check that it's valid. Methods which rewrite a module, such as
+/// [`Module::encode`], [`Module::gc`], [`Module::optimize`], and
+/// [`Module::minimize`], resolve it themselves first.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Module<'a> {
     /// Where this `module` was defined
     pub span: ast::Span,
@@ -46,12 +77,14 @@ pub struct Module<'a> {
 }
 
 /// The different kinds of ways to define a module.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ModuleKind<'a> {
     /// A module defined in the textual s-expression format.
     Text(Vec<ModuleField<'a>>),
     /// A module that had its raw binary bytes defined via the `binary`
     /// directive.
-    Binary(Vec<&'a [u8]>),
+    Binary(Vec<Cow<'a, [u8]>>),
 }
 
 impl<'a> Module<'a> {
@@ -79,7 +112,16 @@ impl<'a> Module<'a> {
     /// If an error happens during resolution, such a name resolution error or
     /// items are found in the wrong order, then an error is returned.
     pub fn resolve(&mut self) -> std::result::Result<Names<'a>, crate::Error> {
-        crate::resolve::resolve(self)
+        self.resolve_with(&ResolveOptions::default())
+    }
+
+    /// Same as [`Module::resolve`], except that resolution is configured by
+    /// `options`.
+    pub fn resolve_with(
+        &mut self,
+        options: &ResolveOptions,
+    ) -> std::result::Result<Names<'a>, crate::Error> {
+        crate::resolve::resolve(self, options)
     }
 
     /// Encodes this [`Module`] to its binary form.
@@ -102,16 +144,442 @@ impl<'a> Module<'a> {
     /// its binary form and returned as a `Vec<u8>`. This is then suitable to
     /// hand off to other wasm runtimes and such.
     ///
+    /// Encoding is deterministic: the same module encoded with the same
+    /// [`EncodeOptions`] always produces byte-for-byte identical output, across
+    /// runs and across processes. If the module has any identifiers a `name`
+    /// custom section is emitted at the end of the module, which can be turned
+    /// off with [`EncodeOptions::name_section`].
+    ///
     /// # Errors
     ///
     /// This function can return an error for name resolution errors and other
     /// expansion-related errors.
     pub fn encode(&mut self) -> std::result::Result<Vec<u8>, crate::Error> {
+        self.encode_with(&EncodeOptions::default())
+    }
+
+    /// Same as [`Module::encode`], except that encoding is configured by
+    /// `options`.
+    pub fn encode_with(
+        &mut self,
+        options: &EncodeOptions,
+    ) -> std::result::Result<Vec<u8>, crate::Error> {
+        crate::features::check(self, &options.features)?;
+        self.resolve_with(&options.resolve)?;
+        if options.minimize_size {
+            crate::minimize::minimize(self)?;
+        }
+        let wasm = crate::binary::encode(self, options);
+        if let ModuleKind::Binary(_) = self.kind {
+            if options.check_binary {
+                crate::reader::check(&wasm).map_err(|e| {
+                    crate::Error::new(
+                        self.span,
+                        format!("{} at byte offset {}", e.message, e.offset),
+                    )
+                })?;
+            }
+        }
+        Ok(wasm)
+    }
+
+    /// Checks the structure of the binary WebAssembly module `bytes`.
+    ///
+    /// This checks the header and version, that sections have known ids and
+    /// appear in the right order, that LEB128 integers are well-formed, and
+    /// that each section's size matches its contents. Function bodies aren't
+    /// checked beyond their local declarations, and the module isn't
+    /// validated.
+    ///
+    /// The [`Span`](ast::Span) of a returned error is the byte offset within
+    /// `bytes` at which the problem was found, which is included when the
+    /// error is displayed. Note that this means `set_text` shouldn't be called
+    /// on the error.
+    pub fn check_binary(bytes: &[u8]) -> std::result::Result<(), crate::Error> {
+        crate::reader::check(bytes)
+            .map_err(|e| crate::Error::new(ast::Span { offset: e.offset }, e.message))
+    }
+
+    /// Decodes the binary WebAssembly module `bytes` into a [`Module`].
+    ///
+    /// The returned module has [`ModuleKind::Text`] fields, so it can be
+    /// inspected and modified like a parsed module before it's encoded again
+    /// with [`Module::encode`]. All indices in the module are numeric, and
+    /// identifiers for the module, functions, and locals are taken from the
+    /// `name` custom section if it's present. Names that are empty or that
+    /// repeat an earlier name are ignored. Other custom sections are dropped.
+    ///
+    /// Errors have the same form as those of [`Module::check_binary`]. Note
+    /// that the module isn't validated, and declarative element segments,
+    /// which can't be represented in the AST, aren't supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn foo() -> Result<(), wast::Error> {
+    /// let wasm = b"\0asm\x01\0\0\0\x01\x04\x01\x60\0\0";
+    /// let mut module = wast::Module::decode(wasm)?;
+    /// assert_eq!(module.encode()?, wasm);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn decode(bytes: &'a [u8]) -> std::result::Result<Module<'a>, crate::Error> {
+        crate::reader::decode(bytes)
+            .map_err(|e| crate::Error::new(ast::Span { offset: e.offset }, e.message))
+    }
+
+    /// Renders the binary WebAssembly module `bytes` in the style of
+    /// `wasm-objdump -x -d`.
+    ///
+    /// The output lists each section with its offset and size, the items
+    /// defined by each section, and a disassembly of every function body
+    /// with the offset and bytes of each instruction. Function names are
+    /// taken from the `name` custom section if it's present. This is useful
+    /// to see where two encodings of a module differ.
+    ///
+    /// Errors have the same form as those of [`Module::check_binary`].
+    pub fn dump(bytes: &[u8]) -> std::result::Result<String, crate::Error> {
+        crate::dump::dump(bytes)
+            .map_err(|e| crate::Error::new(ast::Span { offset: e.offset }, e.message))
+    }
+
+    /// Collects statistics about where the bytes of the binary WebAssembly
+    /// module `bytes` go.
+    ///
+    /// The returned [`Stats`] has the size of each section and each function
+    /// body, with function names taken from the `name` custom section, how
+    /// often each instruction is used, and the number of imports and exports
+    /// of each kind. It's displayed as a text report.
+    ///
+    /// The module is checked as with [`Module::check_binary`], and errors have
+    /// the same form. Unlike [`Module::decode`], items which the AST can't
+    /// represent, such as declarative element segments, are supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn foo() -> Result<(), wast::Error> {
+    /// use wast::parser::{self, ParseBuffer};
+    ///
+    /// let buf = ParseBuffer::new(r#"
+    ///     (module
+    ///         (func $answer (export "answer") (result i32)
+    ///             (i32.add (i32.const 40) (i32.const 2))))
+    /// "#)?;
+    /// let wasm = parser::parse::<wast::Wat>(&buf)?.module.encode()?;
+    /// let stats = wast::Module::stats(&wasm)?;
+    /// assert_eq!(stats.funcs[0].name.as_deref(), Some("answer"));
+    /// assert_eq!(stats.instructions[0].name, "i32.const");
+    /// assert_eq!(stats.instructions[0].count, 2);
+    /// assert_eq!(stats.exports.funcs, 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stats(bytes: &[u8]) -> std::result::Result<Stats, crate::Error> {
+        crate::stats::stats(bytes)
+            .map_err(|e| crate::Error::new(ast::Span { offset: e.offset }, e.message))
+    }
+
+    /// Compares the binary WebAssembly modules `expected` and `actual`
+    /// section by section, returning a description of each difference.
+    ///
+    /// Both modules are decoded with [`Module::decode`] and their entries
+    /// compared in order, so the differences are described in terms of the
+    /// text format, like "function 3 body differs at instruction 12: expected
+    /// `i32.add`, got `i32.sub`" or "type section has extra entry `(func
+    /// (param i32))`". Only the first difference in each function body is
+    /// reported. Custom sections, including names, aren't compared, and an
+    /// empty list is returned if the modules are otherwise the same.
+    ///
+    /// Errors are those of [`Module::decode`] for either module, with the
+    /// message saying which module it was. In particular modules with
+    /// declarative element segments can't be compared, since they can't be
+    /// decoded, even though they're valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn foo() -> Result<(), wast::Error> {
+    /// let expected = b"\0asm\x01\0\0\0\x01\x04\x01\x60\0\0";
+    /// let actual = b"\0asm\x01\0\0\0\x01\x05\x01\x60\x01\x7f\0";
+    /// assert_eq!(
+    ///     wast::Module::diff(expected, actual)?,
+    ///     ["type 0 differs: expected `(func)`, got `(func (param i32))`"],
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn diff(expected: &[u8], actual: &[u8]) -> std::result::Result<Vec<String>, crate::Error> {
+        let decode = |bytes, which| {
+            crate::reader::decode(bytes).map_err(|e| {
+                let msg = format!("failed to decode {} module: {}", which, e.message);
+                crate::Error::new(ast::Span { offset: e.offset }, msg)
+            })
+        };
+        let expected = decode(expected, "expected")?;
+        let actual = decode(actual, "actual")?;
+        Ok(crate::diff::diff(&expected, &actual))
+    }
+
+    /// Type checks this [`Module`], returning an error if it isn't valid.
+    ///
+    /// This checks that every index refers to an item which exists and that
+    /// the instructions of function bodies and constant expressions are used
+    /// with operands of the right types.
+    ///
+    /// Errors point at the item which contains the invalid instruction, and
+    /// the message mentions the position of the instruction within its body.
+    ///
+    /// A module may have at most one memory. Any number of tables are
+    /// allowed, since the reference types proposal is enabled by default, and
+    /// [`Module::validate_with`] can be used to check against other features.
+    ///
+    /// The contents of a `(module binary ...)` are decoded as with
+    /// [`Module::decode`] and then validated, and errors point at the module
+    /// with the byte offset of the problem within its contents.
+    pub fn validate(&self) -> std::result::Result<(), crate::Error> {
+        crate::validate::validate(self)
+    }
+
+    /// Same as [`Module::validate`], except that the module may only have
+    /// more than one table if `features` enables the reference types
+    /// proposal.
+    pub fn validate_with(&self, features: &WasmFeatures) -> std::result::Result<(), crate::Error> {
+        crate::validate::validate_with(self, features)
+    }
+
+    /// Builds the graph of which functions of this module may call which
+    /// others.
+    ///
+    /// Direct edges come from `call` and `return_call`. Indirect edges come
+    /// from `call_indirect` and `return_call_indirect`, to each function with
+    /// the right signature which is listed in an active element segment for
+    /// the table, or in a passive segment. If the table is imported or
+    /// exported, or written by `table.set`, `table.grow` or `table.fill`, it
+    /// may hold any function, so there's an edge to every function with the
+    /// right signature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn foo() -> Result<(), wast::Error> {
+    /// use wast::parser::{self, ParseBuffer};
+    ///
+    /// let buf = ParseBuffer::new(r#"
+    ///     (module
+    ///         (import "env" "log" (func $log (param i32)))
+    ///         (func $helper (call $log (i32.const 1)))
+    ///         (func (export "run") (call $helper)))
+    /// "#)?;
+    /// let mut module = parser::parse::<wast::Wat>(&buf)?.module;
+    /// module.resolve()?;
+    /// let graph = module.call_graph()?;
+    /// assert_eq!(graph.reachable_imports("run"), Some(vec![("env", "log")]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn call_graph(&self) -> std::result::Result<CallGraph, crate::Error> {
+        crate::callgraph::call_graph(self)
+    }
+
+    /// Builds the control-flow graph of each function body in this module,
+    /// in index order.
+    ///
+    /// Branches to a label become edges to the start of the labeled `loop`,
+    /// or to the `end` of any other labeled block, where depths are counted
+    /// as they are after [`Module::resolve`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn foo() -> Result<(), wast::Error> {
+    /// use wast::parser::{self, ParseBuffer};
+    ///
+    /// let buf = ParseBuffer::new(r#"
+    ///     (module
+    ///         (func $countdown (param i32)
+    ///             (loop $top
+    ///                 (local.set 0 (i32.sub (local.get 0) (i32.const 1)))
+    ///                 (br_if $top (local.get 0)))))
+    /// "#)?;
+    /// let mut module = parser::parse::<wast::Wat>(&buf)?.module;
+    /// module.resolve()?;
+    /// let cfgs = module.cfgs()?;
+    /// assert_eq!(cfgs[0].name(), Some("countdown"));
+    /// println!("{}", cfgs[0].to_dot());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cfgs(&self) -> std::result::Result<Vec<Cfg>, crate::Error> {
+        crate::cfg::cfgs(self)
+    }
+
+    /// Removes the functions, globals, and types which this module can't
+    /// use.
+    ///
+    /// Functions and globals are live if they're exported, are the `start`
+    /// function, are listed in an element segment, or are referenced from the
+    /// body or initializer of another live item, for example with `call`,
+    /// `ref.func`, or `global.get`. Types are live if they're used by a live
+    /// item or an import. Imports, tables, memories, and segments are always
+    /// kept. Everything else is removed and all indices are renumbered to
+    /// match, and since the `name` section is generated from the remaining
+    /// fields, names stay attached to the items they named.
+    ///
+    /// An error is returned if resolution fails or if a live item refers to
+    /// a function, global, or type which doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn foo() -> Result<(), wast::Error> {
+    /// use wast::parser::{self, ParseBuffer};
+    ///
+    /// let buf = ParseBuffer::new(r#"
+    ///     (module
+    ///         (func $unused)
+    ///         (func (export "run") (result i32) i32.const 1))
+    /// "#)?;
+    /// let mut module = parser::parse::<wast::Wat>(&buf)?.module;
+    /// module.gc()?;
+    /// if let wast::ModuleKind::Text(fields) = &module.kind {
+    ///     // only the type of `run`, `run` itself, and its export are left
+    ///     assert_eq!(fields.len(), 3);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn gc(&mut self) -> std::result::Result<(), crate::Error> {
+        self.resolve()?;
+        crate::gc::gc(self)
+    }
+
+    /// Rewrites the function bodies of this module with simple peephole
+    /// optimizations which don't change its behavior.
+    ///
+    /// The module must be valid. Constant `i32` arithmetic is folded, `nop`s
+    /// and dropped constants are removed, `local.set` followed by `local.get`
+    /// of the same local becomes `local.tee`, and branches to an immediately
+    /// following `end` are removed. See [`OptimizeOptions`] for details of
+    /// each.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if resolution fails or if the module isn't valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn foo() -> Result<(), wast::Error> {
+    /// use wast::parser::{self, ParseBuffer};
+    ///
+    /// let buf = ParseBuffer::new(r#"
+    ///     (module
+    ///         (func (result i32)
+    ///             nop
+    ///             i32.const 1
+    ///             i32.const 2
+    ///             i32.add))
+    /// "#)?;
+    /// let mut module = parser::parse::<wast::Wat>(&buf)?.module;
+    /// module.optimize()?;
+    /// if let wast::ModuleKind::Text(fields) = &module.kind {
+    ///     if let wast::ModuleField::Func(f) = &fields[1] {
+    ///         if let wast::FuncKind::Inline { expression, .. } = &f.kind {
+    ///             assert_eq!(expression.instrs, [wast::Instruction::I32Const(3)]);
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn optimize(&mut self) -> std::result::Result<(), crate::Error> {
+        self.optimize_with(&OptimizeOptions::default())
+    }
+
+    /// Same as [`Module::optimize`], except that the rewrites applied are
+    /// configured by `options`.
+    pub fn optimize_with(
+        &mut self,
+        options: &OptimizeOptions,
+    ) -> std::result::Result<(), crate::Error> {
         self.resolve()?;
-        Ok(crate::binary::encode(self))
+        crate::optimize::optimize(self, options)
+    }
+
+    /// Rewrites this module so that it encodes to as few bytes as possible,
+    /// returning how much each section shrank.
+    ///
+    /// Types which nothing refers to are removed, and defined functions and
+    /// globals are sorted so that the most referenced ones have the smallest
+    /// indices, which encode to the fewest bytes. The locals of each function
+    /// are also sorted so that those of the same type are declared together,
+    /// without changing what the function does.
+    ///
+    /// The returned [`SizeReport`] compares the default encoding of the module
+    /// before and after it was rewritten. To encode in one step, use
+    /// [`EncodeOptions::minimize_size`] instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if resolution fails or if the module refers to a
+    /// function, global, or type which doesn't exist.
+    pub fn minimize(&mut self) -> std::result::Result<SizeReport, crate::Error> {
+        self.resolve()?;
+        let options = EncodeOptions::default();
+        let before = crate::binary::encode(self, &options);
+        crate::minimize::minimize(self)?;
+        let after = crate::binary::encode(self, &options);
+        Ok(SizeReport::new(&before, &after))
+    }
+
+    /// Converts this [`Module`] into one which owns all of its data.
+    ///
+    /// Parsed modules borrow identifiers, strings, and data segments from the
+    /// source text they were parsed from. The returned module copies all of
+    /// that data so it can be stored or sent to other threads independently
+    /// of the original [`ParseBuffer`](crate::parser::ParseBuffer).
+    pub fn into_owned(self) -> Module<'static> {
+        let kind = match self.kind {
+            ModuleKind::Text(fields) => {
+                ModuleKind::Text(fields.into_iter().map(ModuleField::into_owned).collect())
+            }
+            ModuleKind::Binary(data) => ModuleKind::Binary(
+                data.into_iter()
+                    .map(|d| Cow::Owned(d.into_owned()))
+                    .collect(),
+            ),
+        };
+        Module {
+            span: self.span,
+            name: self.name.map(ast::Id::into_owned),
+            kind,
+        }
     }
 
-    fn validate(&self, parser: Parser<'_>) -> Result<()> {
+    /// Returns whether this module is structurally equal to `other` when the
+    /// [`Span`](ast::Span)s of both modules are ignored.
+    ///
+    /// The derived `PartialEq` implementation compares spans too, so two
+    /// modules parsed from differently formatted text are never equal with
+    /// `==`, even if they're otherwise identical.
+    pub fn eq_ignoring_spans(&self, other: &Module<'a>) -> bool {
+        struct ClearSpans;
+
+        impl<'a> VisitMut<'a> for ClearSpans {
+            fn visit_span(&mut self, span: &mut ast::Span) {
+                *span = ast::Span { offset: 0 };
+            }
+        }
+
+        let mut a = self.clone();
+        let mut b = other.clone();
+        ClearSpans.visit_module(&mut a);
+        ClearSpans.visit_module(&mut b);
+        a == b
+    }
+
+    fn validate_start(&self, parser: Parser<'_>) -> Result<()> {
         let mut starts = 0;
         if let ModuleKind::Text(fields) = &self.kind {
             for item in fields.iter() {
@@ -152,7 +620,8 @@ impl<'a> Parse<'a> for Module<'a> {
 
 /// A listing of all possible fields that can make up a WebAssembly module.
 #[allow(missing_docs)]
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ModuleField<'a> {
     Type(ast::Type<'a>),
     Import(ast::Import<'a>),
@@ -166,6 +635,25 @@ pub enum ModuleField<'a> {
     Data(ast::Data<'a>),
 }
 
+impl ModuleField<'_> {
+    /// Converts this field into one which no longer borrows from the original
+    /// source text.
+    pub fn into_owned(self) -> ModuleField<'static> {
+        match self {
+            ModuleField::Type(t) => ModuleField::Type(t.into_owned()),
+            ModuleField::Import(i) => ModuleField::Import(i.into_owned()),
+            ModuleField::Func(f) => ModuleField::Func(f.into_owned()),
+            ModuleField::Table(t) => ModuleField::Table(t.into_owned()),
+            ModuleField::Memory(m) => ModuleField::Memory(m.into_owned()),
+            ModuleField::Global(g) => ModuleField::Global(g.into_owned()),
+            ModuleField::Export(e) => ModuleField::Export(e.into_owned()),
+            ModuleField::Start(i) => ModuleField::Start(i.into_owned()),
+            ModuleField::Elem(e) => ModuleField::Elem(e.into_owned()),
+            ModuleField::Data(d) => ModuleField::Data(d.into_owned()),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for ModuleField<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         if parser.peek::<kw::r#type>() {
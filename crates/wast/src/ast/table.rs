@@ -1,8 +1,10 @@
 use crate::ast::{self, kw};
 use crate::parser::{Parse, Parser, Result};
+use std::borrow::Cow;
 
 /// A WebAssembly `table` directive in a module.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Table<'a> {
     /// Where this table was defined.
     pub span: ast::Span,
@@ -16,13 +18,14 @@ pub struct Table<'a> {
 }
 
 /// Different ways to textually define a table.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TableKind<'a> {
     /// This table is actually an inlined import definition.
     #[allow(missing_docs)]
     Import {
-        module: &'a str,
-        name: &'a str,
+        module: Cow<'a, str>,
+        name: Cow<'a, str>,
         ty: ast::TableType,
     },
 
@@ -39,6 +42,31 @@ pub enum TableKind<'a> {
     },
 }
 
+impl Table<'_> {
+    /// Converts this table into one which no longer borrows from the
+    /// original source text.
+    pub fn into_owned(self) -> Table<'static> {
+        let kind = match self.kind {
+            TableKind::Import { module, name, ty } => TableKind::Import {
+                module: Cow::Owned(module.into_owned()),
+                name: Cow::Owned(name.into_owned()),
+                ty,
+            },
+            TableKind::Normal(ty) => TableKind::Normal(ty),
+            TableKind::Inline { elem, payload } => TableKind::Inline {
+                elem,
+                payload: payload.into_owned(),
+            },
+        };
+        Table {
+            span: self.span,
+            name: self.name.map(ast::Id::into_owned),
+            exports: self.exports.into_owned(),
+            kind,
+        }
+    }
+}
+
 impl<'a> Parse<'a> for Table<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let span = parser.parse::<kw::table>()?.0;
@@ -88,7 +116,8 @@ impl<'a> Parse<'a> for Table<'a> {
 }
 
 /// An `elem` segment in a WebAssembly module.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Elem<'a> {
     /// Where this `elem` was defined.
     pub span: ast::Span,
@@ -101,7 +130,8 @@ pub struct Elem<'a> {
 }
 
 /// Different ways to define an element segment in an mdoule.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ElemKind<'a> {
     /// A passive segment that isn't associated with a table and can be used in
     /// various bulk-memory instructions.
@@ -117,7 +147,8 @@ pub enum ElemKind<'a> {
 }
 
 /// Different ways to define the element segment payload in a module.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ElemPayload<'a> {
     /// This element segment has a contiguous list of function indices
     Indices(Vec<ast::Index<'a>>),
@@ -133,6 +164,26 @@ pub enum ElemPayload<'a> {
     },
 }
 
+impl Elem<'_> {
+    /// Converts this element segment into one which no longer borrows from
+    /// the original source text.
+    pub fn into_owned(self) -> Elem<'static> {
+        let kind = match self.kind {
+            ElemKind::Passive => ElemKind::Passive,
+            ElemKind::Active { table, offset } => ElemKind::Active {
+                table: table.into_owned(),
+                offset: offset.into_owned(),
+            },
+        };
+        Elem {
+            span: self.span,
+            name: self.name.map(ast::Id::into_owned),
+            kind,
+            payload: self.payload.into_owned(),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for Elem<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let span = parser.parse::<kw::elem>()?.0;
@@ -172,6 +223,24 @@ impl<'a> Parse<'a> for Elem<'a> {
     }
 }
 
+impl ElemPayload<'_> {
+    #[allow(missing_docs)]
+    pub fn into_owned(self) -> ElemPayload<'static> {
+        match self {
+            ElemPayload::Indices(indices) => {
+                ElemPayload::Indices(indices.into_iter().map(ast::Index::into_owned).collect())
+            }
+            ElemPayload::Exprs { ty, exprs } => ElemPayload::Exprs {
+                ty,
+                exprs: exprs
+                    .into_iter()
+                    .map(|e| e.map(ast::Index::into_owned))
+                    .collect(),
+            },
+        }
+    }
+}
+
 impl<'a> Parse<'a> for ElemPayload<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         ElemPayload::parse_tail(parser, parser.parse()?)
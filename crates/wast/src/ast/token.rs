@@ -1,11 +1,13 @@
 use crate::lexer::FloatVal;
 use crate::parser::{Cursor, Parse, Parser, Peek, Result};
+use std::borrow::Cow;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::str;
 
 /// A position in the original source stream, used to render errors.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Span {
     pub(crate) offset: usize,
 }
@@ -35,24 +37,44 @@ impl Span {
 ///
 /// An identifier is used to symbolically refer to items in a a wasm module,
 /// typically via the [`Index`] type.
-#[derive(Copy, Clone)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Id<'a> {
-    name: &'a str,
+    name: Cow<'a, str>,
     span: Span,
 }
 
 impl<'a> Id<'a> {
+    /// Creates a new identifier named `name` located at `span`.
+    ///
+    /// The `name` should not contain the leading `$`.
+    pub fn new(name: impl Into<Cow<'a, str>>, span: Span) -> Id<'a> {
+        Id {
+            name: name.into(),
+            span,
+        }
+    }
+
     /// Returns the underlying name of this identifier.
     ///
     /// The name returned does not contain the leading `$`.
-    pub fn name(&self) -> &'a str {
-        self.name
+    pub fn name(&self) -> &str {
+        &self.name
     }
 
     /// Returns span of this identifier in the original source
     pub fn span(&self) -> Span {
         self.span
     }
+
+    /// Converts this identifier into one which owns its name, and so no
+    /// longer borrows from the original source text.
+    pub fn into_owned(self) -> Id<'static> {
+        Id {
+            name: Cow::Owned(self.name.into_owned()),
+            span: self.span,
+        }
+    }
 }
 
 impl<'a> Hash for Id<'a> {
@@ -75,7 +97,7 @@ impl<'a> Parse<'a> for Id<'a> {
             if let Some((name, rest)) = c.id() {
                 return Ok((
                     Id {
-                        name,
+                        name: name.into(),
                         span: c.cur_span(),
                     },
                     rest,
@@ -110,7 +132,8 @@ impl Peek for Id<'_> {
 ///
 /// The emission phase of a module will ensure that `Index::Id` is never used
 /// and switch them all to `Index::Num`.
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Index<'a> {
     /// A numerical index that this references. The index space this is
     /// referencing is implicit based on where this [`Index`] is stored.
@@ -120,6 +143,17 @@ pub enum Index<'a> {
     Id(Id<'a>),
 }
 
+impl Index<'_> {
+    /// Converts this index into one which no longer borrows from the original
+    /// source text.
+    pub fn into_owned(self) -> Index<'static> {
+        match self {
+            Index::Num(n) => Index::Num(n),
+            Index::Id(id) => Index::Id(id.into_owned()),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for Index<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let mut l = parser.lookahead1();
@@ -212,6 +246,38 @@ impl<'a> Parse<'a> for &'a str {
     }
 }
 
+impl<'a> Parse<'a> for Cow<'a, [u8]> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        Ok(Cow::Borrowed(parser.parse()?))
+    }
+}
+
+impl<'a> Parse<'a> for Cow<'a, str> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        Ok(Cow::Borrowed(parser.parse()?))
+    }
+}
+
+impl Peek for Cow<'_, [u8]> {
+    fn peek(cursor: Cursor<'_>) -> bool {
+        <&[u8]>::peek(cursor)
+    }
+
+    fn display() -> &'static str {
+        <&[u8]>::display()
+    }
+}
+
+impl Peek for Cow<'_, str> {
+    fn peek(cursor: Cursor<'_>) -> bool {
+        <&[u8]>::peek(cursor)
+    }
+
+    fn display() -> &'static str {
+        <&[u8]>::display()
+    }
+}
+
 impl Parse<'_> for String {
     fn parse(parser: Parser<'_>) -> Result<Self> {
         Ok(<&str>::parse(parser)?.to_string())
@@ -236,7 +302,8 @@ macro_rules! float {
         name: $parse:ident,
     })*) => ($(
         /// A parsed floating-point type
-        #[derive(Debug)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub struct $name {
             /// The raw bits that this floating point number represents.
             pub bits: $int,
@@ -4,6 +4,7 @@ use crate::parser::{Cursor, Parse, Parser, Peek, Result};
 /// The value types for a wasm module.
 #[allow(missing_docs)]
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ValType {
     I32,
     I64,
@@ -15,6 +16,22 @@ pub enum ValType {
     Nullref,
 }
 
+impl ValType {
+    /// Returns the name of this type as written in the text format.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValType::I32 => "i32",
+            ValType::I64 => "i64",
+            ValType::F32 => "f32",
+            ValType::F64 => "f64",
+            ValType::Anyref => "anyref",
+            ValType::Funcref => "funcref",
+            ValType::Nullref => "nullref",
+            ValType::V128 => "v128",
+        }
+    }
+}
+
 impl<'a> Parse<'a> for ValType {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let mut l = parser.lookahead1();
@@ -52,7 +69,8 @@ impl<'a> Parse<'a> for ValType {
 }
 
 /// Type for a `global` in a wasm module
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlobalType {
     /// The element type of this `global`
     pub ty: ValType,
@@ -82,7 +100,8 @@ impl<'a> Parse<'a> for GlobalType {
 /// List of different kinds of table types we can have.
 ///
 /// Currently there's only one, a `funcref`.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TableElemType {
     /// An element for a table that is a list of functions.
     Funcref,
@@ -127,7 +146,8 @@ impl Peek for TableElemType {
 }
 
 /// Min/max limits used for tables/memories.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Limits {
     /// The minimum number of units for this type.
     pub min: u32,
@@ -148,7 +168,8 @@ impl<'a> Parse<'a> for Limits {
 }
 
 /// Configuration for a table of a wasm mdoule
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TableType {
     /// Limits on the element sizes of this table
     pub limits: Limits,
@@ -166,7 +187,8 @@ impl<'a> Parse<'a> for TableType {
 }
 
 /// Configuration for a memory of a wasm module
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MemoryType {
     /// Limits on the page sizes of this memory
     pub limits: Limits,
@@ -183,7 +205,8 @@ impl<'a> Parse<'a> for MemoryType {
 }
 
 /// A function type with parameters and results.
-#[derive(Clone, Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FunctionType<'a> {
     /// The parameters of a function, optionally each having a name.
     pub params: Vec<(Option<ast::Id<'a>>, ValType)>,
@@ -233,6 +256,20 @@ impl<'a> FunctionType<'a> {
     }
 }
 
+impl FunctionType<'_> {
+    #[allow(missing_docs)]
+    pub fn into_owned(self) -> FunctionType<'static> {
+        FunctionType {
+            params: self
+                .params
+                .into_iter()
+                .map(|(id, ty)| (id.map(ast::Id::into_owned), ty))
+                .collect(),
+            results: self.results,
+        }
+    }
+}
+
 impl<'a> Parse<'a> for FunctionType<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         parser.parse::<kw::func>()?;
@@ -246,7 +283,8 @@ impl<'a> Parse<'a> for FunctionType<'a> {
 }
 
 /// A type declaration in a module
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Type<'a> {
     /// An optional name to refer to this `type` by.
     pub name: Option<ast::Id<'a>>,
@@ -254,6 +292,17 @@ pub struct Type<'a> {
     pub func: FunctionType<'a>,
 }
 
+impl Type<'_> {
+    /// Converts this type declaration into one which no longer borrows from
+    /// the original source text.
+    pub fn into_owned(self) -> Type<'static> {
+        Type {
+            name: self.name.map(ast::Id::into_owned),
+            func: self.func.into_owned(),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for Type<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         parser.parse::<kw::r#type>()?;
@@ -268,7 +317,8 @@ impl<'a> Parse<'a> for Type<'a> {
 /// This is a pretty tricky type used in a lot of places and is somewhat subtly
 /// handled as well. In general `(type)` or `(param)` annotations are parsed as
 /// this.
-#[derive(Clone, Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TypeUse<'a> {
     /// The span of the index specifier, if it was found
     pub index_span: Option<ast::Span>,
@@ -280,6 +330,15 @@ pub struct TypeUse<'a> {
 }
 
 impl<'a> TypeUse<'a> {
+    #[allow(missing_docs)]
+    pub fn into_owned(self) -> TypeUse<'static> {
+        TypeUse {
+            index_span: self.index_span,
+            index: self.index.map(ast::Index::into_owned),
+            ty: self.ty.into_owned(),
+        }
+    }
+
     /// Parse a `TypeUse`, but don't allow any names of `param` tokens.
     pub fn parse_no_names(parser: Parser<'a>) -> Result<Self> {
         TypeUse::parse_allow_names(parser, false)
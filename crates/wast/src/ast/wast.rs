@@ -1,17 +1,34 @@
 use crate::ast::{self, kw};
 use crate::parser::{Cursor, Parse, Parser, Peek, Result};
 use crate::{AssertExpression, NanPattern, V128Pattern};
+use std::borrow::Cow;
 
 /// A parsed representation of a `*.wast` file.
 ///
 /// WAST files are not officially specified but are used in the official test
 /// suite to write official spec tests for wasm. This type represents a parsed
 /// `*.wast` file which parses a list of directives in a file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Wast<'a> {
     #[allow(missing_docs)]
     pub directives: Vec<WastDirective<'a>>,
 }
 
+impl Wast<'_> {
+    /// Converts this [`Wast`] into one which no longer borrows from the
+    /// original source text, see [`Module::into_owned`](ast::Module::into_owned).
+    pub fn into_owned(self) -> Wast<'static> {
+        Wast {
+            directives: self
+                .directives
+                .into_iter()
+                .map(WastDirective::into_owned)
+                .collect(),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for Wast<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let mut directives = Vec::new();
@@ -51,28 +68,30 @@ impl Peek for WastDirectiveToken {
 /// It's not entirely clear to me what all of these are per se, but they're only
 /// really interesting to test harnesses mostly.
 #[allow(missing_docs)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WastDirective<'a> {
     Module(ast::Module<'a>),
     AssertMalformed {
         span: ast::Span,
         module: QuoteModule<'a>,
-        message: &'a str,
+        message: Cow<'a, str>,
     },
     AssertInvalid {
         span: ast::Span,
         module: ast::Module<'a>,
-        message: &'a str,
+        message: Cow<'a, str>,
     },
     Register {
         span: ast::Span,
-        name: &'a str,
+        name: Cow<'a, str>,
         module: Option<ast::Id<'a>>,
     },
     Invoke(WastInvoke<'a>),
     AssertTrap {
         span: ast::Span,
         exec: WastExecute<'a>,
-        message: &'a str,
+        message: Cow<'a, str>,
     },
     AssertReturn {
         span: ast::Span,
@@ -86,12 +105,12 @@ pub enum WastDirective<'a> {
     AssertExhaustion {
         span: ast::Span,
         call: WastInvoke<'a>,
-        message: &'a str,
+        message: Cow<'a, str>,
     },
     AssertUnlinkable {
         span: ast::Span,
         module: ast::Module<'a>,
-        message: &'a str,
+        message: Cow<'a, str>,
     },
 }
 
@@ -111,6 +130,84 @@ impl WastDirective<'_> {
             WastDirective::Invoke(i) => i.span,
         }
     }
+
+    /// Converts this directive into one which no longer borrows from the
+    /// original source text.
+    pub fn into_owned(self) -> WastDirective<'static> {
+        fn owned(s: Cow<'_, str>) -> Cow<'static, str> {
+            Cow::Owned(s.into_owned())
+        }
+        match self {
+            WastDirective::Module(m) => WastDirective::Module(m.into_owned()),
+            WastDirective::AssertMalformed {
+                span,
+                module,
+                message,
+            } => WastDirective::AssertMalformed {
+                span,
+                module: module.into_owned(),
+                message: owned(message),
+            },
+            WastDirective::AssertInvalid {
+                span,
+                module,
+                message,
+            } => WastDirective::AssertInvalid {
+                span,
+                module: module.into_owned(),
+                message: owned(message),
+            },
+            WastDirective::Register { span, name, module } => WastDirective::Register {
+                span,
+                name: owned(name),
+                module: module.map(ast::Id::into_owned),
+            },
+            WastDirective::Invoke(i) => WastDirective::Invoke(i.into_owned()),
+            WastDirective::AssertTrap {
+                span,
+                exec,
+                message,
+            } => WastDirective::AssertTrap {
+                span,
+                exec: exec.into_owned(),
+                message: owned(message),
+            },
+            WastDirective::AssertReturn {
+                span,
+                exec,
+                results,
+            } => WastDirective::AssertReturn {
+                span,
+                exec: exec.into_owned(),
+                results: results
+                    .into_iter()
+                    .map(AssertExpression::into_owned)
+                    .collect(),
+            },
+            WastDirective::AssertReturnFunc { span, invoke } => WastDirective::AssertReturnFunc {
+                span,
+                invoke: invoke.into_owned(),
+            },
+            WastDirective::AssertExhaustion {
+                span,
+                call,
+                message,
+            } => WastDirective::AssertExhaustion {
+                span,
+                call: call.into_owned(),
+                message: owned(message),
+            },
+            WastDirective::AssertUnlinkable {
+                span,
+                module,
+                message,
+            } => WastDirective::AssertUnlinkable {
+                span,
+                module: module.into_owned(),
+                message: owned(message),
+            },
+        }
+    }
 }
 
 impl<'a> Parse<'a> for WastDirective<'a> {
@@ -249,15 +346,32 @@ impl<'a> Parse<'a> for WastDirective<'a> {
 }
 
 #[allow(missing_docs)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WastExecute<'a> {
     Invoke(WastInvoke<'a>),
     Module(ast::Module<'a>),
     Get {
         module: Option<ast::Id<'a>>,
-        global: &'a str,
+        global: Cow<'a, str>,
     },
 }
 
+impl WastExecute<'_> {
+    /// Converts this action into one which no longer borrows from the
+    /// original source text.
+    pub fn into_owned(self) -> WastExecute<'static> {
+        match self {
+            WastExecute::Invoke(i) => WastExecute::Invoke(i.into_owned()),
+            WastExecute::Module(m) => WastExecute::Module(m.into_owned()),
+            WastExecute::Get { module, global } => WastExecute::Get {
+                module: module.map(ast::Id::into_owned),
+                global: Cow::Owned(global.into_owned()),
+            },
+        }
+    }
+}
+
 impl<'a> Parse<'a> for WastExecute<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let mut l = parser.lookahead1();
@@ -278,13 +392,32 @@ impl<'a> Parse<'a> for WastExecute<'a> {
 }
 
 #[allow(missing_docs)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WastInvoke<'a> {
     pub span: ast::Span,
     pub module: Option<ast::Id<'a>>,
-    pub name: &'a str,
+    pub name: Cow<'a, str>,
     pub args: Vec<ast::Expression<'a>>,
 }
 
+impl WastInvoke<'_> {
+    /// Converts this invocation into one which no longer borrows from the original
+    /// source text.
+    pub fn into_owned(self) -> WastInvoke<'static> {
+        WastInvoke {
+            span: self.span,
+            module: self.module.map(ast::Id::into_owned),
+            name: Cow::Owned(self.name.into_owned()),
+            args: self
+                .args
+                .into_iter()
+                .map(ast::Expression::into_owned)
+                .collect(),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for WastInvoke<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let span = parser.parse::<kw::invoke>()?.0;
@@ -304,9 +437,26 @@ impl<'a> Parse<'a> for WastInvoke<'a> {
 }
 
 #[allow(missing_docs)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum QuoteModule<'a> {
     Module(ast::Module<'a>),
-    Quote(Vec<&'a str>),
+    Quote(Vec<Cow<'a, str>>),
+}
+
+impl QuoteModule<'_> {
+    /// Converts this module into one which no longer borrows from the original
+    /// source text.
+    pub fn into_owned(self) -> QuoteModule<'static> {
+        match self {
+            QuoteModule::Module(m) => QuoteModule::Module(m.into_owned()),
+            QuoteModule::Quote(src) => QuoteModule::Quote(
+                src.into_iter()
+                    .map(|s| Cow::Owned(s.into_owned()))
+                    .collect(),
+            ),
+        }
+    }
 }
 
 impl<'a> Parse<'a> for QuoteModule<'a> {
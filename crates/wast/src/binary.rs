@@ -1,6 +1,89 @@
 use crate::ast::*;
+use crate::features::WasmFeatures;
+use crate::resolve::ResolveOptions;
+use crate::visit::Visit;
+
+/// Options to configure how a module is encoded to its binary form.
+///
+/// This is passed to [`Module::encode_with`](crate::Module::encode_with), and
+/// the default set of options is used by
+/// [`Module::encode`](crate::Module::encode).
+#[derive(Clone, Debug)]
+pub struct EncodeOptions {
+    pub(crate) resolve: ResolveOptions,
+    pub(crate) features: WasmFeatures,
+    pub(crate) name_section: bool,
+    pub(crate) check_binary: bool,
+    pub(crate) minimize_size: bool,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> EncodeOptions {
+        EncodeOptions {
+            resolve: ResolveOptions::default(),
+            features: WasmFeatures::default(),
+            name_section: true,
+            check_binary: false,
+            minimize_size: false,
+        }
+    }
+}
+
+impl EncodeOptions {
+    /// Creates a new set of default options.
+    pub fn new() -> EncodeOptions {
+        EncodeOptions::default()
+    }
+
+    /// Configures the options used to resolve names in the module before it's
+    /// encoded.
+    pub fn resolve(&mut self, options: ResolveOptions) -> &mut EncodeOptions {
+        self.resolve = options;
+        self
+    }
+
+    /// Configures which WebAssembly proposals the module may use.
+    ///
+    /// Encoding fails if the module uses an instruction, type, or section
+    /// from a proposal that isn't enabled in `features`. By default all
+    /// proposals are enabled.
+    pub fn features(&mut self, features: WasmFeatures) -> &mut EncodeOptions {
+        self.features = features;
+        self
+    }
+
+    /// Configures whether the `name` custom section is emitted for modules
+    /// which have identifiers such as `$foo`.
+    ///
+    /// The section is emitted by default, and is only used for debugging.
+    pub fn name_section(&mut self, enabled: bool) -> &mut EncodeOptions {
+        self.name_section = enabled;
+        self
+    }
+
+    /// Configures whether the structure of `(module binary ...)` modules is
+    /// checked with [`Module::check_binary`](crate::Module::check_binary).
+    ///
+    /// By default binary modules are returned verbatim, even if they're
+    /// malformed.
+    pub fn check_binary(&mut self, check: bool) -> &mut EncodeOptions {
+        self.check_binary = check;
+        self
+    }
+
+    /// Configures whether the module is rewritten with
+    /// [`Module::minimize`](crate::Module::minimize) before it's encoded,
+    /// making the binary as small as possible.
+    ///
+    /// This changes the indices of functions, globals, types, and locals, so
+    /// it's off by default.
+    pub fn minimize_size(&mut self, enabled: bool) -> &mut EncodeOptions {
+        self.minimize_size = enabled;
+        self
+    }
+}
 
-pub fn encode(module: &Module<'_>) -> Vec<u8> {
+pub fn encode(module: &Module<'_>, options: &EncodeOptions) -> Vec<u8> {
     let fields = match &module.kind {
         ModuleKind::Text(fields) => fields,
         ModuleKind::Binary(bytes) => {
@@ -55,9 +138,11 @@ pub fn encode(module: &Module<'_>) -> Vec<u8> {
     section_list(10, &funcs, &mut tmp, &mut wasm);
     section_list(11, &data, &mut tmp, &mut wasm);
 
-    let names = find_names(module, fields);
-    if !names.is_empty() {
-        section(0, ("name", names), &mut tmp, &mut wasm);
+    if options.name_section {
+        let names = find_names(module, fields);
+        if !names.is_empty() {
+            section(0, ("name", names), &mut tmp, &mut wasm);
+        }
     }
 
     return wasm;
@@ -76,17 +161,23 @@ pub fn encode(module: &Module<'_>) -> Vec<u8> {
     }
 
     fn contains_bulk_memory(funcs: &[&Func<'_>]) -> bool {
-        funcs
-            .iter()
-            .filter_map(|f| match &f.kind {
-                FuncKind::Inline { expression, .. } => Some(expression),
-                _ => None,
-            })
-            .flat_map(|e| e.instrs.iter())
-            .any(|i| match i {
-                Instruction::MemoryInit(_) | Instruction::DataDrop(_) => true,
-                _ => false,
-            })
+        struct Bulk(bool);
+
+        impl<'a> Visit<'a> for Bulk {
+            fn visit_memory_init(&mut self, _: &MemoryInit<'a>) {
+                self.0 = true;
+            }
+
+            fn visit_data_drop(&mut self, _: &Index<'a>) {
+                self.0 = true;
+            }
+        }
+
+        let mut bulk = Bulk(false);
+        for func in funcs {
+            bulk.visit_func(func);
+        }
+        bulk.0
     }
 }
 
@@ -419,7 +510,7 @@ impl Encode for ElemPayload<'_> {
                 for idx in exprs {
                     match idx {
                         Some(idx) => {
-                            Instruction::RefFunc(*idx).encode(e);
+                            Instruction::RefFunc(idx.clone()).encode(e);
                         }
                         None => {
                             Instruction::RefNull.encode(e);
@@ -564,12 +655,12 @@ impl Encode for Float64 {
 }
 
 struct Names<'a> {
-    module: Option<Id<'a>>,
-    funcs: Vec<(u32, Id<'a>)>,
-    locals: Vec<(u32, Vec<(u32, Id<'a>)>)>,
+    module: Option<&'a Id<'a>>,
+    funcs: Vec<(u32, &'a Id<'a>)>,
+    locals: Vec<(u32, Vec<(u32, &'a Id<'a>)>)>,
 }
 
-fn find_names<'a>(module: &Module<'a>, fields: &[ModuleField<'a>]) -> Names<'a> {
+fn find_names<'a>(module: &'a Module<'a>, fields: &'a [ModuleField<'a>]) -> Names<'a> {
     let mut funcs = Vec::new();
     let mut locals = Vec::new();
     let mut idx = 0;
@@ -581,28 +672,28 @@ fn find_names<'a>(module: &Module<'a>, fields: &[ModuleField<'a>]) -> Names<'a>
                     _ => continue,
                 }
 
-                if let Some(id) = i.id {
+                if let Some(id) = &i.id {
                     funcs.push((idx, id));
                 }
 
                 idx += 1;
             }
             ModuleField::Func(f) => {
-                if let Some(id) = f.name {
+                if let Some(id) = &f.name {
                     funcs.push((idx, id));
                 }
                 let mut local_names = Vec::new();
                 let mut local_idx = 0;
                 for (name, _) in f.ty.ty.params.iter() {
                     if let Some(id) = name {
-                        local_names.push((local_idx, *id));
+                        local_names.push((local_idx, id));
                     }
                     local_idx += 1;
                 }
                 if let FuncKind::Inline { locals, .. } = &f.kind {
                     for (name, _) in locals {
                         if let Some(id) = name {
-                            local_names.push((local_idx, *id));
+                            local_names.push((local_idx, id));
                         }
                         local_idx += 1;
                     }
@@ -617,7 +708,7 @@ fn find_names<'a>(module: &Module<'a>, fields: &[ModuleField<'a>]) -> Names<'a>
     }
 
     Names {
-        module: module.name,
+        module: module.name.as_ref(),
         funcs,
         locals,
     }
@@ -0,0 +1,272 @@
+use crate::ast::*;
+use crate::resolve::{num, type_key};
+use crate::Error;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// The functions of a module and the calls each of them may make, as
+/// returned by [`Module::call_graph`](crate::Module::call_graph).
+///
+/// Functions are identified by their index, counting imported functions
+/// first.
+#[derive(Clone, Debug)]
+pub struct CallGraph {
+    funcs: Vec<Node>,
+    edges: Vec<Edge>,
+    exports: Vec<(String, u32)>,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    name: Option<String>,
+    /// The module and field names of an imported function.
+    import: Option<(String, String)>,
+}
+
+/// A call from one function to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Edge {
+    /// The index of the calling function.
+    pub caller: u32,
+    /// The index of the function which may be called.
+    pub callee: u32,
+    /// How the function is called.
+    pub kind: CallKind,
+}
+
+/// How one function calls another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CallKind {
+    /// A `call` or `return_call` of the function.
+    Direct,
+    /// A `call_indirect` or `return_call_indirect` which may call the
+    /// function, since it has the right signature and it's listed in an
+    /// element segment for the table, or the table may hold any function.
+    Indirect,
+}
+
+/// Builds the call graph of `module`, which must be resolved and valid.
+pub fn call_graph(module: &Module<'_>) -> Result<CallGraph, Error> {
+    crate::validate::validate(module)?;
+    let mut graph = CallGraph {
+        funcs: Vec::new(),
+        edges: Vec::new(),
+        exports: Vec::new(),
+    };
+    let fields = match &module.kind {
+        ModuleKind::Text(fields) => fields,
+        ModuleKind::Binary(_) => return Ok(graph),
+    };
+
+    let types = fields
+        .iter()
+        .filter_map(|f| match f {
+            ModuleField::Type(ty) => Some(type_key(&ty.func)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let signature = |ty: &TypeUse<'_>| match &ty.index {
+        Some(index) => types[num(index) as usize].clone(),
+        None => type_key(&ty.ty),
+    };
+
+    // The signature of each function, and the functions in element segments
+    // along with the table they're for, or `None` for passive segments which
+    // could be copied into any table.
+    //
+    // Tables which are imported or exported may be filled by the host, and
+    // those written by instructions may hold any function whose reference
+    // is taken, so each of these is considered to hold every function.
+    let mut signatures = Vec::new();
+    let mut elems = Vec::new();
+    let mut open_tables = Vec::new();
+    let mut exported_tables = Vec::new();
+    for field in fields {
+        match field {
+            ModuleField::Import(i) => match &i.kind {
+                ImportKind::Func(ty) => {
+                    signatures.push(signature(ty));
+                    graph.funcs.push(Node {
+                        name: i.id.as_ref().map(|id| id.name().to_string()),
+                        import: Some((i.module.to_string(), i.name.to_string())),
+                    });
+                }
+                ImportKind::Table(_) => open_tables.push(true),
+                _ => {}
+            },
+            ModuleField::Table(t) => match t.kind {
+                TableKind::Import { .. } => open_tables.push(true),
+                _ => open_tables.push(false),
+            },
+            ModuleField::Func(f) => {
+                signatures.push(signature(&f.ty));
+                graph.funcs.push(Node {
+                    name: f.name.as_ref().map(|id| id.name().to_string()),
+                    import: None,
+                });
+            }
+            ModuleField::Elem(e) => {
+                let table = match &e.kind {
+                    ElemKind::Active { table, .. } => Some(num(table)),
+                    ElemKind::Passive => None,
+                };
+                let funcs: Vec<u32> = match &e.payload {
+                    ElemPayload::Indices(indices) => indices.iter().map(num).collect(),
+                    ElemPayload::Exprs { exprs, .. } => exprs.iter().flatten().map(num).collect(),
+                };
+                elems.push((table, funcs));
+            }
+            ModuleField::Export(e) => match &e.kind {
+                ExportKind::Func(index) => graph.exports.push((e.name.to_string(), num(index))),
+                ExportKind::Table(index) => exported_tables.push(num(index)),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    let mut edges = BTreeSet::new();
+    let bodies = fields.iter().filter_map(|f| match f {
+        ModuleField::Func(Func {
+            kind: FuncKind::Inline { expression, .. },
+            ..
+        }) => Some(expression),
+        _ => None,
+    });
+    for table in exported_tables {
+        open_tables[table as usize] = true;
+    }
+    for body in bodies.clone() {
+        for instr in body.instrs.iter() {
+            match instr {
+                Instruction::TableSet(table)
+                | Instruction::TableGrow(table)
+                | Instruction::TableFill(table) => open_tables[num(table) as usize] = true,
+                _ => {}
+            }
+        }
+    }
+    let imported = graph.funcs.iter().filter(|f| f.import.is_some()).count();
+    for (i, body) in bodies.enumerate() {
+        let caller = (imported + i) as u32;
+        for instr in body.instrs.iter() {
+            match instr {
+                Instruction::Call(index) | Instruction::ReturnCall(index) => {
+                    edges.insert(Edge {
+                        caller,
+                        callee: num(index),
+                        kind: CallKind::Direct,
+                    });
+                }
+                Instruction::CallIndirect(c) | Instruction::ReturnCallIndirect(c) => {
+                    let table = num(&c.table);
+                    let sig = signature(&c.ty);
+                    let candidates: Vec<u32> = if open_tables[table as usize] {
+                        (0..signatures.len() as u32).collect()
+                    } else {
+                        elems
+                            .iter()
+                            .filter(|(t, _)| t.is_none() || *t == Some(table))
+                            .flat_map(|(_, funcs)| funcs.iter().cloned())
+                            .collect()
+                    };
+                    for callee in candidates {
+                        if signatures[callee as usize] == sig {
+                            edges.insert(Edge {
+                                caller,
+                                callee,
+                                kind: CallKind::Indirect,
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    graph.edges = edges.into_iter().collect();
+    Ok(graph)
+}
+
+impl CallGraph {
+    /// Returns every call in the module, sorted by caller and then callee.
+    ///
+    /// A function which may call another both directly and indirectly has an
+    /// edge of each kind.
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// Returns the calls made by the function `func`.
+    pub fn callees(&self, func: u32) -> impl Iterator<Item = &Edge> + '_ {
+        self.edges.iter().filter(move |e| e.caller == func)
+    }
+
+    /// Returns the functions which may run when `func` is called, including
+    /// `func` itself, in index order.
+    pub fn reachable(&self, func: u32) -> Vec<u32> {
+        let mut seen = vec![false; self.funcs.len()];
+        let mut pending = vec![func];
+        while let Some(func) = pending.pop() {
+            match seen.get_mut(func as usize) {
+                Some(true) | None => continue,
+                Some(seen) => *seen = true,
+            }
+            pending.extend(self.callees(func).map(|e| e.callee));
+        }
+        (0..self.funcs.len() as u32)
+            .filter(|i| seen[*i as usize])
+            .collect()
+    }
+
+    /// Returns the module and field names of the imported functions which
+    /// may be called, directly or transitively, when the function exported
+    /// as `export` is called.
+    ///
+    /// Returns `None` if no function is exported with that name.
+    pub fn reachable_imports(&self, export: &str) -> Option<Vec<(&str, &str)>> {
+        let (_, func) = self.exports.iter().find(|(name, _)| name == export)?;
+        let imports = self
+            .reachable(*func)
+            .into_iter()
+            .filter_map(|i| self.funcs[i as usize].import.as_ref())
+            .map(|(module, name)| (&module[..], &name[..]))
+            .collect();
+        Some(imports)
+    }
+
+    /// Renders this graph in the DOT language of Graphviz.
+    ///
+    /// Imported functions are drawn as boxes, exported functions are labeled
+    /// with their export names, and indirect calls are drawn dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        for (i, func) in self.funcs.iter().enumerate() {
+            let mut label = match &func.name {
+                Some(name) => format!("${}", name),
+                None => format!("func {}", i),
+            };
+            if let Some((module, name)) = &func.import {
+                label.push_str(&format!("\n(import {:?} {:?})", module, name));
+            }
+            for (name, _) in self.exports.iter().filter(|(_, f)| *f as usize == i) {
+                label.push_str(&format!("\n(export {:?})", name));
+            }
+            let shape = if func.import.is_some() {
+                ", shape=box"
+            } else {
+                ""
+            };
+            writeln!(dot, "    f{} [label={:?}{}];", i, label, shape).unwrap();
+        }
+        for edge in self.edges.iter() {
+            let style = match edge.kind {
+                CallKind::Direct => "",
+                CallKind::Indirect => " [style=dashed]",
+            };
+            writeln!(dot, "    f{} -> f{}{};", edge.caller, edge.callee, style).unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
@@ -0,0 +1,329 @@
+use crate::ast::*;
+use crate::dump::instruction;
+use crate::resolve::num;
+use crate::Error;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use std::ops::Range;
+
+/// The control-flow graph of one function body, as returned by
+/// [`Module::cfgs`](crate::Module::cfgs).
+///
+/// Each basic block is a range of the body's instructions which always run
+/// in sequence. The last block is an empty exit block, which returning and
+/// falling off the end of the body lead to.
+#[derive(Clone, Debug)]
+pub struct Cfg {
+    func: u32,
+    name: Option<String>,
+    blocks: Vec<BasicBlock>,
+    edges: Vec<CfgEdge>,
+    /// The text of each instruction, to render blocks with.
+    instrs: Vec<String>,
+}
+
+/// A sequence of instructions with one entry and one exit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BasicBlock {
+    /// The indices of the block's instructions within
+    /// [`Expression::instrs`].
+    pub range: Range<usize>,
+    /// The span of the label of the innermost labeled `block`, `loop`, or
+    /// `if` around the block's first instruction, or of the function if
+    /// there's none, since instructions don't record where they were parsed.
+    pub span: Span,
+}
+
+/// A possible transfer of control from the end of one basic block to the
+/// start of another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CfgEdge {
+    /// The index of the block control leaves.
+    pub from: usize,
+    /// The index of the block control enters.
+    pub to: usize,
+    /// How control gets there.
+    pub kind: FlowKind,
+}
+
+/// How control moves along a [`CfgEdge`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FlowKind {
+    /// Control continues with the next instruction, including into the
+    /// `then` arm of an `if`.
+    Fallthrough,
+    /// Control jumps, with a branch to a label, a `return`, an `if` whose
+    /// condition is false, or the `else` at the end of a `then` arm.
+    Branch,
+}
+
+/// Builds the control-flow graph of each function body in `module`, which
+/// must be resolved and valid.
+pub fn cfgs(module: &Module<'_>) -> Result<Vec<Cfg>, Error> {
+    crate::validate::validate(module)?;
+    let fields = match &module.kind {
+        ModuleKind::Text(fields) => fields,
+        ModuleKind::Binary(_) => return Ok(Vec::new()),
+    };
+    let mut func = fields
+        .iter()
+        .filter(|f| match f {
+            ModuleField::Import(i) => matches!(i.kind, ImportKind::Func(_)),
+            _ => false,
+        })
+        .count() as u32;
+    let mut cfgs = Vec::new();
+    for field in fields {
+        if let ModuleField::Func(f) = field {
+            if let FuncKind::Inline { expression, .. } = &f.kind {
+                let mut cfg = Cfg::new(expression, f.span);
+                cfg.func = func;
+                cfg.name = f.name.as_ref().map(|id| id.name().to_string());
+                cfgs.push(cfg);
+            }
+            func += 1;
+        }
+    }
+    Ok(cfgs)
+}
+
+/// A block, loop, or `if` enclosing an instruction.
+struct Frame {
+    /// The index of the `loop` instruction, for loops.
+    start: Option<usize>,
+    /// The index of the `end` instruction.
+    end: usize,
+}
+
+impl Cfg {
+    /// Builds the graph of the structured and resolved `expr`, which is the
+    /// body of a function with the span `span`.
+    fn new(expr: &Expression<'_>, span: Span) -> Cfg {
+        let instrs = &expr.instrs;
+
+        // Match up each `block`, `loop`, and `if` with its `else` and `end`,
+        // and find the span of the innermost label around each instruction,
+        // where a block's own instructions are inside of it.
+        let mut ends = HashMap::new();
+        let mut elses = HashMap::new();
+        let mut open = Vec::new();
+        let mut labels = vec![span];
+        let mut spans = Vec::with_capacity(instrs.len());
+        for (i, instr) in instrs.iter().enumerate() {
+            match instr {
+                Instruction::Block(bt) | Instruction::Loop(bt) | Instruction::If(bt) => {
+                    open.push(i);
+                    let label = bt.label.as_ref().map(|l| l.span());
+                    labels.push(label.unwrap_or(*labels.last().unwrap()));
+                }
+                Instruction::Else(_) => {
+                    elses.insert(*open.last().unwrap(), i);
+                }
+                Instruction::End(_) => {
+                    ends.insert(open.pop().unwrap(), i);
+                }
+                _ => {}
+            }
+            spans.push(*labels.last().unwrap());
+            if let Instruction::End(_) = instr {
+                labels.pop();
+            }
+        }
+
+        // Blocks start at the body's first instruction, at each `loop` and
+        // `end` since they can be branched to, and after each instruction
+        // which transfers control.
+        let mut leaders = BTreeSet::new();
+        leaders.insert(0);
+        for (i, instr) in instrs.iter().enumerate() {
+            match instr {
+                Instruction::Loop(_) | Instruction::End(_) => {
+                    leaders.insert(i);
+                }
+                _ if transfers_control(instr) => {
+                    leaders.insert(i + 1);
+                }
+                _ => {}
+            }
+        }
+        let exit = leaders.iter().filter(|i| **i < instrs.len()).count();
+        let mut starts = leaders
+            .into_iter()
+            .filter(|i| *i < instrs.len())
+            .collect::<Vec<_>>();
+        starts.push(instrs.len());
+        let block_at = |i: usize| starts.binary_search(&i).unwrap();
+        let mut blocks = starts
+            .windows(2)
+            .map(|w| BasicBlock {
+                range: w[0]..w[1],
+                span: spans[w[0]],
+            })
+            .collect::<Vec<_>>();
+        blocks.push(BasicBlock {
+            range: instrs.len()..instrs.len(),
+            span,
+        });
+
+        let mut edges = BTreeSet::new();
+        let mut frames: Vec<Frame> = Vec::new();
+        let mut block = 0;
+        for (i, instr) in instrs.iter().enumerate() {
+            if i == blocks[block].range.end {
+                block += 1;
+            }
+            let target = |frames: &[Frame], label: &Index<'_>| {
+                let depth = num(label) as usize;
+                if depth == frames.len() {
+                    return exit;
+                }
+                let frame = &frames[frames.len() - 1 - depth];
+                block_at(frame.start.unwrap_or(frame.end))
+            };
+            let mut edge = |to, kind| {
+                edges.insert(CfgEdge {
+                    from: block,
+                    to,
+                    kind,
+                });
+            };
+            match instr {
+                Instruction::Block(_) | Instruction::Loop(_) | Instruction::If(_) => {
+                    let start = match instr {
+                        Instruction::Loop(_) => Some(i),
+                        _ => None,
+                    };
+                    frames.push(Frame {
+                        start,
+                        end: ends[&i],
+                    });
+                    if let Instruction::If(_) = instr {
+                        edge(block_at(i + 1), FlowKind::Fallthrough);
+                        let otherwise = match elses.get(&i) {
+                            Some(e) => *e + 1,
+                            None => ends[&i],
+                        };
+                        edge(block_at(otherwise), FlowKind::Branch);
+                    }
+                }
+                Instruction::Else(_) => {
+                    edge(block_at(frames.last().unwrap().end), FlowKind::Branch)
+                }
+                Instruction::End(_) => {
+                    frames.pop();
+                }
+                Instruction::Br(label) => edge(target(&frames, label), FlowKind::Branch),
+                Instruction::BrIf(label) => {
+                    edge(target(&frames, label), FlowKind::Branch);
+                    edge(block + 1, FlowKind::Fallthrough);
+                }
+                Instruction::BrTable(table) => {
+                    for label in table.labels.iter().chain(Some(&table.default)) {
+                        edge(target(&frames, label), FlowKind::Branch);
+                    }
+                }
+                Instruction::Return
+                | Instruction::ReturnCall(_)
+                | Instruction::ReturnCallIndirect(_) => edge(exit, FlowKind::Branch),
+                _ => {}
+            }
+            if i + 1 == blocks[block].range.end && !transfers_control(instr) {
+                edge(block + 1, FlowKind::Fallthrough);
+            }
+        }
+
+        Cfg {
+            func: 0,
+            name: None,
+            blocks,
+            edges: edges.into_iter().collect(),
+            instrs: instrs.iter().map(instruction).collect(),
+        }
+    }
+
+    /// Returns the index of the function, counting imported functions.
+    pub fn func(&self) -> u32 {
+        self.func
+    }
+
+    /// Returns the name of the function, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the basic blocks of the body in order, where the first is the
+    /// entry block and the last is the exit block, which are the same block
+    /// for an empty body.
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+
+    /// Returns the index of the exit block.
+    pub fn exit(&self) -> usize {
+        self.blocks.len() - 1
+    }
+
+    /// Returns every edge, sorted by the block it leaves and then the block
+    /// it enters.
+    pub fn edges(&self) -> &[CfgEdge] {
+        &self.edges
+    }
+
+    /// Returns the edges leaving the block `block`.
+    pub fn successors(&self, block: usize) -> impl Iterator<Item = &CfgEdge> + '_ {
+        self.edges.iter().filter(move |e| e.from == block)
+    }
+
+    /// Renders this graph in the DOT language of Graphviz.
+    ///
+    /// Each block is labeled with its instructions, and branches are drawn
+    /// dashed.
+    pub fn to_dot(&self) -> String {
+        let title = match &self.name {
+            Some(name) => format!("${}", name),
+            None => format!("func {}", self.func),
+        };
+        let mut dot = format!("digraph {:?} {{\n", title);
+        dot.push_str("    node [shape=box];\n");
+        for (i, block) in self.blocks.iter().enumerate() {
+            let mut label = String::new();
+            if i == self.exit() {
+                label.push_str("exit\\l");
+            }
+            for instr in self.instrs[block.range.clone()].iter() {
+                // Escape the text as a string, without the quotes, so each
+                // line can be left-justified with `\l`.
+                let instr = format!("{:?}", instr);
+                label.push_str(&instr[1..instr.len() - 1]);
+                label.push_str("\\l");
+            }
+            writeln!(dot, "    b{} [label=\"{}\"];", i, label).unwrap();
+        }
+        for edge in self.edges.iter() {
+            let style = match edge.kind {
+                FlowKind::Fallthrough => "",
+                FlowKind::Branch => " [style=dashed]",
+            };
+            writeln!(dot, "    b{} -> b{}{};", edge.from, edge.to, style).unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Returns whether `instr` ends a basic block because it may jump, or
+/// doesn't continue to the next instruction.
+fn transfers_control(instr: &Instruction<'_>) -> bool {
+    matches!(
+        instr,
+        Instruction::If(_)
+            | Instruction::Else(_)
+            | Instruction::Br(_)
+            | Instruction::BrIf(_)
+            | Instruction::BrTable(_)
+            | Instruction::Return
+            | Instruction::ReturnCall(_)
+            | Instruction::ReturnCallIndirect(_)
+            | Instruction::Unreachable
+    )
+}
@@ -0,0 +1,256 @@
+//! A lossless concrete syntax tree (CST) of the text format.
+//!
+//! The AST representation of a module, such as [`Module`](crate::Module), is
+//! a fairly lossy view of the original source text: whitespace and comments
+//! are dropped, folded instructions are flattened, and deprecated spellings
+//! such as `get_local` are normalized. This module instead provides a
+//! [`SyntaxTree`] which is a plain tree of s-expressions built from the
+//! tokens of a [`ParseBuffer`] where every node owns the whitespace and
+//! comments ("trivia") that precede it.
+//!
+//! Printing a [`SyntaxTree`] with its `Display` implementation reproduces the
+//! original input byte-for-byte, so tools like renamers or formatters can
+//! rewrite a few tokens and leave the rest of a file untouched.
+//!
+//! # Examples
+//!
+//! ```
+//! use wast::cst::SyntaxTree;
+//! use wast::parser::ParseBuffer;
+//!
+//! # fn foo() -> Result<(), wast::Error> {
+//! let wat = "(module\n  (func $f) ;; hello\n  (start $f))";
+//! let buf = ParseBuffer::new(wat)?;
+//! let mut tree = SyntaxTree::new(&buf)?;
+//! tree.for_each_atom_mut(|atom| {
+//!     if atom.text == "$f" {
+//!         atom.text = "$main".into();
+//!     }
+//! });
+//! assert_eq!(tree.to_string(), "(module\n  (func $main) ;; hello\n  (start $main))");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::lexer::{Comment, Source, Token};
+use crate::parser::ParseBuffer;
+use crate::{Error, Span};
+use std::borrow::Cow;
+use std::fmt;
+use std::mem;
+
+/// A lossless tree of s-expressions for an entire input.
+#[derive(Debug, Clone)]
+pub struct SyntaxTree<'a> {
+    /// The top-level nodes of the input.
+    pub nodes: Vec<Node<'a>>,
+    /// Trivia found after the last node of the input.
+    pub trailing: Vec<Trivia<'a>>,
+}
+
+/// A node in a [`SyntaxTree`], either a parenthesized list or a single token.
+#[derive(Debug, Clone)]
+pub enum Node<'a> {
+    /// A parenthesized list of nodes, such as `(func $f)`.
+    List(List<'a>),
+    /// A single non-parenthesis token, such as `func` or `$f`.
+    Atom(Atom<'a>),
+}
+
+/// A parenthesized list of [`Node`]s.
+#[derive(Debug, Clone)]
+pub struct List<'a> {
+    /// Trivia preceding the opening `(` of this list.
+    pub leading: Vec<Trivia<'a>>,
+    /// Where the opening `(` of this list is located.
+    pub span: Span,
+    /// The nodes contained within this list.
+    pub children: Vec<Node<'a>>,
+    /// Trivia preceding the closing `)` of this list.
+    pub closing: Vec<Trivia<'a>>,
+}
+
+/// A single token within a [`SyntaxTree`].
+#[derive(Debug, Clone)]
+pub struct Atom<'a> {
+    /// Trivia preceding this token.
+    pub leading: Vec<Trivia<'a>>,
+    /// Where this token was originally located.
+    pub span: Span,
+    /// What kind of token this is.
+    pub kind: AtomKind,
+    /// The text of this token, which may be replaced to rewrite the source.
+    pub text: Cow<'a, str>,
+}
+
+/// The different kinds of tokens an [`Atom`] can be.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum AtomKind {
+    Id,
+    Keyword,
+    Reserved,
+    Integer,
+    Float,
+    String,
+}
+
+/// Whitespace or a comment which precedes a [`Node`].
+#[derive(Debug, Clone)]
+pub enum Trivia<'a> {
+    /// A run of whitespace.
+    Whitespace(&'a str),
+    /// A line or block comment.
+    Comment(Comment<'a>),
+}
+
+impl<'a> SyntaxTree<'a> {
+    /// Builds the syntax tree for all of the tokens in `buf`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the parentheses in `buf` are unbalanced.
+    pub fn new(buf: &ParseBuffer<'a>) -> Result<SyntaxTree<'a>, Error> {
+        let input = buf.input();
+        let error = |src: &str, msg: &str| {
+            let offset = src.as_ptr() as usize - input.as_ptr() as usize;
+            let mut err = Error::new(Span { offset }, msg.to_string());
+            err.set_text(input);
+            err
+        };
+
+        // A stack of partially-built lists, where the bottom entry is the
+        // top-level list of nodes which has no parentheses.
+        let mut stack = vec![(None, Vec::new())];
+        let mut trivia = Vec::new();
+        for source in buf.sources() {
+            let token = match source {
+                Source::Whitespace(s) => {
+                    trivia.push(Trivia::Whitespace(s));
+                    continue;
+                }
+                Source::Comment(c) => {
+                    trivia.push(Trivia::Comment(*c));
+                    continue;
+                }
+                Source::Token(token) => token,
+            };
+            let offset = token.src().as_ptr() as usize - input.as_ptr() as usize;
+            let span = Span { offset };
+            let leading = mem::take(&mut trivia);
+            let kind = match token {
+                Token::LParen(_) => {
+                    stack.push((Some((leading, span)), Vec::new()));
+                    continue;
+                }
+                Token::RParen(src) => {
+                    let (open, children) = stack.pop().unwrap();
+                    let (open_leading, span) = match open {
+                        Some(open) => open,
+                        None => return Err(error(src, "unexpected `)` without matching `(`")),
+                    };
+                    stack.last_mut().unwrap().1.push(Node::List(List {
+                        leading: open_leading,
+                        span,
+                        children,
+                        closing: leading,
+                    }));
+                    continue;
+                }
+                Token::Id(_) => AtomKind::Id,
+                Token::Keyword(_) => AtomKind::Keyword,
+                Token::Reserved(_) => AtomKind::Reserved,
+                Token::Integer(_) => AtomKind::Integer,
+                Token::Float(_) => AtomKind::Float,
+                Token::String { .. } => AtomKind::String,
+            };
+            stack.last_mut().unwrap().1.push(Node::Atom(Atom {
+                leading,
+                span,
+                kind,
+                text: token.src().into(),
+            }));
+        }
+        let (open, nodes) = stack.pop().unwrap();
+        if let Some((_, span)) = open {
+            let mut err = Error::new(span, "unclosed `(`".to_string());
+            err.set_text(input);
+            return Err(err);
+        }
+        Ok(SyntaxTree {
+            nodes,
+            trailing: trivia,
+        })
+    }
+
+    /// Invokes `f` with every [`Atom`] in this tree, in source order.
+    pub fn for_each_atom_mut(&mut self, mut f: impl FnMut(&mut Atom<'a>)) {
+        fn visit<'a>(nodes: &mut [Node<'a>], f: &mut dyn FnMut(&mut Atom<'a>)) {
+            for node in nodes {
+                match node {
+                    Node::List(list) => visit(&mut list.children, f),
+                    Node::Atom(atom) => f(atom),
+                }
+            }
+        }
+        visit(&mut self.nodes, &mut f)
+    }
+}
+
+impl<'a> List<'a> {
+    /// Returns the keyword at the head of this list, if any, such as `func`
+    /// for `(func $f)`.
+    pub fn head(&self) -> Option<&str> {
+        match self.children.first()? {
+            Node::Atom(a) if a.kind == AtomKind::Keyword => Some(&a.text),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SyntaxTree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for node in self.nodes.iter() {
+            node.fmt(f)?;
+        }
+        fmt_trivia(&self.trailing, f)
+    }
+}
+
+impl fmt::Display for Node<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Node::List(list) => list.fmt(f),
+            Node::Atom(atom) => atom.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for List<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_trivia(&self.leading, f)?;
+        f.write_str("(")?;
+        for node in self.children.iter() {
+            node.fmt(f)?;
+        }
+        fmt_trivia(&self.closing, f)?;
+        f.write_str(")")
+    }
+}
+
+impl fmt::Display for Atom<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_trivia(&self.leading, f)?;
+        f.write_str(&self.text)
+    }
+}
+
+fn fmt_trivia(trivia: &[Trivia<'_>], f: &mut fmt::Formatter) -> fmt::Result {
+    for t in trivia {
+        match t {
+            Trivia::Whitespace(s) => f.write_str(s)?,
+            Trivia::Comment(c) => f.write_str(c.src())?,
+        }
+    }
+    Ok(())
+}
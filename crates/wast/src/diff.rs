@@ -0,0 +1,295 @@
+//! Structural comparison of two binary modules.
+//!
+//! Both modules are decoded and their sections compared entry by entry, so
+//! differences are reported in terms of types, functions, instructions, and
+//! so on rather than byte offsets.
+
+use crate::ast::*;
+use crate::dump::{elem_type, instruction, join};
+use crate::resolve::num;
+
+/// Returns a description of each difference between the decoded modules
+/// `expected` and `actual`, in section order.
+pub fn diff(expected: &Module<'_>, actual: &Module<'_>) -> Vec<String> {
+    let expected = Sections::new(expected);
+    let actual = Sections::new(actual);
+    let mut diff = Diff::default();
+
+    diff.entries("type", 0, &expected.types, &actual.types);
+    diff.entries("import", 0, &expected.imports, &actual.imports);
+    diff.funcs(&expected, &actual);
+    let base = &expected.imported;
+    diff.entries("table", base[1], &expected.tables, &actual.tables);
+    diff.entries("memory", base[2], &expected.memories, &actual.memories);
+    diff.entries("global", base[3], &expected.globals, &actual.globals);
+    diff.entries("export", 0, &expected.exports, &actual.exports);
+    diff.entries("start", 0, &expected.start, &actual.start);
+    diff.entries("elem", 0, &expected.elems, &actual.elems);
+    diff.entries("data", 0, &expected.data, &actual.data);
+    diff.out
+}
+
+/// The entries of each section of a module, rendered in the text format.
+#[derive(Default)]
+struct Sections {
+    types: Vec<String>,
+    imports: Vec<String>,
+    /// The number of imported functions, tables, memories, and globals.
+    imported: [u32; 4],
+    funcs: Vec<Func>,
+    tables: Vec<String>,
+    memories: Vec<String>,
+    globals: Vec<String>,
+    exports: Vec<String>,
+    start: Vec<String>,
+    elems: Vec<String>,
+    data: Vec<String>,
+}
+
+struct Func {
+    /// The type and locals of the function.
+    header: String,
+    body: Vec<String>,
+}
+
+impl Sections {
+    fn new(module: &Module<'_>) -> Sections {
+        let mut sections = Sections::default();
+        let fields = match &module.kind {
+            ModuleKind::Text(fields) => fields,
+            ModuleKind::Binary(_) => return sections,
+        };
+        for field in fields {
+            match field {
+                ModuleField::Type(ty) => sections.types.push(func_type(&ty.func)),
+                ModuleField::Import(import) => {
+                    let (kind, desc) = match &import.kind {
+                        ImportKind::Func(ty) => (0, format!("(func {})", type_use(ty))),
+                        ImportKind::Table(ty) => (1, format!("(table {})", table_type(ty))),
+                        ImportKind::Memory(ty) => (2, format!("(memory {})", memory_type(ty))),
+                        ImportKind::Global(ty) => (3, format!("(global {})", global_type(ty))),
+                    };
+                    sections.imported[kind] += 1;
+                    sections.imports.push(format!(
+                        "(import {:?} {:?} {})",
+                        import.module, import.name, desc
+                    ));
+                }
+                ModuleField::Func(func) => {
+                    let (locals, body) = match &func.kind {
+                        FuncKind::Inline { locals, expression } => {
+                            (&locals[..], &expression.instrs[..])
+                        }
+                        FuncKind::Import { .. } => (&[][..], &[][..]),
+                    };
+                    let mut header = format!("(func {}", type_use(&func.ty));
+                    if !locals.is_empty() {
+                        let locals = join(locals.iter().map(|(_, ty)| ty.name()), " ");
+                        header.push_str(&format!(" (local {})", locals));
+                    }
+                    header.push(')');
+                    sections.funcs.push(Func {
+                        header,
+                        body: body.iter().map(instruction).collect(),
+                    });
+                }
+                ModuleField::Table(table) => {
+                    if let TableKind::Normal(ty) = &table.kind {
+                        sections.tables.push(format!("(table {})", table_type(ty)));
+                    }
+                }
+                ModuleField::Memory(memory) => {
+                    if let MemoryKind::Normal(ty) = &memory.kind {
+                        sections
+                            .memories
+                            .push(format!("(memory {})", memory_type(ty)));
+                    }
+                }
+                ModuleField::Global(global) => {
+                    if let GlobalKind::Inline(init) = &global.kind {
+                        sections.globals.push(format!(
+                            "(global {} {})",
+                            global_type(&global.ty),
+                            expression(init)
+                        ));
+                    }
+                }
+                ModuleField::Export(export) => {
+                    let (kind, index) = match &export.kind {
+                        ExportKind::Func(i) => ("func", i),
+                        ExportKind::Table(i) => ("table", i),
+                        ExportKind::Memory(i) => ("memory", i),
+                        ExportKind::Global(i) => ("global", i),
+                    };
+                    sections.exports.push(format!(
+                        "(export {:?} ({} {}))",
+                        export.name,
+                        kind,
+                        num(index)
+                    ));
+                }
+                ModuleField::Start(index) => {
+                    sections.start.push(format!("(start {})", num(index)));
+                }
+                ModuleField::Elem(elem) => sections.elems.push(elem_segment(elem)),
+                ModuleField::Data(data) => sections.data.push(data_segment(data)),
+            }
+        }
+        sections
+    }
+}
+
+#[derive(Default)]
+struct Diff {
+    out: Vec<String>,
+}
+
+impl Diff {
+    /// Compares the entries of one section, where the first entry has index
+    /// `base` in its index space.
+    fn entries(&mut self, section: &str, base: u32, expected: &[String], actual: &[String]) {
+        for (i, (e, a)) in expected.iter().zip(actual).enumerate() {
+            if e != a {
+                self.out.push(format!(
+                    "{} {} differs: expected `{}`, got `{}`",
+                    section,
+                    base + i as u32,
+                    e,
+                    a
+                ));
+            }
+        }
+        for e in expected.iter().skip(actual.len()) {
+            self.out
+                .push(format!("{} section is missing entry `{}`", section, e));
+        }
+        for a in actual.iter().skip(expected.len()) {
+            self.out
+                .push(format!("{} section has extra entry `{}`", section, a));
+        }
+    }
+
+    fn funcs(&mut self, expected: &Sections, actual: &Sections) {
+        let headers = |s: &Sections| s.funcs.iter().map(|f| f.header.clone()).collect::<Vec<_>>();
+        let base = expected.imported[0];
+        self.entries("function", base, &headers(expected), &headers(actual));
+
+        // Only the first difference in each body is reported, since anything
+        // after an inserted or removed instruction would differ too.
+        for (i, (e, a)) in expected.funcs.iter().zip(&actual.funcs).enumerate() {
+            let len = e.body.len().max(a.body.len());
+            let describe = |body: &[String], j: usize| match body.get(j) {
+                Some(instr) => format!("`{}`", instr),
+                None => "end of body".to_string(),
+            };
+            if let Some(j) = (0..len).find(|&j| e.body.get(j) != a.body.get(j)) {
+                self.out.push(format!(
+                    "function {} body differs at instruction {}: expected {}, got {}",
+                    base + i as u32,
+                    j,
+                    describe(&e.body, j),
+                    describe(&a.body, j)
+                ));
+            }
+        }
+    }
+}
+
+fn valtypes(keyword: &str, tys: impl Iterator<Item = ValType>) -> String {
+    let tys = join(tys.map(|ty| ty.name()), " ");
+    if tys.is_empty() {
+        String::new()
+    } else {
+        format!(" ({} {})", keyword, tys)
+    }
+}
+
+fn func_type(ty: &FunctionType<'_>) -> String {
+    format!(
+        "(func{}{})",
+        valtypes("param", ty.params.iter().map(|(_, ty)| *ty)),
+        valtypes("result", ty.results.iter().cloned())
+    )
+}
+
+fn type_use(ty: &TypeUse<'_>) -> String {
+    match &ty.index {
+        Some(index) => format!("(type {})", num(index)),
+        None => func_type(&ty.ty),
+    }
+}
+
+fn limits(limits: &Limits) -> String {
+    match limits.max {
+        Some(max) => format!("{} {}", limits.min, max),
+        None => limits.min.to_string(),
+    }
+}
+
+fn table_type(ty: &TableType) -> String {
+    format!("{} {}", limits(&ty.limits), elem_type(ty.elem))
+}
+
+fn memory_type(ty: &MemoryType) -> String {
+    let shared = if ty.shared { " shared" } else { "" };
+    format!("{}{}", limits(&ty.limits), shared)
+}
+
+fn global_type(ty: &GlobalType) -> String {
+    if ty.mutable {
+        format!("(mut {})", ty.ty.name())
+    } else {
+        ty.ty.name().to_string()
+    }
+}
+
+fn expression(expr: &Expression<'_>) -> String {
+    join(
+        expr.instrs.iter().map(|i| format!("({})", instruction(i))),
+        " ",
+    )
+}
+
+fn elem_segment(elem: &Elem<'_>) -> String {
+    let mut dst = "(elem".to_string();
+    if let ElemKind::Active { table, offset } = &elem.kind {
+        dst.push_str(&format!(" (table {}) {}", num(table), expression(offset)));
+    }
+    match &elem.payload {
+        ElemPayload::Indices(indices) => {
+            dst.push_str(" func");
+            for index in indices {
+                dst.push_str(&format!(" {}", num(index)));
+            }
+        }
+        ElemPayload::Exprs { ty, exprs } => {
+            dst.push(' ');
+            dst.push_str(elem_type(*ty));
+            for expr in exprs {
+                match expr {
+                    Some(index) => dst.push_str(&format!(" (ref.func {})", num(index))),
+                    None => dst.push_str(" (ref.null)"),
+                }
+            }
+        }
+    }
+    dst.push(')');
+    dst
+}
+
+fn data_segment(data: &Data<'_>) -> String {
+    let mut dst = "(data".to_string();
+    if let DataKind::Active { memory, offset } = &data.kind {
+        dst.push_str(&format!(" (memory {}) {}", num(memory), expression(offset)));
+    }
+    dst.push_str(" \"");
+    for byte in data.data.iter().flat_map(|d| d.iter()) {
+        match byte {
+            b'"' | b'\\' => dst.push_str(&format!("\\{}", char::from(*byte))),
+            0x20..=0x7e => dst.push(char::from(*byte)),
+            _ => dst.push_str(&format!("\\{:02x}", byte)),
+        }
+    }
+    dst.push_str("\")");
+    dst
+}
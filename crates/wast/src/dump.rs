@@ -0,0 +1,661 @@
+//! Rendering of binary modules in the style of `wasm-objdump -x -d`.
+//!
+//! This is mostly useful to see where two encodings of a module differ, so
+//! every section, item, and instruction is listed along with the byte offset
+//! it starts at.
+
+use crate::ast::*;
+use crate::reader::{self, Decode, Reader, Result};
+use crate::resolve::num;
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+
+pub(crate) const SECTION_NAMES: [&str; 13] = [
+    "Custom",
+    "Type",
+    "Import",
+    "Function",
+    "Table",
+    "Memory",
+    "Global",
+    "Export",
+    "Start",
+    "Elem",
+    "Code",
+    "Data",
+    "DataCount",
+];
+
+/// Renders the binary module `bytes` as a list of its sections, the items in
+/// each section, and a disassembly of its function bodies.
+pub fn dump(bytes: &[u8]) -> Result<String> {
+    let mut dumper = Dumper::default();
+
+    // The name section comes last but function names are used throughout, so
+    // they're collected first. Any errors are reported by the second pass.
+    let _ = reader::sections(bytes, |id, s| {
+        if id == 0 && s.name()? == "name" {
+            dumper.func_names(s)?;
+        }
+        s.skip_to_end();
+        Ok(())
+    });
+    reader::sections(bytes, |id, s| dumper.section(id, s))?;
+
+    let mut out = String::new();
+    out.push_str("Sections:\n\n");
+    out.push_str(&dumper.sections);
+    out.push_str("\nSection Details:\n\n");
+    out.push_str(&dumper.details);
+    if !dumper.code.is_empty() {
+        out.push_str("\nCode Disassembly:\n\n");
+        out.push_str(&dumper.code);
+    }
+    Ok(out)
+}
+
+#[derive(Default)]
+struct Dumper<'a> {
+    names: HashMap<u32, &'a str>,
+    types: Vec<FunctionType<'a>>,
+    /// The type index of every function, including imported ones.
+    func_types: Vec<u32>,
+    imported_funcs: u32,
+    tables: u32,
+    memories: u32,
+    globals: u32,
+    sections: String,
+    details: String,
+    code: String,
+}
+
+impl<'a> Dumper<'a> {
+    fn func_names(&mut self, s: &mut Reader<'a>) -> Result<()> {
+        while !s.eof() {
+            let id = s.u8()?;
+            let len = s.var_u32()? as usize;
+            let mut sub = s.sub(len)?;
+            if id == 1 {
+                for (index, name) in sub.vec(|r| Ok((r.var_u32()?, r.name()?)))? {
+                    self.names.insert(index, name);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn func_name(&self, index: u32) -> String {
+        match self.names.get(&index) {
+            Some(name) => format!(" <{}>", name),
+            None => String::new(),
+        }
+    }
+
+    fn section(&mut self, id: u8, s: &mut Reader<'a>) -> Result<()> {
+        let name = SECTION_NAMES[usize::from(id)];
+        write!(
+            self.sections,
+            "{:>9} start={:#010x} end={:#010x} (size={:#010x})",
+            name,
+            s.pos(),
+            s.end(),
+            s.end() - s.pos()
+        )
+        .unwrap();
+
+        match id {
+            0 => {
+                let custom = s.name()?;
+                writeln!(self.sections, " \"{}\"", custom).unwrap();
+                writeln!(self.details, "Custom:\n - name: \"{}\"", custom).unwrap();
+                s.skip_to_end();
+                return Ok(());
+            }
+            8 => {
+                let start = s.var_u32()?;
+                writeln!(self.sections, " start: {}", start).unwrap();
+                writeln!(
+                    self.details,
+                    "Start:\n - start function: {}{}",
+                    start,
+                    self.func_name(start)
+                )
+                .unwrap();
+                return Ok(());
+            }
+            12 => {
+                let count = s.var_u32()?;
+                writeln!(self.sections, " count: {}", count).unwrap();
+                writeln!(self.details, "DataCount:\n - data count: {}", count).unwrap();
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        let count = s.clone().var_u32()?;
+        writeln!(self.sections, " count: {}", count).unwrap();
+        writeln!(self.details, "{}[{}]:", name, count).unwrap();
+        match id {
+            1 => {
+                self.types = s.vec(reader::func_type)?;
+                for (i, ty) in self.types.iter().enumerate() {
+                    writeln!(self.details, " - type[{}] {}", i, func_type(ty)).unwrap();
+                }
+            }
+            2 => {
+                s.vec(|r| self.import(r))?;
+            }
+            3 => {
+                s.vec(|r| {
+                    let ty = r.var_u32()?;
+                    let index = self.func_types.len() as u32;
+                    self.func_types.push(ty);
+                    let name = self.func_name(index);
+                    writeln!(self.details, " - func[{}] sig={}{}", index, ty, name).unwrap();
+                    Ok(())
+                })?;
+            }
+            4 => {
+                s.vec(|r| {
+                    let ty = reader::table_type(r)?;
+                    self.table(&ty, "");
+                    Ok(())
+                })?;
+            }
+            5 => {
+                s.vec(|r| {
+                    let ty = reader::memory_type(r)?;
+                    self.memory(&ty, "");
+                    Ok(())
+                })?;
+            }
+            6 => {
+                s.vec(|r| {
+                    let ty = reader::global_type(r)?;
+                    let init = Expression::decode(r)?;
+                    self.global(&ty, &format!(" - init {}", expression(&init)));
+                    Ok(())
+                })?;
+            }
+            7 => {
+                s.vec(|r| {
+                    let export = reader::export(r)?;
+                    let (kind, index) = match &export.kind {
+                        ExportKind::Func(i) => ("func", i),
+                        ExportKind::Table(i) => ("table", i),
+                        ExportKind::Memory(i) => ("memory", i),
+                        ExportKind::Global(i) => ("global", i),
+                    };
+                    let index = num(index);
+                    let name = match export.kind {
+                        ExportKind::Func(_) => self.func_name(index),
+                        _ => String::new(),
+                    };
+                    writeln!(
+                        self.details,
+                        " - {}[{}]{} -> \"{}\"",
+                        kind, index, name, export.name
+                    )
+                    .unwrap();
+                    Ok(())
+                })?;
+            }
+            9 => {
+                let mut segment = 0;
+                s.vec(|r| {
+                    self.elem(segment, r)?;
+                    segment += 1;
+                    Ok(())
+                })?;
+            }
+            10 => {
+                let mut func = self.imported_funcs;
+                s.vec(|r| {
+                    self.func_body(func, r)?;
+                    func += 1;
+                    Ok(())
+                })?;
+            }
+            11 => {
+                let mut segment = 0;
+                s.vec(|r| {
+                    self.data(segment, r)?;
+                    segment += 1;
+                    Ok(())
+                })?;
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    fn import(&mut self, r: &mut Reader<'a>) -> Result<()> {
+        let module = r.name()?;
+        let field = r.name()?;
+        let from = format!(" <- {}.{}", module, field);
+        match r.u8()? {
+            0x00 => {
+                let ty = r.var_u32()?;
+                let index = self.func_types.len() as u32;
+                self.func_types.push(ty);
+                self.imported_funcs += 1;
+                let name = self.func_name(index);
+                writeln!(
+                    self.details,
+                    " - func[{}] sig={}{}{}",
+                    index, ty, name, from
+                )
+                .unwrap();
+            }
+            0x01 => self.table(&reader::table_type(r)?, &from),
+            0x02 => self.memory(&reader::memory_type(r)?, &from),
+            0x03 => self.global(&reader::global_type(r)?, &from),
+            _ => return Err(r.error_at(r.pos() - 1, "malformed import kind")),
+        }
+        Ok(())
+    }
+
+    fn table(&mut self, ty: &TableType, rest: &str) {
+        writeln!(
+            self.details,
+            " - table[{}] type={} {}{}",
+            self.tables,
+            elem_type(ty.elem),
+            limits(&ty.limits),
+            rest
+        )
+        .unwrap();
+        self.tables += 1;
+    }
+
+    fn memory(&mut self, ty: &MemoryType, rest: &str) {
+        let shared = if ty.shared { " shared" } else { "" };
+        writeln!(
+            self.details,
+            " - memory[{}] pages: {}{}{}",
+            self.memories,
+            limits(&ty.limits),
+            shared,
+            rest
+        )
+        .unwrap();
+        self.memories += 1;
+    }
+
+    fn global(&mut self, ty: &GlobalType, rest: &str) {
+        writeln!(
+            self.details,
+            " - global[{}] {} mutable={}{}",
+            self.globals,
+            ty.ty.name(),
+            ty.mutable as u8,
+            rest
+        )
+        .unwrap();
+        self.globals += 1;
+    }
+
+    fn elem(&mut self, segment: u32, r: &mut Reader<'a>) -> Result<()> {
+        let (flags, elem) = reader::elem_segment(r)?;
+        let (ty, items) = match &elem.payload {
+            ElemPayload::Indices(indices) => {
+                let items = indices
+                    .iter()
+                    .map(|i| {
+                        let i = num(i);
+                        format!("func[{}]{}", i, self.func_name(i))
+                    })
+                    .collect::<Vec<_>>();
+                (TableElemType::Funcref, items)
+            }
+            ElemPayload::Exprs { ty, exprs } => {
+                let items = exprs
+                    .iter()
+                    .map(|e| match e {
+                        Some(i) => {
+                            let i = num(i);
+                            format!("ref.func {}{}", i, self.func_name(i))
+                        }
+                        None => "ref.null".to_string(),
+                    })
+                    .collect();
+                (*ty, items)
+            }
+        };
+        write!(
+            self.details,
+            " - segment[{}] flags={} type={} count={}",
+            segment,
+            flags,
+            elem_type(ty),
+            items.len()
+        )
+        .unwrap();
+        match &elem.kind {
+            ElemKind::Active { table, offset } => writeln!(
+                self.details,
+                " table={} - init {}",
+                num(table),
+                expression(offset)
+            ),
+            ElemKind::Passive if flags & 0b011 == 0b011 => writeln!(self.details, " declared"),
+            ElemKind::Passive => writeln!(self.details, " passive"),
+        }
+        .unwrap();
+        for (i, item) in items.iter().enumerate() {
+            writeln!(self.details, "  - elem[{}] = {}", i, item).unwrap();
+        }
+        Ok(())
+    }
+
+    fn data(&mut self, segment: u32, r: &mut Reader<'a>) -> Result<()> {
+        let data = reader::data_segment(r)?;
+        let bytes = &data.data[0];
+        write!(self.details, " - segment[{}] size={}", segment, bytes.len()).unwrap();
+        match &data.kind {
+            DataKind::Active { memory, offset } => writeln!(
+                self.details,
+                " memory={} - init {}",
+                num(memory),
+                expression(offset)
+            ),
+            DataKind::Passive => writeln!(self.details, " passive"),
+        }
+        .unwrap();
+
+        // Rows are labeled with the offset of their first byte in the module.
+        let start = r.pos() - bytes.len();
+        for (row, chunk) in bytes.chunks(16).enumerate() {
+            let mut hex = String::new();
+            for (i, b) in chunk.iter().enumerate() {
+                write!(hex, "{:02x}", b).unwrap();
+                if i % 2 == 1 {
+                    hex.push(' ');
+                }
+            }
+            let ascii = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        char::from(b)
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+            writeln!(
+                self.details,
+                "  - {:07x}: {:<40} {}",
+                start + row * 16,
+                hex,
+                ascii
+            )
+            .unwrap();
+        }
+        Ok(())
+    }
+
+    fn func_body(&mut self, func: u32, r: &mut Reader<'a>) -> Result<()> {
+        let len = r.var_u32()? as usize;
+        let start = r.pos();
+        let mut body = r.sub(len)?;
+        let name = self.func_name(func);
+        writeln!(self.details, " - func[{}] size={}{}", func, len, name).unwrap();
+        writeln!(self.code, "{:06x} func[{}]{}:", start, func, name).unwrap();
+
+        // Locals are numbered after the function's parameters.
+        let params = self
+            .func_types
+            .get(func as usize)
+            .and_then(|ty| self.types.get(*ty as usize))
+            .map(|ty| ty.params.len())
+            .unwrap_or(0);
+        let mut local = params as u64;
+        for _ in 0..body.var_u32()? {
+            let pos = body.pos();
+            let count = body.var_u32()?;
+            let ty = reader::valtype(&mut body)?;
+            if count == 0 {
+                continue;
+            }
+            let range = if count == 1 {
+                local.to_string()
+            } else {
+                format!("{}..{}", local, local + u64::from(count) - 1)
+            };
+            let text = format!("local[{}] type={}", range, ty.name());
+            self.line(pos, body.bytes_since(pos), &text);
+            local += u64::from(count);
+        }
+
+        let mut depth = 0usize;
+        while !body.eof() {
+            let pos = body.pos();
+            let instr = Instruction::decode(&mut body)?;
+            if let Instruction::Else(_) | Instruction::End(_) = instr {
+                depth = depth.saturating_sub(1);
+            }
+            let text = format!("{}{}", "  ".repeat(depth), self.instruction(&instr));
+            self.line(pos, body.bytes_since(pos), &text);
+            if let Instruction::Block(_)
+            | Instruction::Loop(_)
+            | Instruction::If(_)
+            | Instruction::Else(_) = instr
+            {
+                depth += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes one line of disassembly, showing up to eight of the bytes which
+    /// `text` was decoded from.
+    fn line(&mut self, pos: usize, bytes: &[u8], text: &str) {
+        let mut hex = String::new();
+        for (i, b) in bytes.iter().enumerate() {
+            if i == 8 {
+                hex.push_str("..");
+                break;
+            }
+            write!(hex, "{:02x} ", b).unwrap();
+        }
+        writeln!(self.code, " {:06x}: {:<26}| {}", pos, hex, text).unwrap();
+    }
+
+    fn instruction(&self, instr: &Instruction<'_>) -> String {
+        let mut dst = instruction(instr);
+        if let Instruction::Call(i) | Instruction::ReturnCall(i) | Instruction::RefFunc(i) = instr {
+            dst.push_str(&self.func_name(num(i)));
+        }
+        dst
+    }
+}
+
+/// The immediates of an instruction, as written by
+/// `Instruction::write_operands`.
+pub(crate) trait Operand {
+    fn write(&self, dst: &mut String);
+}
+
+impl Operand for Index<'_> {
+    fn write(&self, dst: &mut String) {
+        match self {
+            Index::Num(n) => write!(dst, "{}", n).unwrap(),
+            Index::Id(id) => write!(dst, "${}", id.name()).unwrap(),
+        }
+    }
+}
+
+impl Operand for Option<Id<'_>> {
+    fn write(&self, _dst: &mut String) {}
+}
+
+impl Operand for BlockType<'_> {
+    fn write(&self, dst: &mut String) {
+        match &self.ty.index {
+            Some(index) => {
+                dst.push_str("type[");
+                index.write(dst);
+                dst.push(']');
+            }
+            None => dst.push_str(&join(self.ty.ty.results.iter().map(|t| t.name()), " ")),
+        }
+    }
+}
+
+impl Operand for BrTableIndices<'_> {
+    fn write(&self, dst: &mut String) {
+        for label in self.labels.iter() {
+            label.write(dst);
+            dst.push(' ');
+        }
+        self.default.write(dst);
+    }
+}
+
+impl Operand for CallIndirect<'_> {
+    fn write(&self, dst: &mut String) {
+        if let Some(index) = &self.ty.index {
+            index.write(dst);
+            dst.push(' ');
+        }
+        self.table.write(dst);
+    }
+}
+
+impl Operand for SelectTypes {
+    fn write(&self, dst: &mut String) {
+        dst.push_str(&join(self.tys.iter().map(|t| t.name()), " "));
+    }
+}
+
+impl Operand for MemArg {
+    fn write(&self, dst: &mut String) {
+        if self.offset != 0 {
+            write!(dst, "offset={} ", self.offset).unwrap();
+        }
+        write!(dst, "align={}", self.align).unwrap();
+    }
+}
+
+impl Operand for MemoryInit<'_> {
+    fn write(&self, dst: &mut String) {
+        self.data.write(dst);
+    }
+}
+
+impl Operand for TableInit<'_> {
+    fn write(&self, dst: &mut String) {
+        self.elem.write(dst);
+    }
+}
+
+impl Operand for u32 {
+    fn write(&self, dst: &mut String) {
+        write!(dst, "{}", self).unwrap();
+    }
+}
+
+impl Operand for i32 {
+    fn write(&self, dst: &mut String) {
+        write!(dst, "{}", self).unwrap();
+    }
+}
+
+impl Operand for i64 {
+    fn write(&self, dst: &mut String) {
+        write!(dst, "{}", self).unwrap();
+    }
+}
+
+impl Operand for Float32 {
+    fn write(&self, dst: &mut String) {
+        let f = f32::from_bits(self.bits);
+        if f.is_nan() {
+            let sign = if self.bits >> 31 != 0 { "-" } else { "" };
+            write!(dst, "{}nan:{:#x}", sign, self.bits & 0x7f_ffff).unwrap();
+        } else {
+            write!(dst, "{}", f).unwrap();
+        }
+    }
+}
+
+impl Operand for Float64 {
+    fn write(&self, dst: &mut String) {
+        let f = f64::from_bits(self.bits);
+        if f.is_nan() {
+            let sign = if self.bits >> 63 != 0 { "-" } else { "" };
+            write!(dst, "{}nan:{:#x}", sign, self.bits & 0xf_ffff_ffff_ffff).unwrap();
+        } else {
+            write!(dst, "{}", f).unwrap();
+        }
+    }
+}
+
+impl Operand for V128Const {
+    fn write(&self, dst: &mut String) {
+        let bytes = self.to_le_bytes();
+        let lanes = bytes
+            .chunks(4)
+            .map(|c| format!("{:#010x}", u32::from_le_bytes([c[0], c[1], c[2], c[3]])));
+        write!(dst, "i32x4 {}", join(lanes, " ")).unwrap();
+    }
+}
+
+impl Operand for V8x16Shuffle {
+    fn write(&self, dst: &mut String) {
+        dst.push_str(&join(self.lanes.iter(), " "));
+    }
+}
+
+pub(crate) fn join(items: impl Iterator<Item = impl Display>, sep: &str) -> String {
+    let mut dst = String::new();
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            dst.push_str(sep);
+        }
+        write!(dst, "{}", item).unwrap();
+    }
+    dst
+}
+
+fn func_type(ty: &FunctionType<'_>) -> String {
+    let params = join(ty.params.iter().map(|(_, t)| t.name()), ", ");
+    let results = match &ty.results[..] {
+        [] => "nil".to_string(),
+        [ty] => ty.name().to_string(),
+        tys => format!("({})", join(tys.iter().map(|t| t.name()), ", ")),
+    };
+    format!("({}) -> {}", params, results)
+}
+
+pub(crate) fn elem_type(ty: TableElemType) -> &'static str {
+    match ty {
+        TableElemType::Funcref => "funcref",
+        TableElemType::Anyref => "anyref",
+        TableElemType::Nullref => "nullref",
+    }
+}
+
+fn limits(limits: &Limits) -> String {
+    match limits.max {
+        Some(max) => format!("initial={} max={}", limits.min, max),
+        None => format!("initial={}", limits.min),
+    }
+}
+
+fn expression(expr: &Expression<'_>) -> String {
+    join(expr.instrs.iter().map(instruction), ", ")
+}
+
+/// Renders `instr` as its name followed by its immediates, if any.
+pub(crate) fn instruction(instr: &Instruction<'_>) -> String {
+    let mut dst = instr.name().to_string();
+    let mut operands = String::new();
+    instr.write_operands(&mut operands);
+    if !operands.is_empty() {
+        dst.push(' ');
+        dst.push_str(&operands);
+    }
+    dst
+}
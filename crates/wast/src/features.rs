@@ -0,0 +1,387 @@
+use crate::ast::*;
+use crate::visit::Visit;
+use crate::Error;
+
+/// The set of WebAssembly proposals a module is allowed to use.
+///
+/// This is configured on [`EncodeOptions`](crate::EncodeOptions) and checked
+/// before a module is encoded. Any instruction, type, or section which belongs
+/// to a disabled proposal is rejected with an error pointing at the item which
+/// uses it.
+///
+/// By default every proposal supported by this crate is enabled, and
+/// [`WasmFeatures::mvp`] can be used to start from the original WebAssembly
+/// specification instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WasmFeatures {
+    pub(crate) simd: bool,
+    pub(crate) threads: bool,
+    pub(crate) reference_types: bool,
+    pub(crate) bulk_memory: bool,
+    pub(crate) tail_call: bool,
+    pub(crate) multi_value: bool,
+    pub(crate) sign_extension: bool,
+    pub(crate) saturating_float_to_int: bool,
+}
+
+impl Default for WasmFeatures {
+    fn default() -> WasmFeatures {
+        WasmFeatures {
+            simd: true,
+            threads: true,
+            reference_types: true,
+            bulk_memory: true,
+            tail_call: true,
+            multi_value: true,
+            sign_extension: true,
+            saturating_float_to_int: true,
+        }
+    }
+}
+
+impl WasmFeatures {
+    /// Creates a new set of features with every proposal enabled.
+    pub fn new() -> WasmFeatures {
+        WasmFeatures::default()
+    }
+
+    /// Creates a new set of features with every proposal disabled, allowing
+    /// only what's in the original WebAssembly specification.
+    pub fn mvp() -> WasmFeatures {
+        WasmFeatures {
+            simd: false,
+            threads: false,
+            reference_types: false,
+            bulk_memory: false,
+            tail_call: false,
+            multi_value: false,
+            sign_extension: false,
+            saturating_float_to_int: false,
+        }
+    }
+
+    /// Configures whether the SIMD proposal, including the `v128` type, is
+    /// enabled.
+    pub fn simd(&mut self, enabled: bool) -> &mut WasmFeatures {
+        self.simd = enabled;
+        self
+    }
+
+    /// Configures whether the threads proposal, including atomic instructions
+    /// and shared memories, is enabled.
+    pub fn threads(&mut self, enabled: bool) -> &mut WasmFeatures {
+        self.threads = enabled;
+        self
+    }
+
+    /// Configures whether the reference types proposal, including `anyref`
+    /// and multiple tables, is enabled.
+    pub fn reference_types(&mut self, enabled: bool) -> &mut WasmFeatures {
+        self.reference_types = enabled;
+        self
+    }
+
+    /// Configures whether the bulk memory proposal, including passive
+    /// segments, is enabled.
+    pub fn bulk_memory(&mut self, enabled: bool) -> &mut WasmFeatures {
+        self.bulk_memory = enabled;
+        self
+    }
+
+    /// Configures whether the tail call proposal is enabled.
+    pub fn tail_call(&mut self, enabled: bool) -> &mut WasmFeatures {
+        self.tail_call = enabled;
+        self
+    }
+
+    /// Configures whether the multi-value proposal, which allows functions
+    /// and blocks to return more than one value, is enabled.
+    pub fn multi_value(&mut self, enabled: bool) -> &mut WasmFeatures {
+        self.multi_value = enabled;
+        self
+    }
+
+    /// Configures whether the sign-extension operators proposal is enabled.
+    pub fn sign_extension(&mut self, enabled: bool) -> &mut WasmFeatures {
+        self.sign_extension = enabled;
+        self
+    }
+
+    /// Configures whether the non-trapping float-to-int conversions proposal
+    /// is enabled.
+    pub fn saturating_float_to_int(&mut self, enabled: bool) -> &mut WasmFeatures {
+        self.saturating_float_to_int = enabled;
+        self
+    }
+
+    fn proposal(&self, proposal: Proposal) -> Option<&'static str> {
+        let (enabled, name) = match proposal {
+            Proposal::Mvp => return None,
+            Proposal::Simd => (self.simd, "simd"),
+            Proposal::Atomics => (self.threads, "threads"),
+            Proposal::BulkMemory => (self.bulk_memory, "bulk memory"),
+            Proposal::ReferenceTypes => (self.reference_types, "reference types"),
+            Proposal::TailCall => (self.tail_call, "tail call"),
+            Proposal::SignExtension => (self.sign_extension, "sign extension"),
+            Proposal::SaturatingFloatToInt => {
+                (self.saturating_float_to_int, "saturating float-to-int")
+            }
+        };
+        if enabled {
+            None
+        } else {
+            Some(name)
+        }
+    }
+}
+
+/// Returns an error if `module` uses any proposal which isn't enabled in
+/// `features`.
+pub fn check(module: &Module<'_>, features: &WasmFeatures) -> Result<(), Error> {
+    if let ModuleKind::Binary(bytes) = &module.kind {
+        // There's nothing to reject when every proposal is enabled, so only
+        // decode the module when there is.
+        if *features == WasmFeatures::default() {
+            return Ok(());
+        }
+        return crate::validate::decode_binary(module.span, bytes, |m| check(m, features));
+    }
+    let types = match &module.kind {
+        ModuleKind::Text(fields) => fields
+            .iter()
+            .filter_map(|f| match f {
+                ModuleField::Type(ty) => Some((ty.name.as_ref(), &ty.func)),
+                _ => None,
+            })
+            .collect(),
+        ModuleKind::Binary(_) => Vec::new(),
+    };
+    let mut checker = Checker {
+        features,
+        types,
+        span: module.span,
+        tables: 0,
+        err: None,
+    };
+    checker.visit_module(module);
+    match checker.err {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+struct Checker<'f, 'a> {
+    features: &'f WasmFeatures,
+    /// The `type` definitions of the module with their names, since the
+    /// module isn't resolved yet.
+    types: Vec<(Option<&'f Id<'a>>, &'f FunctionType<'a>)>,
+    span: Span,
+    tables: usize,
+    err: Option<Error>,
+}
+
+impl<'f, 'a> Checker<'f, 'a> {
+    fn require(&mut self, proposal: Proposal, what: impl FnOnce() -> String) {
+        if self.err.is_some() {
+            return;
+        }
+        if let Some(name) = self.features.proposal(proposal) {
+            self.err = Some(Error::new(
+                self.span,
+                format!(
+                    "{} requires the {} proposal, which is not enabled",
+                    what(),
+                    name
+                ),
+            ));
+        }
+    }
+
+    fn check_valtype(&mut self, ty: ValType) {
+        let proposal = match ty {
+            ValType::V128 => Proposal::Simd,
+            ValType::Anyref | ValType::Funcref | ValType::Nullref => Proposal::ReferenceTypes,
+            _ => return,
+        };
+        self.require(proposal, || format!("the `{}` type", ty.name()));
+    }
+
+    fn check_func_type(&mut self, ty: &FunctionType<'_>) {
+        for (_, param) in ty.params.iter() {
+            self.check_valtype(*param);
+        }
+        for result in ty.results.iter() {
+            self.check_valtype(*result);
+        }
+        if ty.results.len() > 1 && !self.features.multi_value {
+            self.multi_value("a function with multiple results");
+        }
+    }
+
+    fn check_block_type(&mut self, ty: &TypeUse<'_>) {
+        // A block type which only refers to a `type` definition doesn't list
+        // its parameters and results until the module is resolved.
+        let mut func = &ty.ty;
+        if let (Some(index), true) = (
+            &ty.index,
+            ty.ty.params.is_empty() && ty.ty.results.is_empty(),
+        ) {
+            match self.lookup_type(index) {
+                Some(found) => func = found,
+                None => return,
+            }
+        }
+        self.check_func_type(func);
+        if !func.params.is_empty() && !self.features.multi_value {
+            self.multi_value("a block with parameters");
+        }
+    }
+
+    fn lookup_type(&self, index: &Index<'_>) -> Option<&'f FunctionType<'a>> {
+        match index {
+            Index::Num(n) => self.types.get(*n as usize).map(|t| t.1),
+            Index::Id(id) => self.types.iter().find(|t| t.0 == Some(id)).map(|t| t.1),
+        }
+    }
+
+    fn check_table_type(&mut self, ty: &TableType) {
+        self.tables += 1;
+        if self.tables > 1 {
+            self.require(Proposal::ReferenceTypes, || "multiple tables".to_string());
+        }
+        self.check_table_elem_type(ty.elem);
+    }
+
+    fn check_table_elem_type(&mut self, ty: TableElemType) {
+        let name = match ty {
+            TableElemType::Funcref => return,
+            TableElemType::Anyref => "anyref",
+            TableElemType::Nullref => "nullref",
+        };
+        self.require(Proposal::ReferenceTypes, || {
+            format!("the `{}` element type", name)
+        });
+    }
+
+    fn check_memory_type(&mut self, ty: &MemoryType) {
+        if ty.shared {
+            self.require(Proposal::Atomics, || "a shared memory".to_string());
+        }
+    }
+
+    fn multi_value(&mut self, what: &str) {
+        if self.err.is_none() {
+            self.err = Some(Error::new(
+                self.span,
+                format!(
+                    "{} requires the multi-value proposal, which is not enabled",
+                    what
+                ),
+            ));
+        }
+    }
+}
+
+impl<'a> Visit<'a> for Checker<'_, 'a> {
+    fn visit_type(&mut self, ty: &Type<'a>) {
+        self.check_func_type(&ty.func);
+    }
+
+    fn visit_import(&mut self, import: &Import<'a>) {
+        self.span = import.span;
+        match &import.kind {
+            ImportKind::Func(ty) => self.check_func_type(&ty.ty),
+            ImportKind::Table(ty) => self.check_table_type(ty),
+            ImportKind::Memory(ty) => self.check_memory_type(ty),
+            ImportKind::Global(ty) => self.check_valtype(ty.ty),
+        }
+    }
+
+    fn visit_func(&mut self, func: &Func<'a>) {
+        self.span = func.span;
+        self.check_func_type(&func.ty.ty);
+        if let FuncKind::Inline { locals, .. } = &func.kind {
+            for (_, local) in locals.iter() {
+                self.check_valtype(*local);
+            }
+        }
+        self.walk_func(func);
+    }
+
+    fn visit_table(&mut self, table: &Table<'a>) {
+        self.span = table.span;
+        match &table.kind {
+            TableKind::Import { ty, .. } | TableKind::Normal(ty) => self.check_table_type(ty),
+            TableKind::Inline { elem, .. } => {
+                self.check_table_type(&TableType {
+                    limits: Limits { min: 0, max: None },
+                    elem: *elem,
+                });
+            }
+        }
+        self.walk_table(table);
+    }
+
+    fn visit_memory(&mut self, memory: &Memory<'a>) {
+        self.span = memory.span;
+        match &memory.kind {
+            MemoryKind::Import { ty, .. } | MemoryKind::Normal(ty) => self.check_memory_type(ty),
+            MemoryKind::Inline(_) => {}
+        }
+    }
+
+    fn visit_global(&mut self, global: &Global<'a>) {
+        self.span = global.span;
+        self.check_valtype(global.ty.ty);
+        self.walk_global(global);
+    }
+
+    fn visit_elem(&mut self, elem: &Elem<'a>) {
+        self.span = elem.span;
+        if let ElemKind::Passive = elem.kind {
+            self.require(Proposal::BulkMemory, || {
+                "a passive element segment".to_string()
+            });
+        }
+        self.walk_elem(elem);
+    }
+
+    fn visit_elem_payload(&mut self, payload: &ElemPayload<'a>) {
+        if let ElemPayload::Exprs { ty, .. } = payload {
+            self.require(Proposal::BulkMemory, || {
+                "an element segment of expressions".to_string()
+            });
+            self.check_table_elem_type(*ty);
+        }
+    }
+
+    fn visit_data(&mut self, data: &Data<'a>) {
+        self.span = data.span;
+        if let DataKind::Passive = data.kind {
+            self.require(Proposal::BulkMemory, || {
+                "a passive data segment".to_string()
+            });
+        }
+        self.walk_data(data);
+    }
+
+    fn visit_instruction(&mut self, instr: &Instruction<'a>) {
+        self.require(instr.proposal(), || {
+            format!("the `{}` instruction", instr.name())
+        });
+        match instr {
+            Instruction::Block(bt) | Instruction::If(bt) | Instruction::Loop(bt) => {
+                self.check_block_type(&bt.ty);
+            }
+            Instruction::CallIndirect(c) | Instruction::ReturnCallIndirect(c) => {
+                self.check_func_type(&c.ty.ty);
+            }
+            Instruction::Select(s) => {
+                for ty in s.tys.iter() {
+                    self.check_valtype(*ty);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use crate::ast::*;
+use crate::visit::{IndexSpace, Visit, VisitMut};
+use crate::Error;
+
+/// Removes the functions, globals, and types of `module` which can't be
+/// reached from its exports, `start` function, element segments, or the
+/// other items it always keeps, and renumbers all references to the items
+/// which remain.
+pub fn gc(module: &mut Module<'_>) -> Result<(), Error> {
+    let fields = match &mut module.kind {
+        ModuleKind::Text(fields) => fields,
+        ModuleKind::Binary(_) => return Ok(()),
+    };
+
+    let mut live = Live {
+        funcs: Vec::new(),
+        globals: Vec::new(),
+        types: Vec::new(),
+        pending_funcs: Vec::new(),
+        pending_globals: Vec::new(),
+        span: module.span,
+        err: None,
+    };
+    // Imported functions and globals are always kept.
+    for field in fields.iter() {
+        match field {
+            ModuleField::Type(_) => live.types.push(false),
+            ModuleField::Func(_) => live.funcs.push(false),
+            ModuleField::Global(_) => live.globals.push(false),
+            ModuleField::Import(i) => match i.kind {
+                ImportKind::Func(_) => live.funcs.push(true),
+                ImportKind::Global(_) => live.globals.push(true),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    let funcs = fields
+        .iter()
+        .filter_map(|f| match f {
+            ModuleField::Func(f) => Some(f),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let globals = fields
+        .iter()
+        .filter_map(|f| match f {
+            ModuleField::Global(g) => Some(g),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let imported_funcs = live.funcs.len() - funcs.len();
+    let imported_globals = live.globals.len() - globals.len();
+
+    // Everything other than functions, globals, and types is kept, so all of
+    // it is a root. Items found to be live are then visited in turn, until
+    // no more are found.
+    for field in fields.iter() {
+        match field {
+            ModuleField::Type(_) | ModuleField::Func(_) | ModuleField::Global(_) => {}
+            _ => live.visit_module_field(field),
+        }
+    }
+    loop {
+        if let Some(err) = live.err.take() {
+            return Err(err);
+        }
+        if let Some(i) = live.pending_funcs.pop() {
+            if let Some(func) = i.checked_sub(imported_funcs).and_then(|i| funcs.get(i)) {
+                live.visit_func(func);
+            }
+        } else if let Some(i) = live.pending_globals.pop() {
+            if let Some(global) = i.checked_sub(imported_globals).and_then(|i| globals.get(i)) {
+                live.visit_global(global);
+            }
+        } else {
+            break;
+        }
+    }
+
+    let mut renumber = Renumber {
+        funcs: new_indices(&live.funcs),
+        globals: new_indices(&live.globals),
+        types: new_indices(&live.types),
+    };
+    let (mut func, mut global, mut ty) = (imported_funcs, imported_globals, 0);
+    fields.retain(|field| {
+        let (index, live) = match field {
+            ModuleField::Func(_) => (&mut func, &live.funcs),
+            ModuleField::Global(_) => (&mut global, &live.globals),
+            ModuleField::Type(_) => (&mut ty, &live.types),
+            _ => return true,
+        };
+        *index += 1;
+        live[*index - 1]
+    });
+    for field in fields.iter_mut() {
+        renumber.visit_module_field(field);
+    }
+    Ok(())
+}
+
+/// Which functions, globals, and types are live, indexed by their index.
+struct Live {
+    funcs: Vec<bool>,
+    globals: Vec<bool>,
+    types: Vec<bool>,
+    /// Live functions and globals which haven't been visited yet.
+    pending_funcs: Vec<usize>,
+    pending_globals: Vec<usize>,
+    /// The item being visited, for errors about unknown indices.
+    span: Span,
+    err: Option<Error>,
+}
+
+impl<'a> Visit<'a> for Live {
+    fn visit_span(&mut self, span: &Span) {
+        self.span = *span;
+    }
+
+    fn visit_index(&mut self, index: &Index<'a>, space: IndexSpace) {
+        let n = match index {
+            Index::Num(n) => *n as usize,
+            Index::Id(_) => return,
+        };
+        let (live, pending, what) = match space {
+            IndexSpace::Func => (&mut self.funcs, Some(&mut self.pending_funcs), "function"),
+            IndexSpace::Global => (&mut self.globals, Some(&mut self.pending_globals), "global"),
+            IndexSpace::Type => (&mut self.types, None, "type"),
+            _ => return,
+        };
+        match live.get_mut(n) {
+            Some(true) => {}
+            Some(slot) => {
+                *slot = true;
+                if let Some(pending) = pending {
+                    pending.push(n);
+                }
+            }
+            None => {
+                if self.err.is_none() {
+                    self.err = Some(Error::new(self.span, format!("unknown {} {}", what, n)));
+                }
+            }
+        }
+    }
+}
+
+/// Returns the new index of each live item, or `None` for removed ones.
+pub(crate) fn new_indices(live: &[bool]) -> Vec<Option<u32>> {
+    let mut next = 0;
+    live.iter()
+        .map(|live| {
+            if *live {
+                next += 1;
+                Some(next - 1)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Maps the old index of each function, global, and type to its new index,
+/// where every reference must be to an item which has one.
+pub(crate) struct Renumber {
+    pub(crate) funcs: Vec<Option<u32>>,
+    pub(crate) globals: Vec<Option<u32>>,
+    pub(crate) types: Vec<Option<u32>>,
+}
+
+impl<'a> VisitMut<'a> for Renumber {
+    fn visit_index(&mut self, index: &mut Index<'a>, space: IndexSpace) {
+        let n = match index {
+            Index::Num(n) => n,
+            Index::Id(_) => return,
+        };
+        let map = match space {
+            IndexSpace::Func => &self.funcs,
+            IndexSpace::Global => &self.globals,
+            IndexSpace::Type => &self.types,
+            _ => return,
+        };
+        // Every remaining reference is to a live item.
+        *n = map[*n as usize].unwrap();
+    }
+}
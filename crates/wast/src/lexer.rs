@@ -101,7 +101,7 @@ pub enum Token<'a> {
 ///
 /// Note that the original text here includes the symbols for the comment
 /// itself.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Comment<'a> {
     /// A line comment, preceded with `;;`
     Line(&'a str),
@@ -20,6 +20,9 @@
 //!   around working with a [`Parser`](`parser::Parser`) to parse streams of
 //!   tokens.
 //!
+//! * [`cst`] - a lossless syntax tree of s-expressions which preserves all
+//!   whitespace and comments, useful for tools which rewrite source text.
+//!
 //! * [`Module`] - this contains an Abstract Syntax Tree (AST) of the
 //!   WebAssembly Text format (WAT) as well as the unofficial WAST format. This
 //!   also has a [`Module::encode`] method to emit a module in its binary form.
@@ -42,32 +45,67 @@
 //! don't need this (for example you're parsing your own s-expression format)
 //! then this feature can be disabled.
 //!
+//! The optional `serde` feature derives `Serialize` and `Deserialize` for the
+//! AST types of the `wasm-module` feature, such as [`Module`] and [`Wast`].
+//!
 //! [`Parse`]: parser::Parse
 //! [`LexError`]: lexer::LexError
 
 #![deny(missing_docs)]
 
+use std::collections::BTreeMap;
 use std::fmt;
 use std::path::{Path, PathBuf};
 
 #[cfg(feature = "wasm-module")]
 mod binary;
 #[cfg(feature = "wasm-module")]
+mod callgraph;
+#[cfg(feature = "wasm-module")]
+mod cfg;
+#[cfg(feature = "wasm-module")]
+mod diff;
+#[cfg(feature = "wasm-module")]
+mod dump;
+#[cfg(feature = "wasm-module")]
+mod features;
+#[cfg(feature = "wasm-module")]
+mod gc;
+#[cfg(feature = "wasm-module")]
+mod link;
+#[cfg(feature = "wasm-module")]
+mod minimize;
+#[cfg(feature = "wasm-module")]
+mod optimize;
+#[cfg(feature = "wasm-module")]
+mod reader;
+#[cfg(feature = "wasm-module")]
 mod resolve;
+#[cfg(feature = "wasm-module")]
+mod stats;
+#[cfg(feature = "wasm-module")]
+mod suggest;
+#[cfg(feature = "wasm-module")]
+mod validate;
 
+#[macro_use]
 mod ast;
 pub use self::ast::*;
 
+pub mod cst;
 pub mod lexer;
 pub mod parser;
+#[cfg(feature = "wasm-module")]
+pub mod visit;
 
 /// A convenience error type to tie together all the detailed errors produced by
 /// this crate.
 ///
 /// This type can be created from a [`lexer::LexError`] or [`parser::Error`].
 /// This also contains storage for file/text information so a nice error can be
-/// rendered along the same lines of rustc's own error messages (minus the
-/// color).
+/// rendered along the same lines of rustc's own error messages. The `Display`
+/// implementation renders a compact, uncolored, form of the error and
+/// [`Error::render`] can be used to configure a richer rendering.
 ///
 /// This type is typically suitable for use in public APIs for consumers of this
 /// crate.
@@ -82,13 +120,50 @@ struct ErrorInner {
     file: Option<PathBuf>,
     span: Span,
     kind: ErrorKind,
+    labels: Vec<Label>,
+    notes: Vec<Note>,
 }
 
 #[derive(Debug)]
 struct Text {
     line: usize,
     col: usize,
-    snippet: String,
+    /// The lines of the source around `line`, along with the lines that
+    /// labels point at, indexed by their line number.
+    lines: BTreeMap<usize, String>,
+}
+
+/// The number of lines kept on each side of the line an error points at.
+const MAX_CONTEXT: usize = 5;
+
+#[derive(Debug)]
+struct Label {
+    span: Span,
+    message: String,
+    /// The line and column of `span`, if the text was set after this label
+    /// was added.
+    linecol: Option<(usize, usize)>,
+}
+
+#[derive(Debug)]
+struct Note {
+    kind: NoteKind,
+    message: String,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum NoteKind {
+    Help,
+    Note,
+}
+
+impl NoteKind {
+    fn desc(&self) -> &'static str {
+        match self {
+            NoteKind::Help => "help",
+            NoteKind::Note => "note",
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -105,6 +180,8 @@ impl Error {
                 file: None,
                 span,
                 kind: ErrorKind::Lex(kind),
+                labels: Vec::new(),
+                notes: Vec::new(),
             }),
         };
         ret.set_text(content);
@@ -118,6 +195,8 @@ impl Error {
                 file: None,
                 span,
                 kind: ErrorKind::Custom(message),
+                labels: Vec::new(),
+                notes: Vec::new(),
             }),
         };
         ret.set_text(content);
@@ -136,6 +215,8 @@ impl Error {
                 file: None,
                 span,
                 kind: ErrorKind::Custom(message),
+                labels: Vec::new(),
+                notes: Vec::new(),
             }),
         }
     }
@@ -150,7 +231,7 @@ impl Error {
         if self.inner.text.is_some() {
             return;
         }
-        self.inner.text = Some(Text::new(contents, self.inner.span));
+        self.inner.text = Some(Text::new(contents, self.inner.span, &mut self.inner.labels));
     }
 
     /// To provide a more useful error this function can be used to set
@@ -165,6 +246,73 @@ impl Error {
         self.inner.file = Some(path.to_path_buf());
     }
 
+    /// Attaches a secondary `span` to this error, rendered with `message`
+    /// next to it.
+    ///
+    /// This is useful for errors which involve more than one location in the
+    /// source, such as pointing at a previous definition of a duplicate name.
+    ///
+    /// Only the lines of the source that are rendered are kept by
+    /// `set_text`, so labels added after it's called are rendered with their
+    /// byte offset instead of a snippet.
+    pub fn add_label(&mut self, span: Span, message: String) {
+        self.inner.labels.push(Label {
+            span,
+            message,
+            linecol: None,
+        });
+    }
+
+    /// Attaches a `help:` message to this error, typically a suggestion of how
+    /// to fix the problem.
+    ///
+    /// Help messages are rendered after the source snippet of this error.
+    pub fn add_help(&mut self, message: String) {
+        self.inner.notes.push(Note {
+            kind: NoteKind::Help,
+            message,
+        });
+    }
+
+    /// Attaches a `note:` message to this error, typically extra context
+    /// about why this error happened.
+    ///
+    /// Notes are rendered after the source snippet of this error.
+    pub fn add_note(&mut self, message: String) {
+        self.inner.notes.push(Note {
+            kind: NoteKind::Note,
+            message,
+        });
+    }
+
+    /// Returns a value which can be used to render this error with
+    /// configurable options, such as the number of lines of context to show
+    /// and whether terminal colors are used.
+    ///
+    /// The returned value implements `Display`. By default two lines of
+    /// context are shown around the offending line and no colors are used.
+    pub fn render(&self) -> Render<'_> {
+        Render {
+            error: self,
+            before: 2,
+            after: 2,
+            color: false,
+        }
+    }
+
+    /// Converts an error found in a module decoded from the contents of a
+    /// `(module binary ...)`, whose span is a byte offset of those contents,
+    /// into one which points at the text module `span` and mentions the
+    /// offset instead.
+    pub(crate) fn in_binary(self, span: Span) -> Error {
+        let message = format!(
+            "{} at byte offset {}",
+            self.message(),
+            self.inner.span.offset
+        );
+        Error::new(span, message)
+    }
+
     /// Returns the underlying `LexError`, if any, that describes this error.
     pub fn lex_error(&self) -> Option<&lexer::LexError> {
         match &self.inner.kind {
@@ -176,22 +324,23 @@ impl Error {
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let err = match &self.inner.kind {
-            ErrorKind::Lex(e) => e as &dyn fmt::Display,
-            ErrorKind::Custom(e) => e as &dyn fmt::Display,
-        };
+        let err = self.message();
         let text = match &self.inner.text {
             Some(text) => text,
             None => {
-                return write!(f, "{} at byte offset {}", err, self.inner.span.offset);
+                write!(f, "{} at byte offset {}", err, self.inner.span.offset)?;
+                for label in self.inner.labels.iter() {
+                    let offset = label.span.offset;
+                    write!(
+                        f,
+                        "\n      = note: {} at byte offset {}",
+                        label.message, offset
+                    )?;
+                }
+                return self.fmt_notes(f);
             }
         };
-        let file = self
-            .inner
-            .file
-            .as_ref()
-            .and_then(|p| p.to_str())
-            .unwrap_or("<anon>");
+        let file = self.file();
         write!(
             f,
             "\
@@ -205,18 +354,344 @@ impl fmt::Display for Error {
             line = text.line + 1,
             col = text.col + 1,
             err = err,
-            text = text.snippet,
+            text = text.source_line(text.line),
             marker = "^",
-        )
+        )?;
+        for label in self.inner.labels.iter() {
+            let (line, col) = match label.linecol {
+                Some(linecol) => linecol,
+                None => {
+                    write!(
+                        f,
+                        "\n      = note: {} at byte offset {}",
+                        label.message, label.span.offset
+                    )?;
+                    continue;
+                }
+            };
+            write!(
+                f,
+                "
+     ::: {file}:{line}:{col}
+      |
+ {line:4} | {text}
+      | {marker:>0$} {message}",
+                col + 1,
+                file = file,
+                line = line + 1,
+                col = col + 1,
+                text = text.source_line(line),
+                marker = "^",
+                message = label.message,
+            )?;
+        }
+        self.fmt_notes(f)
+    }
+}
+
+impl Error {
+    fn message(&self) -> &dyn fmt::Display {
+        match &self.inner.kind {
+            ErrorKind::Lex(e) => e,
+            ErrorKind::Custom(e) => e,
+        }
+    }
+
+    fn file(&self) -> &str {
+        self.inner
+            .file
+            .as_ref()
+            .and_then(|p| p.to_str())
+            .unwrap_or("<anon>")
+    }
+
+    fn fmt_notes(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for note in self.inner.notes.iter() {
+            write!(f, "\n      = {}: {}", note.kind.desc(), note.message)?;
+        }
+        Ok(())
     }
 }
 
 impl std::error::Error for Error {}
 
 impl Text {
-    fn new(content: &str, span: Span) -> Text {
+    /// Extracts the lines of `content` needed to render an error at `span`
+    /// with `labels`, and records where each label points.
+    fn new(content: &str, span: Span, labels: &mut [Label]) -> Text {
         let (line, col) = span.linecol_in(content);
-        let snippet = content.lines().nth(line).unwrap_or("").to_string();
-        Text { line, col, snippet }
+        let wanted = line.saturating_sub(MAX_CONTEXT)..=line + MAX_CONTEXT;
+        let mut label_lines = Vec::new();
+        for label in labels.iter_mut() {
+            let linecol = label.span.linecol_in(content);
+            label.linecol = Some(linecol);
+            label_lines.push(linecol.0);
+        }
+        let last = label_lines
+            .iter()
+            .cloned()
+            .fold(*wanted.end(), std::cmp::max);
+        let lines = content
+            .lines()
+            .enumerate()
+            .take(last + 1)
+            .filter(|(i, _)| wanted.contains(i) || label_lines.contains(i))
+            .map(|(i, l)| (i, l.to_string()))
+            .collect();
+        Text { line, col, lines }
+    }
+
+    fn source_line(&self, line: usize) -> &str {
+        self.lines.get(&line).map(|s| &s[..]).unwrap_or("")
+    }
+}
+
+/// A configurable rendering of an [`Error`], created with [`Error::render`].
+///
+/// Unlike the `Display` implementation of [`Error`] this renders a
+/// multi-line window of the original source around the error, optionally
+/// using ANSI terminal colors.
+pub struct Render<'a> {
+    error: &'a Error,
+    before: usize,
+    after: usize,
+    color: bool,
+}
+
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+const RESET: &str = "\x1b[0m";
+
+impl Render<'_> {
+    /// Configures how many lines of source are shown `before` and `after` the
+    /// line that this error points at.
+    ///
+    /// At most five lines on each side are kept when the error's text is
+    /// set, so larger values don't show any more than that.
+    pub fn context(mut self, before: usize, after: usize) -> Self {
+        self.before = before;
+        self.after = after;
+        self
+    }
+
+    /// Configures whether ANSI terminal colors are used in the output.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    fn paint(&self, style: &'static str) -> &'static str {
+        if self.color {
+            style
+        } else {
+            ""
+        }
+    }
+}
+
+impl fmt::Display for Render<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let error = &self.error.inner;
+        let (red, blue, bold, reset) = (
+            self.paint(RED),
+            self.paint(BLUE),
+            self.paint(BOLD),
+            self.paint(RESET),
+        );
+        write!(
+            f,
+            "{}error{}{}: {}{}",
+            red,
+            reset,
+            bold,
+            self.error.message(),
+            reset
+        )?;
+        let text = match &error.text {
+            Some(text) => text,
+            None => {
+                write!(f, " at byte offset {}", error.span.offset)?;
+                for label in error.labels.iter() {
+                    let offset = label.span.offset;
+                    write!(
+                        f,
+                        "\n      = note: {} at byte offset {}",
+                        label.message, offset
+                    )?;
+                }
+                return self.fmt_notes(f, 6);
+            }
+        };
+
+        let first = text.line.saturating_sub(self.before.min(MAX_CONTEXT));
+        let last = text.line + self.after.min(MAX_CONTEXT);
+        let max_line = error
+            .labels
+            .iter()
+            .filter_map(|l| l.linecol.map(|(line, _)| line))
+            .fold(last, std::cmp::max);
+        let width = (max_line + 1).to_string().len().max(4);
+        let gutter = " ".repeat(width + 2);
+        write!(
+            f,
+            "\n{}{}-->{} {}:{}:{}",
+            &gutter[1..],
+            blue,
+            reset,
+            self.error.file(),
+            text.line + 1,
+            text.col + 1
+        )?;
+        write!(f, "\n{}{}|{}", gutter, blue, reset)?;
+        for (&i, line) in text.lines.range(first..=last) {
+            write!(
+                f,
+                "\n{blue} {num:width$} |{reset} {line}",
+                blue = blue,
+                num = i + 1,
+                width = width,
+                reset = reset,
+                line = line,
+            )?;
+            if i == text.line {
+                write!(
+                    f,
+                    "\n{gutter}{blue}|{reset} {red}{marker:>col$}{reset}",
+                    gutter = gutter,
+                    blue = blue,
+                    red = red,
+                    reset = reset,
+                    marker = "^",
+                    col = text.col + 1,
+                )?;
+            }
+        }
+        for label in error.labels.iter() {
+            let (line, col) = match label.linecol {
+                Some(linecol) => linecol,
+                None => {
+                    write!(
+                        f,
+                        "\n{}{}={} note: {} at byte offset {}",
+                        gutter, blue, reset, label.message, label.span.offset
+                    )?;
+                    continue;
+                }
+            };
+            write!(
+                f,
+                "\n{}{}:::{} {}:{}:{}",
+                &gutter[1..],
+                blue,
+                reset,
+                self.error.file(),
+                line + 1,
+                col + 1
+            )?;
+            write!(f, "\n{}{}|{}", gutter, blue, reset)?;
+            write!(
+                f,
+                "\n{blue} {num:width$} |{reset} {line}",
+                blue = blue,
+                num = line + 1,
+                width = width,
+                reset = reset,
+                line = text.source_line(line),
+            )?;
+            write!(
+                f,
+                "\n{gutter}{blue}|{reset} {blue}{marker:>col$} {message}{reset}",
+                gutter = gutter,
+                blue = blue,
+                reset = reset,
+                marker = "-",
+                col = col + 1,
+                message = label.message,
+            )?;
+        }
+        if !error.notes.is_empty() {
+            write!(f, "\n{}{}|{}", gutter, blue, reset)?;
+        }
+        self.fmt_notes(f, width + 2)
+    }
+}
+
+impl Render<'_> {
+    fn fmt_notes(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        let (blue, reset) = (self.paint(BLUE), self.paint(RESET));
+        for note in self.error.inner.notes.iter() {
+            let color = match note.kind {
+                NoteKind::Help => self.paint(CYAN),
+                NoteKind::Note => self.paint(BOLD),
+            };
+            write!(
+                f,
+                "\n{:indent$}{}={} {}{}{}: {}",
+                "",
+                blue,
+                reset,
+                color,
+                note.kind.desc(),
+                reset,
+                note.message,
+                indent = indent,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_context() {
+        let text = "a\nb\nc\nd\ne\nf\n";
+        let mut err = Error::new(Span { offset: 6 }, "bad".to_string());
+        err.set_text(text);
+        err.add_help("try something else".to_string());
+        assert_eq!(
+            err.render().context(1, 1).to_string(),
+            "\
+error: bad
+     --> <anon>:4:1
+      |
+    3 | c
+    4 | d
+      | ^
+    5 | e
+      |
+      = help: try something else"
+        );
+        assert!(err.render().color(true).to_string().contains("\x1b[1;31m"));
+    }
+
+    #[test]
+    fn text_keeps_only_rendered_lines() {
+        let text = (0..100)
+            .map(|i| format!("line {}\n", i))
+            .collect::<String>();
+        let offset = |line: usize| text.find(&format!("line {}\n", line)).unwrap();
+        let mut err = Error::new(Span { offset: offset(50) }, "bad".to_string());
+        err.add_label(Span { offset: offset(1) }, "first".to_string());
+        err.set_text(&text);
+        err.add_label(Span { offset: offset(99) }, "last".to_string());
+
+        let lines = &err.inner.text.as_ref().unwrap().lines;
+        assert_eq!(lines.len(), 2 * MAX_CONTEXT + 2);
+        let rendered = err.render().context(100, 100).to_string();
+        assert!(
+            rendered.contains("   2 | line 1\n      | - first"),
+            "{}",
+            rendered
+        );
+        assert!(!rendered.contains("line 44"), "{}", rendered);
+        assert!(rendered.contains("  46 | line 45"), "{}", rendered);
+        assert!(rendered.contains(&format!("= note: last at byte offset {}", offset(99))));
+        let displayed = err.to_string();
+        assert!(displayed.contains("   2 | line 1"), "{}", displayed);
     }
 }
@@ -0,0 +1,618 @@
+use crate::ast::*;
+use crate::resolve::{num, type_key};
+use crate::visit::{IndexSpace, VisitMut};
+use crate::{Error, WasmFeatures};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A static linker which merges several modules into one.
+///
+/// Each module is added under a name, and imports whose module name matches
+/// one of the added modules are satisfied by that module's export of the same
+/// name, following chains of re-exported imports. Imports from any other
+/// module are kept as imports of the merged module, with identical imports
+/// merged into one.
+///
+/// Every module is resolved and validated before it's linked. The merged
+/// module starts with the function types of all modules, with identical types
+/// deduplicated, and the imports which weren't satisfied. These are followed
+/// by the functions, tables, memories, globals, and segments of each module
+/// in the order the modules were added, and all indices are renumbered to
+/// match. The exports of every module are kept, so export names must be
+/// unique across all modules, and at most one module may have a `start`
+/// function. Likewise at most one module may have a memory, and at most one
+/// may have a table unless the reference types proposal is enabled with
+/// [`Linker::features`].
+///
+/// # Examples
+///
+/// ```
+/// # fn foo() -> Result<(), wast::Error> {
+/// use wast::parser::{self, ParseBuffer};
+/// use wast::{Linker, Wat};
+///
+/// let math = ParseBuffer::new(r#"
+///     (module
+///         (func (export "double") (param i32) (result i32)
+///             (i32.add (local.get 0) (local.get 0))))
+/// "#)?;
+/// let main = ParseBuffer::new(r#"
+///     (module
+///         (import "math" "double" (func $double (param i32) (result i32)))
+///         (func (export "main") (result i32)
+///             (call $double (i32.const 21))))
+/// "#)?;
+/// let mut module = Linker::new()
+///     .module("math", parser::parse::<Wat>(&math)?.module)
+///     .module("main", parser::parse::<Wat>(&main)?.module)
+///     .link()?;
+/// let wasm = module.encode()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct Linker<'a> {
+    modules: Vec<(String, Module<'a>)>,
+    features: WasmFeatures,
+}
+
+/// The kinds of items which can be imported, in the order used to index
+/// arrays of per-kind data.
+const KINDS: [&str; 4] = ["function", "table", "memory", "global"];
+const FUNC: usize = 0;
+const TABLE: usize = 1;
+const MEMORY: usize = 2;
+const GLOBAL: usize = 3;
+
+type TypeKey = (Vec<ValType>, Vec<ValType>);
+
+impl<'a> Linker<'a> {
+    /// Creates a new linker with no modules.
+    pub fn new() -> Linker<'a> {
+        Linker::default()
+    }
+
+    /// Adds `module` to be linked, making its exports available to imports
+    /// from the module named `name`.
+    pub fn module(&mut self, name: &str, module: Module<'a>) -> &mut Linker<'a> {
+        self.modules.push((name.to_string(), module));
+        self
+    }
+
+    /// Configures which WebAssembly proposals the modules, and the merged
+    /// module, may use.
+    ///
+    /// Every module is validated with these features, which decide whether
+    /// the merged module may have more than one table. All proposals are
+    /// enabled by default.
+    pub fn features(&mut self, features: WasmFeatures) -> &mut Linker<'a> {
+        self.features = features;
+        self
+    }
+
+    /// Links together all of the modules added so far, returning the merged
+    /// module and leaving this linker empty.
+    ///
+    /// Errors are returned if any module fails to resolve or validate, if an
+    /// import names an export which doesn't exist or has a different kind or
+    /// type than the import, including limits which don't fit within the
+    /// import's, if imports form a cycle, or if the merged module would have
+    /// duplicate export names, multiple `start` functions, multiple
+    /// memories, or multiple tables without the reference types proposal.
+    /// Errors point into the source of the module they were found
+    /// in, and their message names that module.
+    pub fn link(&mut self) -> Result<Module<'a>, Error> {
+        let mut inputs = Vec::new();
+        for (name, module) in std::mem::take(&mut self.modules) {
+            inputs.push(Input::new(name, module, &self.features)?);
+        }
+        let mut by_name = HashMap::new();
+        for (i, input) in inputs.iter().enumerate() {
+            if by_name.insert(input.name.as_str(), i).is_some() {
+                return Err(Error::new(
+                    input.span,
+                    format!("duplicate module name `{}`", input.name),
+                ));
+            }
+        }
+
+        // Find the item each import refers to, which is either a definition
+        // in some module or an import from outside of the linked modules.
+        let mut targets = Vec::new();
+        for (m, input) in inputs.iter().enumerate() {
+            let mut module_targets: [Vec<Target>; 4] = Default::default();
+            for (kind, module_targets) in module_targets.iter_mut().enumerate() {
+                for index in 0..input.imports[kind].len() as u32 {
+                    let target = target(&inputs, &by_name, m, kind, index, &mut Vec::new())?;
+                    module_targets.push(target);
+                }
+            }
+            targets.push(module_targets);
+        }
+
+        // Outside imports come first in each index space, with identical
+        // ones merged, and are followed by the definitions of each module.
+        let mut counts = [0u32; 4];
+        let mut outside = HashMap::new();
+        let mut outside_index = HashMap::new();
+        let mut kept_imports = HashSet::new();
+        // The module each table and memory of the merged module comes from.
+        let mut owners: [Vec<usize>; 4] = Default::default();
+        for module_targets in targets.iter() {
+            for (kind, kind_targets) in module_targets.iter().enumerate() {
+                for target in kind_targets.iter() {
+                    if target.index >= inputs[target.module].imports[kind].len() as u32 {
+                        continue;
+                    }
+                    let input = &inputs[target.module];
+                    let import = input.import(kind, target.index);
+                    let desc = &input.descs[kind][target.index as usize];
+                    let key = (&import.module, &import.name, desc);
+                    let index = *outside.entry(key).or_insert_with(|| {
+                        kept_imports.insert((target.module, target.index, kind));
+                        owners[kind].push(target.module);
+                        counts[kind] += 1;
+                        counts[kind] - 1
+                    });
+                    outside_index.insert((target.module, kind, target.index), index);
+                }
+            }
+        }
+        let mut defined_base = Vec::new();
+        for (m, input) in inputs.iter().enumerate() {
+            defined_base.push(counts);
+            for (count, defined) in counts.iter_mut().zip(input.defined.iter()) {
+                *count += defined;
+            }
+            for (owners, defined) in owners.iter_mut().zip(input.defined.iter()) {
+                owners.extend((0..*defined).map(|_| m));
+            }
+        }
+        for &kind in [TABLE, MEMORY].iter() {
+            if kind == TABLE && self.features.reference_types {
+                continue;
+            }
+            if let [first, second, ..] = owners[kind][..] {
+                return Err(Error::new(
+                    inputs[second].span,
+                    format!(
+                        "modules `{}` and `{}` both have a {}, but the linked module can \
+                         only have one",
+                        inputs[first].name, inputs[second].name, KINDS[kind]
+                    ),
+                ));
+            }
+        }
+        let new_index = |module: usize, kind: usize, index: u32| {
+            let imports = inputs[module].imports[kind].len() as u32;
+            if index < imports {
+                outside_index[&(module, kind, index)]
+            } else {
+                defined_base[module][kind] + index - imports
+            }
+        };
+
+        // Identical types are merged, like `tyexpand::Expander` does.
+        let mut types = Vec::new();
+        let mut type_index = HashMap::new();
+        let mut maps = Vec::new();
+        let (mut elems, mut data) = (0, 0);
+        for (m, (input, targets)) in inputs.iter().zip(&targets).enumerate() {
+            let mut map = Renumber::default();
+            for key in input.types.iter() {
+                let index = *type_index.entry(key.clone()).or_insert_with(|| {
+                    types.push(key.clone());
+                    types.len() as u32 - 1
+                });
+                map.types.push(index);
+            }
+            for (kind, targets) in targets.iter().enumerate() {
+                let len = input.imports[kind].len() as u32 + input.defined[kind];
+                map.items[kind] = (0..len)
+                    .map(|index| match targets.get(index as usize) {
+                        Some(t) => new_index(t.module, kind, t.index),
+                        None => new_index(m, kind, index),
+                    })
+                    .collect();
+            }
+            map.elem_base = elems;
+            map.data_base = data;
+            elems += input.elems;
+            data += input.data;
+            maps.push(map);
+        }
+
+        let mut fields = types
+            .into_iter()
+            .map(|(params, results)| {
+                ModuleField::Type(Type {
+                    name: None,
+                    func: FunctionType {
+                        params: params.into_iter().map(|ty| (None, ty)).collect(),
+                        results,
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut imports = Vec::new();
+        let mut rest = Vec::new();
+        let mut exports = HashMap::new();
+        let mut start = None;
+        let mut names = HashSet::new();
+        let span = inputs.first().map(|i| i.span).unwrap_or(Span { offset: 0 });
+        for (m, (input, map)) in inputs.into_iter().zip(maps.iter_mut()).enumerate() {
+            let mut counts = [0u32; 4];
+            for mut field in input.fields {
+                map.visit_module_field(&mut field);
+                match &mut field {
+                    ModuleField::Type(_) => continue,
+                    ModuleField::Import(import) => {
+                        let kind = import_kind(&import.kind);
+                        counts[kind] += 1;
+                        if !kept_imports.contains(&(m, counts[kind] - 1, kind)) {
+                            continue;
+                        }
+                        unique_name(&mut import.id, kind == FUNC, &mut names);
+                        imports.push(field);
+                        continue;
+                    }
+                    ModuleField::Func(f) => unique_name(&mut f.name, true, &mut names),
+                    ModuleField::Table(t) => t.name = None,
+                    ModuleField::Memory(m) => m.name = None,
+                    ModuleField::Global(g) => g.name = None,
+                    ModuleField::Elem(e) => e.name = None,
+                    ModuleField::Data(d) => d.name = None,
+                    ModuleField::Export(e) => {
+                        if let Some(prev) = exports.insert(e.name.to_string(), input.name.clone()) {
+                            return Err(Error::new(
+                                e.span,
+                                format!(
+                                    "export `{}` of module `{}` has the same name as an \
+                                     export of module `{}`",
+                                    e.name, input.name, prev
+                                ),
+                            ));
+                        }
+                    }
+                    ModuleField::Start(_) => {
+                        if let Some(prev) = start.replace(input.name.clone()) {
+                            return Err(Error::new(
+                                input.span,
+                                format!(
+                                    "modules `{}` and `{}` both have a start function",
+                                    prev, input.name
+                                ),
+                            ));
+                        }
+                    }
+                }
+                rest.push(field);
+            }
+        }
+        fields.extend(imports);
+        fields.extend(rest);
+        Ok(Module {
+            span,
+            name: None,
+            kind: ModuleKind::Text(fields),
+        })
+    }
+}
+
+/// A module being linked, after it's been resolved.
+struct Input<'a> {
+    name: String,
+    span: Span,
+    fields: Vec<ModuleField<'a>>,
+    types: Vec<TypeKey>,
+    /// The position in `fields` of the imports of each kind.
+    imports: [Vec<usize>; 4],
+    /// The number of items of each kind defined by the module.
+    defined: [u32; 4],
+    /// The type of every item of each kind, in index order, which must be
+    /// compatible between an import and what it's linked to.
+    descs: [Vec<Desc>; 4],
+    exports: HashMap<String, (usize, u32)>,
+    elems: u32,
+    data: u32,
+}
+
+impl<'a> Input<'a> {
+    fn new(
+        name: String,
+        mut module: Module<'a>,
+        features: &WasmFeatures,
+    ) -> Result<Input<'a>, Error> {
+        let with_name = |mut e: Error| {
+            e.add_note(format!("in module `{}`", name));
+            e
+        };
+        module.resolve().map_err(with_name)?;
+        module.validate_with(features).map_err(with_name)?;
+        let fields = match module.kind {
+            ModuleKind::Text(fields) => fields,
+            ModuleKind::Binary(_) => {
+                return Err(Error::new(
+                    module.span,
+                    format!(
+                        "module `{}` is in the binary format and can't be linked",
+                        name
+                    ),
+                ))
+            }
+        };
+
+        let mut input = Input {
+            name,
+            span: module.span,
+            fields: Vec::new(),
+            types: Vec::new(),
+            imports: Default::default(),
+            defined: [0; 4],
+            descs: Default::default(),
+            exports: HashMap::new(),
+            elems: 0,
+            data: 0,
+        };
+        // Types are collected first; see `resolve` for why.
+        for field in fields.iter() {
+            if let ModuleField::Type(ty) = field {
+                input.types.push(type_key(&ty.func));
+            }
+        }
+        for (i, field) in fields.iter().enumerate() {
+            match field {
+                ModuleField::Type(_) => {}
+                ModuleField::Import(import) => {
+                    let (kind, desc) = match &import.kind {
+                        ImportKind::Func(ty) => (FUNC, input.func_desc(ty)),
+                        ImportKind::Table(ty) => (TABLE, table_desc(ty)),
+                        ImportKind::Memory(ty) => (MEMORY, memory_desc(ty)),
+                        ImportKind::Global(ty) => (GLOBAL, global_desc(ty)),
+                    };
+                    input.imports[kind].push(i);
+                    input.descs[kind].push(desc);
+                }
+                ModuleField::Func(f) => {
+                    let desc = input.func_desc(&f.ty);
+                    input.define(FUNC, desc);
+                }
+                ModuleField::Table(t) => {
+                    if let TableKind::Normal(ty) = &t.kind {
+                        input.define(TABLE, table_desc(ty));
+                    }
+                }
+                ModuleField::Memory(m) => {
+                    if let MemoryKind::Normal(ty) = &m.kind {
+                        input.define(MEMORY, memory_desc(ty));
+                    }
+                }
+                ModuleField::Global(g) => input.define(GLOBAL, global_desc(&g.ty)),
+                ModuleField::Export(e) => {
+                    let (kind, index) = match &e.kind {
+                        ExportKind::Func(i) => (FUNC, i),
+                        ExportKind::Table(i) => (TABLE, i),
+                        ExportKind::Memory(i) => (MEMORY, i),
+                        ExportKind::Global(i) => (GLOBAL, i),
+                    };
+                    input.exports.insert(e.name.to_string(), (kind, num(index)));
+                }
+                ModuleField::Elem(_) => input.elems += 1,
+                ModuleField::Data(_) => input.data += 1,
+                ModuleField::Start(_) => {}
+            }
+        }
+        input.fields = fields;
+        Ok(input)
+    }
+
+    fn define(&mut self, kind: usize, desc: Desc) {
+        self.defined[kind] += 1;
+        self.descs[kind].push(desc);
+    }
+
+    fn func_desc(&self, ty: &TypeUse<'a>) -> Desc {
+        let key = match &ty.index {
+            Some(index) => self.types[num(index) as usize].clone(),
+            None => type_key(&ty.ty),
+        };
+        let names = |tys: &[ValType]| {
+            let names = tys.iter().map(|t| t.name()).collect::<Vec<_>>();
+            format!("[{}]", names.join(" "))
+        };
+        Desc {
+            ty: format!("func {} -> {}", names(&key.0), names(&key.1)),
+            limits: None,
+        }
+    }
+
+    fn import(&self, kind: usize, index: u32) -> &Import<'a> {
+        match &self.fields[self.imports[kind][index as usize]] {
+            ModuleField::Import(import) => import,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// The type of an item, such as `func [i32] -> []` or `memory 1 2`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Desc {
+    /// Everything but the limits, which must match exactly.
+    ty: String,
+    limits: Option<Limits>,
+}
+
+impl Desc {
+    /// Returns whether an item of this type can satisfy an import of type
+    /// `import`, which requires the item's limits to fit within the
+    /// import's.
+    fn satisfies(&self, import: &Desc) -> bool {
+        let limits = match (&self.limits, &import.limits) {
+            (Some(actual), Some(expected)) => {
+                actual.min >= expected.min
+                    && match expected.max {
+                        Some(max) => matches!(actual.max, Some(n) if n <= max),
+                        None => true,
+                    }
+            }
+            _ => true,
+        };
+        self.ty == import.ty && limits
+    }
+}
+
+impl fmt::Display for Desc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.ty)?;
+        if let Some(limits) = &self.limits {
+            write!(f, " {}", limits.min)?;
+            if let Some(max) = limits.max {
+                write!(f, " {}", max)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn table_desc(ty: &TableType) -> Desc {
+    let elem = match ty.elem {
+        TableElemType::Funcref => "funcref",
+        TableElemType::Anyref => "anyref",
+        TableElemType::Nullref => "nullref",
+    };
+    Desc {
+        ty: format!("table {}", elem),
+        limits: Some(ty.limits),
+    }
+}
+
+fn memory_desc(ty: &MemoryType) -> Desc {
+    let ty_name = if ty.shared { "shared memory" } else { "memory" };
+    Desc {
+        ty: ty_name.to_string(),
+        limits: Some(ty.limits),
+    }
+}
+
+fn global_desc(ty: &GlobalType) -> Desc {
+    let ty = if ty.mutable {
+        format!("global (mut {})", ty.ty.name())
+    } else {
+        format!("global {}", ty.ty.name())
+    };
+    Desc { ty, limits: None }
+}
+
+fn import_kind(kind: &ImportKind<'_>) -> usize {
+    match kind {
+        ImportKind::Func(_) => FUNC,
+        ImportKind::Table(_) => TABLE,
+        ImportKind::Memory(_) => MEMORY,
+        ImportKind::Global(_) => GLOBAL,
+    }
+}
+
+/// Clears `name` unless it's a function name which hasn't been seen before,
+/// since identifiers of the merged module must be unique and only function
+/// names are kept in the `name` section.
+fn unique_name(name: &mut Option<Id<'_>>, func: bool, seen: &mut HashSet<String>) {
+    let keep = match name {
+        Some(id) => func && seen.insert(id.name().to_string()),
+        None => false,
+    };
+    if !keep {
+        *name = None;
+    }
+}
+
+/// The item an index refers to, as an index into `module`'s index space of
+/// the same kind. The item is an import from outside the linked modules if
+/// the index is less than the number of imports of that module.
+struct Target {
+    module: usize,
+    index: u32,
+}
+
+/// Follows the import `index` of `kind` in module `m` to the item which
+/// satisfies it, checking that types match along the way.
+fn target(
+    inputs: &[Input<'_>],
+    by_name: &HashMap<&str, usize>,
+    m: usize,
+    kind: usize,
+    index: u32,
+    stack: &mut Vec<(usize, usize, u32)>,
+) -> Result<Target, Error> {
+    let input = &inputs[m];
+    if index >= input.imports[kind].len() as u32 {
+        return Ok(Target { module: m, index });
+    }
+    let import = input.import(kind, index);
+    let other = match by_name.get(&*import.module) {
+        Some(other) => *other,
+        None => return Ok(Target { module: m, index }),
+    };
+    let err = |msg: String| {
+        Error::new(
+            import.span,
+            format!(
+                "failed to link import `{}` `{}` of module `{}`: {}",
+                import.module, import.name, input.name, msg
+            ),
+        )
+    };
+    let (export_kind, export_index) = match inputs[other].exports.get(&*import.name) {
+        Some(export) => *export,
+        None => return Err(err("no export of that name".to_string())),
+    };
+    if export_kind != kind {
+        return Err(err(format!(
+            "expected a {}, but the export is a {}",
+            KINDS[kind], KINDS[export_kind]
+        )));
+    }
+    if stack.contains(&(m, kind, index)) {
+        return Err(err("imports form a cycle".to_string()));
+    }
+    stack.push((m, kind, index));
+    let target = target(inputs, by_name, other, kind, export_index, stack)?;
+    stack.pop();
+
+    let expected = &input.descs[kind][index as usize];
+    let actual = &inputs[target.module].descs[kind][target.index as usize];
+    if !actual.satisfies(expected) {
+        return Err(err(format!(
+            "expected `{}`, but found `{}`",
+            expected, actual
+        )));
+    }
+    Ok(target)
+}
+
+/// Maps the indices of one module to those of the merged module.
+#[derive(Default)]
+struct Renumber {
+    types: Vec<u32>,
+    items: [Vec<u32>; 4],
+    elem_base: u32,
+    data_base: u32,
+}
+
+impl<'a> VisitMut<'a> for Renumber {
+    fn visit_index(&mut self, index: &mut Index<'a>, space: IndexSpace) {
+        let n = match index {
+            Index::Num(n) => n,
+            Index::Id(_) => return,
+        };
+        *n = match space {
+            IndexSpace::Func => self.items[FUNC][*n as usize],
+            IndexSpace::Table => self.items[TABLE][*n as usize],
+            IndexSpace::Memory => self.items[MEMORY][*n as usize],
+            IndexSpace::Global => self.items[GLOBAL][*n as usize],
+            IndexSpace::Type => self.types[*n as usize],
+            IndexSpace::Elem => self.elem_base + *n,
+            IndexSpace::Data => self.data_base + *n,
+            IndexSpace::Local | IndexSpace::Label => return,
+        };
+    }
+}
@@ -0,0 +1,329 @@
+use crate::ast::*;
+use crate::dump::SECTION_NAMES;
+use crate::gc::{new_indices, Renumber};
+use crate::visit::{IndexSpace, Visit, VisitMut};
+use crate::Error;
+use std::fmt;
+
+/// How the size of each section of a module changed when it was rewritten by
+/// [`Module::minimize`](crate::Module::minimize).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SizeReport {
+    /// Each section found in either encoding, in the order of their ids.
+    /// Custom sections are all counted together.
+    pub sections: Vec<SectionSize>,
+}
+
+/// The size in bytes of one section, including its id and size, before and
+/// after the module was minimized.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionSize {
+    /// The name of the section, such as `Type` or `Code`.
+    pub name: &'static str,
+    /// The size of the section before minimizing, or 0 if it wasn't present.
+    pub before: usize,
+    /// The size of the section after minimizing, or 0 if it was removed.
+    pub after: usize,
+}
+
+impl SizeReport {
+    /// Creates a report comparing the sections of the binary modules
+    /// `before` and `after`.
+    pub(crate) fn new(before: &[u8], after: &[u8]) -> SizeReport {
+        let before = section_sizes(before);
+        let after = section_sizes(after);
+        let sections = SECTION_NAMES
+            .iter()
+            .zip(before.iter().zip(&after))
+            .filter(|(_, (before, after))| **before > 0 || **after > 0)
+            .map(|(name, (before, after))| SectionSize {
+                name,
+                before: *before,
+                after: *after,
+            })
+            .collect();
+        SizeReport { sections }
+    }
+
+    /// Returns the number of bytes saved across all sections, which is
+    /// negative if the module got larger.
+    pub fn saved(&self) -> isize {
+        self.sections.iter().map(|s| s.saved()).sum()
+    }
+}
+
+impl SectionSize {
+    /// Returns the number of bytes saved in this section, which is negative
+    /// if the section got larger.
+    pub fn saved(&self) -> isize {
+        self.before as isize - self.after as isize
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for s in self.sections.iter() {
+            writeln!(
+                f,
+                "{:>9}: {} -> {} bytes ({} saved)",
+                s.name,
+                s.before,
+                s.after,
+                s.saved()
+            )?;
+        }
+        write!(f, "{:>9}: {} bytes saved", "Total", self.saved())
+    }
+}
+
+/// Returns the total size of the sections of the binary module `bytes` with
+/// each id.
+///
+/// `(module binary ...)` modules are encoded verbatim even if they're
+/// malformed, so sections are only counted up to the first error.
+fn section_sizes(bytes: &[u8]) -> [usize; 13] {
+    let mut sizes = [0; 13];
+    let _ = crate::reader::sections(bytes, |id, s| {
+        let len = s.end() - s.pos();
+        sizes[usize::from(id)] += 1 + leb_len(len) + len;
+        s.skip_to_end();
+        Ok(())
+    });
+    sizes
+}
+
+fn leb_len(mut n: usize) -> usize {
+    let mut len = 1;
+    while n >= 0x80 {
+        n >>= 7;
+        len += 1;
+    }
+    len
+}
+
+/// Rewrites the resolved `module` so that it encodes to fewer bytes without
+/// changing its behavior.
+///
+/// Types which aren't referenced are removed, defined functions and globals
+/// are sorted so that the most referenced ones get the smallest indices, and
+/// the locals of each function are sorted so that locals of the same type
+/// are declared in one group.
+pub fn minimize(module: &mut Module<'_>) -> Result<(), Error> {
+    let fields = match &mut module.kind {
+        ModuleKind::Text(fields) => fields,
+        ModuleKind::Binary(_) => return Ok(()),
+    };
+
+    let params = fields
+        .iter()
+        .filter_map(|f| match f {
+            ModuleField::Type(ty) => Some(ty.func.params.len()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let mut counts = Counts {
+        funcs: Vec::new(),
+        globals: Vec::new(),
+        types: vec![0; params.len()],
+        span: module.span,
+        err: None,
+    };
+    let (mut imported_funcs, mut imported_globals) = (0, 0);
+    for field in fields.iter() {
+        match field {
+            ModuleField::Func(_) => counts.funcs.push(0),
+            ModuleField::Global(_) => counts.globals.push(0),
+            ModuleField::Import(i) => match i.kind {
+                ImportKind::Func(_) => {
+                    counts.funcs.push(0);
+                    imported_funcs += 1;
+                }
+                ImportKind::Global(_) => {
+                    counts.globals.push(0);
+                    imported_globals += 1;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    for field in fields.iter() {
+        counts.visit_module_field(field);
+    }
+    if let Some(err) = counts.err {
+        return Err(err);
+    }
+
+    // Locals are grouped before types are renumbered, since the number of
+    // parameters of each function is found through its type.
+    for field in fields.iter_mut() {
+        if let ModuleField::Func(func) = field {
+            let params = match &func.ty.index {
+                Some(Index::Num(n)) => params.get(*n as usize).cloned(),
+                _ => Some(func.ty.ty.params.len()),
+            };
+            if let Some(params) = params {
+                group_locals(func, params);
+            }
+        }
+    }
+
+    // Imports keep their indices, since they're numbered before everything
+    // else, and only defined items are sorted.
+    let funcs = by_frequency(&counts.funcs, imported_funcs);
+    let globals = by_frequency(&counts.globals, imported_globals);
+    reorder(fields, &funcs[imported_funcs..], imported_funcs, |f| {
+        matches!(f, ModuleField::Func(_))
+    });
+    reorder(
+        fields,
+        &globals[imported_globals..],
+        imported_globals,
+        |f| matches!(f, ModuleField::Global(_)),
+    );
+
+    let mut ty = 0;
+    fields.retain(|field| match field {
+        ModuleField::Type(_) => {
+            ty += 1;
+            counts.types[ty - 1] > 0
+        }
+        _ => true,
+    });
+
+    let mut renumber = Renumber {
+        funcs: positions(&funcs),
+        globals: positions(&globals),
+        types: new_indices(&counts.types.iter().map(|c| *c > 0).collect::<Vec<_>>()),
+    };
+    for field in fields.iter_mut() {
+        renumber.visit_module_field(field);
+    }
+    Ok(())
+}
+
+/// Returns the old indices of items in their new order, with the first
+/// `fixed` items kept in place and the rest sorted by descending `counts`.
+fn by_frequency(counts: &[u32], fixed: usize) -> Vec<usize> {
+    let mut order = (0..counts.len()).collect::<Vec<_>>();
+    order[fixed..].sort_by_key(|i| std::cmp::Reverse(counts[*i]));
+    order
+}
+
+/// Returns the new index of each item, given the old indices in new order.
+fn positions(order: &[usize]) -> Vec<Option<u32>> {
+    let mut indices = vec![None; order.len()];
+    for (new, old) in order.iter().enumerate() {
+        indices[*old] = Some(new as u32);
+    }
+    indices
+}
+
+/// Rearranges the fields for which `is_item` returns true, which are numbered
+/// from `base`, so that they're in the index order `order`.
+fn reorder<'a>(
+    fields: &mut Vec<ModuleField<'a>>,
+    order: &[usize],
+    base: usize,
+    is_item: impl Fn(&ModuleField<'a>) -> bool,
+) {
+    let slots = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| is_item(f))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    let mut all = std::mem::take(fields)
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<_>>();
+    let items = order
+        .iter()
+        .map(|old| all[slots[old - base]].take().unwrap())
+        .collect::<Vec<_>>();
+    for (slot, item) in slots.into_iter().zip(items) {
+        all[slot] = Some(item);
+    }
+    *fields = all.into_iter().map(Option::unwrap).collect();
+}
+
+/// Sorts the locals of `func`, which has `params` parameters, so that locals
+/// of the same type are adjacent, in the order each type is first declared.
+fn group_locals(func: &mut Func<'_>, params: usize) {
+    let locals = match &mut func.kind {
+        FuncKind::Inline { locals, .. } => locals,
+        FuncKind::Import { .. } => return,
+    };
+    let mut tys = Vec::new();
+    for (_, ty) in locals.iter() {
+        if !tys.contains(ty) {
+            tys.push(*ty);
+        }
+    }
+    let mut order = (0..locals.len()).collect::<Vec<_>>();
+    order.sort_by_key(|i| tys.iter().position(|ty| *ty == locals[*i].1));
+    if order.iter().enumerate().all(|(new, old)| new == *old) {
+        return;
+    }
+
+    let mut map = vec![0; params + locals.len()];
+    for (i, slot) in map.iter_mut().enumerate().take(params) {
+        *slot = i as u32;
+    }
+    for (new, old) in order.iter().enumerate() {
+        map[params + old] = (params + new) as u32;
+    }
+    *locals = order.iter().map(|i| locals[*i].clone()).collect();
+    LocalRenumber(map).visit_func(func);
+}
+
+/// Counts the references to each function, global, and type.
+struct Counts {
+    funcs: Vec<u32>,
+    globals: Vec<u32>,
+    types: Vec<u32>,
+    /// The item being visited, for errors about unknown indices.
+    span: Span,
+    err: Option<Error>,
+}
+
+impl<'a> Visit<'a> for Counts {
+    fn visit_span(&mut self, span: &Span) {
+        self.span = *span;
+    }
+
+    fn visit_index(&mut self, index: &Index<'a>, space: IndexSpace) {
+        let n = match index {
+            Index::Num(n) => *n as usize,
+            Index::Id(_) => return,
+        };
+        let (counts, what) = match space {
+            IndexSpace::Func => (&mut self.funcs, "function"),
+            IndexSpace::Global => (&mut self.globals, "global"),
+            IndexSpace::Type => (&mut self.types, "type"),
+            _ => return,
+        };
+        match counts.get_mut(n) {
+            Some(count) => *count += 1,
+            None => {
+                if self.err.is_none() {
+                    self.err = Some(Error::new(self.span, format!("unknown {} {}", what, n)));
+                }
+            }
+        }
+    }
+}
+
+/// Maps the old index of each local of a function to its new index.
+struct LocalRenumber(Vec<u32>);
+
+impl<'a> VisitMut<'a> for LocalRenumber {
+    fn visit_index(&mut self, index: &mut Index<'a>, space: IndexSpace) {
+        if let (Index::Num(n), IndexSpace::Local) = (index, space) {
+            // Unknown locals are left for validation to report.
+            if let Some(new) = self.0.get(*n as usize) {
+                *n = *new;
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use crate::ast::*;
+use crate::Error;
+
+/// Options to configure which rewrites are applied to function bodies.
+///
+/// This is passed to [`Module::optimize_with`](crate::Module::optimize_with),
+/// and the default set of options, with every rewrite enabled, is used by
+/// [`Module::optimize`](crate::Module::optimize).
+#[derive(Clone, Debug)]
+pub struct OptimizeOptions {
+    fold_constants: bool,
+    remove_nops: bool,
+    remove_dropped_constants: bool,
+    local_tees: bool,
+    thread_branches: bool,
+}
+
+impl Default for OptimizeOptions {
+    fn default() -> OptimizeOptions {
+        OptimizeOptions {
+            fold_constants: true,
+            remove_nops: true,
+            remove_dropped_constants: true,
+            local_tees: true,
+            thread_branches: true,
+        }
+    }
+}
+
+impl OptimizeOptions {
+    /// Creates a new set of default options.
+    pub fn new() -> OptimizeOptions {
+        OptimizeOptions::default()
+    }
+
+    /// Configures whether `i32.add`, `i32.sub`, and `i32.mul` of two
+    /// `i32.const` operands are replaced with the `i32.const` of their
+    /// wrapping result.
+    pub fn fold_constants(&mut self, enabled: bool) -> &mut OptimizeOptions {
+        self.fold_constants = enabled;
+        self
+    }
+
+    /// Configures whether `nop` instructions are removed.
+    pub fn remove_nops(&mut self, enabled: bool) -> &mut OptimizeOptions {
+        self.remove_nops = enabled;
+        self
+    }
+
+    /// Configures whether a constant, such as `i32.const` or `ref.null`,
+    /// which is immediately dropped is removed along with its `drop`.
+    pub fn remove_dropped_constants(&mut self, enabled: bool) -> &mut OptimizeOptions {
+        self.remove_dropped_constants = enabled;
+        self
+    }
+
+    /// Configures whether `local.set` followed by `local.get` of the same
+    /// local is replaced with `local.tee`.
+    pub fn local_tees(&mut self, enabled: bool) -> &mut OptimizeOptions {
+        self.local_tees = enabled;
+        self
+    }
+
+    /// Configures whether branches to the end of the block they're
+    /// immediately followed by are removed.
+    ///
+    /// A `br 0` is removed when the stack already holds exactly the values
+    /// the block ends with, and a `br_if 0` is replaced with a `drop` of its
+    /// condition. Branches to the start of a `loop` are never changed.
+    pub fn thread_branches(&mut self, enabled: bool) -> &mut OptimizeOptions {
+        self.thread_branches = enabled;
+        self
+    }
+}
+
+/// Applies the rewrites enabled in `options` to each function body of
+/// `module`, which must be resolved and valid.
+pub fn optimize(module: &mut Module<'_>, options: &OptimizeOptions) -> Result<(), Error> {
+    crate::validate::validate(module)?;
+    let ends = crate::validate::block_ends(module)?;
+    let fields = match &mut module.kind {
+        ModuleKind::Text(fields) => fields,
+        ModuleKind::Binary(_) => return Ok(()),
+    };
+    let bodies = fields.iter_mut().filter_map(|field| match field {
+        ModuleField::Func(Func {
+            kind: FuncKind::Inline { expression, .. },
+            ..
+        }) => Some(expression),
+        _ => None,
+    });
+    for (expression, ends) in bodies.zip(ends) {
+        let mut instrs = std::mem::take(&mut expression.instrs);
+        if options.thread_branches {
+            instrs = thread_branches(instrs, &ends);
+        }
+        expression.instrs = peephole(instrs, options);
+    }
+    Ok(())
+}
+
+/// Removes branches which are immediately followed by the end of their
+/// target, where `ends` is whether each instruction is reached with exactly
+/// the values its innermost block ends with.
+fn thread_branches<'a>(instrs: Vec<Instruction<'a>>, ends: &[bool]) -> Vec<Instruction<'a>> {
+    // Whether each enclosing block is a `loop`, whose label is its start.
+    let mut loops = Vec::new();
+    let mut out = Vec::with_capacity(instrs.len());
+    for (i, instr) in instrs.iter().enumerate() {
+        let falls_to_end = match instrs.get(i + 1) {
+            None => loops.is_empty(),
+            Some(Instruction::End(_)) | Some(Instruction::Else(_)) => loops.last() == Some(&false),
+            Some(_) => false,
+        };
+        match instr {
+            Instruction::Block(_) | Instruction::If(_) => loops.push(false),
+            Instruction::Loop(_) => loops.push(true),
+            Instruction::End(_) => {
+                loops.pop();
+            }
+            Instruction::Br(Index::Num(0)) if falls_to_end && ends[i] => continue,
+            Instruction::BrIf(Index::Num(0)) if falls_to_end => {
+                out.push(Instruction::Drop);
+                continue;
+            }
+            _ => {}
+        }
+        out.push(instr.clone());
+    }
+    out
+}
+
+/// Rewrites `instrs` one instruction at a time, matching patterns against
+/// the end of the rewritten instructions so that rewrites can cascade.
+fn peephole<'a>(instrs: Vec<Instruction<'a>>, options: &OptimizeOptions) -> Vec<Instruction<'a>> {
+    let mut out: Vec<Instruction<'a>> = Vec::with_capacity(instrs.len());
+    for instr in instrs {
+        let n = out.len();
+        match (&out[n.saturating_sub(2)..], &instr) {
+            (_, Instruction::Nop) if options.remove_nops => {}
+            ([.., last], Instruction::Drop)
+                if options.remove_dropped_constants && is_constant(last) =>
+            {
+                out.pop();
+            }
+            ([Instruction::I32Const(a), Instruction::I32Const(b)], op)
+                if options.fold_constants && fold(op, 0, 0).is_some() =>
+            {
+                let folded = fold(op, *a, *b).unwrap();
+                out.truncate(n - 2);
+                out.push(Instruction::I32Const(folded));
+            }
+            ([.., Instruction::LocalSet(set)], Instruction::LocalGet(get))
+                if options.local_tees && set == get =>
+            {
+                out[n - 1] = Instruction::LocalTee(get.clone());
+            }
+            _ => out.push(instr),
+        }
+    }
+    out
+}
+
+/// Returns the result of the `i32` arithmetic instruction `op` applied to
+/// `a` and `b`, or `None` if `op` isn't one which is folded.
+fn fold(op: &Instruction<'_>, a: i32, b: i32) -> Option<i32> {
+    match op {
+        Instruction::I32Add => Some(a.wrapping_add(b)),
+        Instruction::I32Sub => Some(a.wrapping_sub(b)),
+        Instruction::I32Mul => Some(a.wrapping_mul(b)),
+        _ => None,
+    }
+}
+
+/// Returns whether `instr` pushes a value without any other effect.
+fn is_constant(instr: &Instruction<'_>) -> bool {
+    matches!(
+        instr,
+        Instruction::I32Const(_)
+            | Instruction::I64Const(_)
+            | Instruction::F32Const(_)
+            | Instruction::F64Const(_)
+            | Instruction::V128Const(_)
+            | Instruction::RefNull
+    )
+}
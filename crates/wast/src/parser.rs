@@ -344,6 +344,16 @@ impl ParseBuffer<'_> {
     }
 }
 
+impl<'a> ParseBuffer<'a> {
+    pub(crate) fn input(&self) -> &'a str {
+        self.input
+    }
+
+    pub(crate) fn sources(&self) -> impl Iterator<Item = &Source<'a>> {
+        self.tokens.iter().map(|(source, _)| source)
+    }
+}
+
 impl<'a> Parser<'a> {
     /// Returns whether there are no more `Token` tokens to parse from this
     /// [`Parser`].
@@ -0,0 +1,992 @@
+//! Reading of the binary format of WebAssembly modules.
+//!
+//! This is used both to check the structure of binary modules, for example
+//! those passed through verbatim with `(module binary ...)`, and to decode
+//! binary modules into a [`Module`]. Errors are reported with the byte offset
+//! within the binary at which they were found.
+
+use crate::ast::*;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::str;
+
+/// An error found in a binary module, at `offset` bytes into the module.
+#[derive(Debug)]
+pub struct BinaryError {
+    pub offset: usize,
+    pub message: String,
+}
+
+pub type Result<T> = std::result::Result<T, BinaryError>;
+
+/// The maximum number of locals a decoded function may declare, which keeps
+/// a few bytes of input from allocating gigabytes of locals.
+const MAX_LOCALS: u64 = 50_000;
+
+/// A cursor over a binary module, limited to the bytes before `end`.
+///
+/// Positions are always relative to the start of the whole module so errors
+/// from nested readers report absolute offsets.
+#[derive(Clone)]
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    end: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader {
+            bytes,
+            pos: 0,
+            end: bytes.len(),
+        }
+    }
+
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn eof(&self) -> bool {
+        self.pos >= self.end
+    }
+
+    pub fn skip_to_end(&mut self) {
+        self.pos = self.end;
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> BinaryError {
+        self.error_at(self.pos, message)
+    }
+
+    pub fn error_at(&self, offset: usize, message: impl Into<String>) -> BinaryError {
+        BinaryError {
+            offset,
+            message: message.into(),
+        }
+    }
+
+    pub fn u8(&mut self) -> Result<u8> {
+        if self.eof() {
+            return Err(self.error("unexpected end"));
+        }
+        self.pos += 1;
+        Ok(self.bytes[self.pos - 1])
+    }
+
+    pub fn peek_u8(&self) -> Result<u8> {
+        self.clone().u8()
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.end - self.pos < len {
+            return Err(self.error("unexpected end"));
+        }
+        self.pos += len;
+        Ok(&self.bytes[self.pos - len..self.pos])
+    }
+
+    /// Reads a byte which is reserved in the binary format and must be
+    /// `byte`, such as the memory index of `memory.size`.
+    pub fn reserved(&mut self, byte: u8) -> Result<()> {
+        if self.u8()? == byte {
+            return Ok(());
+        }
+        let offset = self.pos - 1;
+        if byte == 0 {
+            Err(self.error_at(offset, "zero byte expected"))
+        } else {
+            Err(self.error_at(offset, format!("expected byte {:#04x}", byte)))
+        }
+    }
+
+    /// Returns the bytes from `start` up to the current position.
+    pub fn bytes_since(&self, start: usize) -> &'a [u8] {
+        &self.bytes[start..self.pos]
+    }
+
+    /// Returns a reader over the next `len` bytes, and skips past them.
+    pub fn sub(&mut self, len: usize) -> Result<Reader<'a>> {
+        let start = self.pos;
+        self.bytes(len)?;
+        Ok(Reader {
+            bytes: self.bytes,
+            pos: start,
+            end: self.pos,
+        })
+    }
+
+    pub fn var_u32(&mut self) -> Result<u32> {
+        Ok(self.leb(32, false)? as u32)
+    }
+
+    pub fn var_i32(&mut self) -> Result<i32> {
+        Ok(self.leb(32, true)? as i32)
+    }
+
+    pub fn var_s33(&mut self) -> Result<i64> {
+        Ok(self.leb(33, true)? as i64)
+    }
+
+    pub fn var_i64(&mut self) -> Result<i64> {
+        Ok(self.leb(64, true)? as i64)
+    }
+
+    /// Reads a LEB128 integer of `bits` bits, returning its bits sign or zero
+    /// extended to 64 bits.
+    fn leb(&mut self, bits: u32, signed: bool) -> Result<u64> {
+        let max_bytes = bits / 7 + 1;
+        let mut result = 0u64;
+        let mut shift = 0;
+        for i in 0..max_bytes {
+            let byte = self.u8()?;
+            result |= u64::from(byte & 0x7f) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                // In the last byte only the bits which fit in the integer may
+                // be used, and for signed integers the unused bits must match
+                // the sign bit.
+                if i == max_bytes - 1 {
+                    let used = bits - 7 * i;
+                    let unused = (byte & 0x7f) >> used;
+                    let valid = if signed {
+                        let sign = (byte >> (used - 1)) & 1;
+                        let mask = 0x7f >> used;
+                        unused == 0 || (sign == 1 && unused == mask)
+                    } else {
+                        unused == 0
+                    };
+                    if !valid {
+                        return Err(self.error_at(self.pos - 1, "integer too large"));
+                    }
+                }
+                if signed && shift < 64 && byte & 0x40 != 0 {
+                    result |= !0 << shift;
+                }
+                return Ok(result);
+            }
+        }
+        Err(self.error_at(self.pos - 1, "integer representation too long"))
+    }
+
+    pub fn name(&mut self) -> Result<&'a str> {
+        let len = self.var_u32()? as usize;
+        let start = self.pos;
+        let bytes = self.bytes(len)?;
+        str::from_utf8(bytes).map_err(|_| self.error_at(start, "malformed UTF-8 encoding"))
+    }
+
+    /// Reads a vector, calling `f` to read each of its items.
+    pub fn vec<T>(&mut self, mut f: impl FnMut(&mut Reader<'a>) -> Result<T>) -> Result<Vec<T>> {
+        let count = self.var_u32()?;
+        // The count isn't used to preallocate since it's often much larger
+        // than the rest of the input in malformed modules.
+        let mut items = Vec::new();
+        for _ in 0..count {
+            items.push(f(self)?);
+        }
+        Ok(items)
+    }
+
+    /// Reads the opcode of an instruction, including its prefix if it has one.
+    pub fn opcode(&mut self) -> Result<Opcode> {
+        let byte = self.u8()?;
+        match byte {
+            0xfc..=0xfe => Ok(Opcode {
+                prefix: Some(byte),
+                code: self.var_u32()?,
+            }),
+            _ => Ok(Opcode {
+                prefix: None,
+                code: u32::from(byte),
+            }),
+        }
+    }
+}
+
+/// The inverse of [`Encode`](crate::binary::Encode), for types which can be
+/// read from the binary format.
+pub(crate) trait Decode<'a>: Sized {
+    fn decode(r: &mut Reader<'a>) -> Result<Self>;
+}
+
+/// Checks the structure of the binary module `bytes`.
+///
+/// This checks the header, that sections have known ids, appear in the right
+/// order, and have sizes matching their contents, and that integers are
+/// encoded correctly. Function bodies aren't decoded beyond their locals.
+pub fn check(bytes: &[u8]) -> Result<()> {
+    let mut decoder = Decoder::new(true);
+    sections(bytes, |id, s| decoder.section(id, s))
+}
+
+/// Decodes the binary module `bytes` into a [`Module`] made of text fields.
+pub fn decode(bytes: &[u8]) -> Result<Module<'_>> {
+    let mut decoder = Decoder::new(false);
+    sections(bytes, |id, s| decoder.section(id, s))?;
+    decoder.finish()
+}
+
+/// Checks the header of the binary module `bytes` and then calls `f` with the
+/// id and contents of each of its sections.
+///
+/// This checks that sections have known ids and appear in the right order,
+/// that `f` reads all of each non-custom section, and that the function and
+/// data counts of the sections which have them agree.
+pub fn sections<'a>(
+    bytes: &'a [u8],
+    mut f: impl FnMut(u8, &mut Reader<'a>) -> Result<()>,
+) -> Result<()> {
+    let mut r = Reader::new(bytes);
+    if r.bytes(4).ok() != Some(b"\0asm") {
+        return Err(r.error_at(0, "magic header not detected"));
+    }
+    if r.bytes(4)? != [1, 0, 0, 0] {
+        return Err(r.error_at(4, "unknown binary version"));
+    }
+
+    let mut last = 0;
+    let mut funcs = None;
+    let mut code = None;
+    let mut data_count = None;
+    let mut data = None;
+    while !r.eof() {
+        let start = r.pos();
+        let id = r.u8()?;
+        let len = r.var_u32()? as usize;
+        let mut section = r.sub(len)?;
+        if id == 0 {
+            f(id, &mut section)?;
+            continue;
+        }
+
+        // The data count section comes between the element and code sections.
+        let order = match id {
+            1..=9 => id,
+            12 => 10,
+            10 | 11 => id + 1,
+            _ => return Err(r.error_at(start, "malformed section id")),
+        };
+        if order <= last {
+            return Err(r.error_at(start, "unexpected content after last section"));
+        }
+        last = order;
+
+        // These sections all start with a count, which is compared with the
+        // others once every section has been read.
+        let count = section.clone().var_u32();
+        match id {
+            3 => funcs = Some(count?),
+            10 => code = Some(count?),
+            11 => data = Some(count?),
+            12 => data_count = Some(count?),
+            _ => {}
+        }
+        f(id, &mut section)?;
+        if !section.eof() {
+            return Err(section.error("section size mismatch"));
+        }
+    }
+
+    if funcs.unwrap_or(0) != code.unwrap_or(0) {
+        return Err(r.error("function and code section have inconsistent lengths"));
+    }
+    if let Some(count) = data_count {
+        if count != data.unwrap_or(0) {
+            return Err(r.error("data count and data section have inconsistent lengths"));
+        }
+    }
+    Ok(())
+}
+
+/// Builds up a [`Module`] from the sections of a binary module.
+struct Decoder<'a> {
+    /// Whether the module is only being checked, in which case function bodies
+    /// aren't decoded beyond their locals and items which the AST can't
+    /// represent are allowed.
+    check_only: bool,
+    types: Vec<FunctionType<'a>>,
+    imports: Vec<Import<'a>>,
+    imported_funcs: usize,
+    /// The type index of each function in the function section.
+    func_types: Vec<u32>,
+    funcs: Vec<Func<'a>>,
+    fields: Vec<ModuleField<'a>>,
+    names: Option<Reader<'a>>,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(check_only: bool) -> Decoder<'a> {
+        Decoder {
+            check_only,
+            types: Vec::new(),
+            imports: Vec::new(),
+            imported_funcs: 0,
+            func_types: Vec::new(),
+            funcs: Vec::new(),
+            fields: Vec::new(),
+            names: None,
+        }
+    }
+
+    fn section(&mut self, id: u8, s: &mut Reader<'a>) -> Result<()> {
+        match id {
+            0 => {
+                // The `name` section is the only custom section with a place
+                // in the AST, and it's applied once all functions are known.
+                if s.name()? == "name" {
+                    self.names = Some(s.clone());
+                }
+            }
+            1 => self.types = s.vec(func_type)?,
+            2 => {
+                self.imports = s.vec(|s| self.import(s))?;
+                for import in self.imports.iter() {
+                    if let ImportKind::Func(_) = import.kind {
+                        self.imported_funcs += 1;
+                    }
+                }
+            }
+            3 => self.func_types = s.vec(Reader::var_u32)?,
+            4 => {
+                let tables = s.vec(|s| {
+                    Ok(ModuleField::Table(Table {
+                        span: Span { offset: s.pos() },
+                        name: None,
+                        exports: no_exports(),
+                        kind: TableKind::Normal(table_type(s)?),
+                    }))
+                })?;
+                self.fields.extend(tables);
+            }
+            5 => {
+                let memories = s.vec(|s| {
+                    Ok(ModuleField::Memory(Memory {
+                        span: Span { offset: s.pos() },
+                        name: None,
+                        exports: no_exports(),
+                        kind: MemoryKind::Normal(memory_type(s)?),
+                    }))
+                })?;
+                self.fields.extend(memories);
+            }
+            6 => {
+                let globals = s.vec(|s| {
+                    Ok(ModuleField::Global(Global {
+                        span: Span { offset: s.pos() },
+                        name: None,
+                        exports: no_exports(),
+                        ty: global_type(s)?,
+                        kind: GlobalKind::Inline(Expression::decode(s)?),
+                    }))
+                })?;
+                self.fields.extend(globals);
+            }
+            7 => {
+                let exports = s.vec(export)?;
+                self.fields
+                    .extend(exports.into_iter().map(ModuleField::Export));
+            }
+            8 => {
+                let start = Index::decode(s)?;
+                self.fields.push(ModuleField::Start(start));
+            }
+            9 => {
+                let elems = s.vec(|s| self.elem(s))?;
+                let elems = elems.into_iter().flatten();
+                self.fields.extend(elems.map(ModuleField::Elem));
+            }
+            10 => {
+                let mut index = 0;
+                self.funcs = s.vec(|s| {
+                    index += 1;
+                    self.func(index - 1, s)
+                })?;
+            }
+            11 => {
+                let data = s.vec(data_segment)?;
+                self.fields.extend(data.into_iter().map(ModuleField::Data));
+            }
+            // The data count is implied by the data section, and it's emitted
+            // again if needed when the module is encoded.
+            12 => {
+                s.var_u32()?;
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    fn import(&self, r: &mut Reader<'a>) -> Result<Import<'a>> {
+        let start = r.pos();
+        let module = r.name()?;
+        let name = r.name()?;
+        let kind = match r.u8()? {
+            0x00 => ImportKind::Func(self.type_use(r.var_u32()?)),
+            0x01 => ImportKind::Table(table_type(r)?),
+            0x02 => ImportKind::Memory(memory_type(r)?),
+            0x03 => ImportKind::Global(global_type(r)?),
+            _ => return Err(r.error_at(r.pos() - 1, "malformed import kind")),
+        };
+        Ok(Import {
+            span: Span { offset: start },
+            module: Cow::Borrowed(module),
+            name: Cow::Borrowed(name),
+            id: None,
+            kind,
+        })
+    }
+
+    /// Returns a use of the type at `index`, with the type also listed inline
+    /// so that names can be given to its parameters.
+    fn type_use(&self, index: u32) -> TypeUse<'a> {
+        TypeUse {
+            index_span: None,
+            index: Some(Index::Num(index)),
+            ty: match self.types.get(index as usize) {
+                Some(ty) => ty.clone(),
+                None => empty_func_type(),
+            },
+        }
+    }
+
+    fn elem(&self, r: &mut Reader<'a>) -> Result<Option<Elem<'a>>> {
+        let start = r.pos();
+        let (flags, elem) = elem_segment(r)?;
+        // Declarative segments, which are passive segments with a table, have
+        // no equivalent in the AST.
+        if flags & 0b011 == 0b011 {
+            if self.check_only {
+                return Ok(None);
+            }
+            return Err(r.error_at(start, "declarative element segments are not supported"));
+        }
+        Ok(Some(elem))
+    }
+
+    fn func(&self, index: usize, r: &mut Reader<'a>) -> Result<Func<'a>> {
+        let len = r.var_u32()? as usize;
+        let start = r.pos();
+        let mut body = r.sub(len)?;
+
+        let mut locals = Vec::new();
+        let mut total = 0u64;
+        for (count, ty) in body.vec(|body| Ok((body.var_u32()?, valtype(body)?)))? {
+            total += u64::from(count);
+            if total > MAX_LOCALS {
+                return Err(body.error_at(start, "too many locals"));
+            }
+            locals.extend((0..count).map(|_| (None, ty)));
+        }
+
+        let expression = if self.check_only {
+            if len == 0 || r.bytes[r.pos() - 1] != 0x0b {
+                return Err(r.error("unexpected end of section or function"));
+            }
+            Expression { instrs: Vec::new() }
+        } else {
+            let expression = Expression::decode(&mut body)?;
+            if !body.eof() {
+                return Err(body.error("function body size mismatch"));
+            }
+            expression
+        };
+
+        // A missing type is reported once all sections have been read, since
+        // the function section is then known to be too short.
+        let ty = self.func_types.get(index).cloned().unwrap_or(0);
+        Ok(Func {
+            span: Span { offset: start },
+            name: None,
+            exports: no_exports(),
+            kind: FuncKind::Inline { locals, expression },
+            ty: self.type_use(ty),
+        })
+    }
+
+    fn finish(mut self) -> Result<Module<'a>> {
+        let name = match self.names.take() {
+            Some(names) => self.names(names)?,
+            None => None,
+        };
+
+        let mut fields = Vec::new();
+        for func in self.types {
+            fields.push(ModuleField::Type(Type { name: None, func }));
+        }
+        fields.extend(self.imports.into_iter().map(ModuleField::Import));
+        fields.extend(self.funcs.into_iter().map(ModuleField::Func));
+        fields.extend(self.fields);
+        Ok(Module {
+            span: Span { offset: 0 },
+            name,
+            kind: ModuleKind::Text(fields),
+        })
+    }
+
+    /// Gives identifiers to functions and locals from the `name` section `r`,
+    /// returning the identifier of the module itself.
+    ///
+    /// Names which are empty or which repeat an earlier name in the same
+    /// namespace are skipped, since they couldn't be resolved. Unknown
+    /// subsections are ignored.
+    fn names(&mut self, mut r: Reader<'a>) -> Result<Option<Id<'a>>> {
+        let mut module = None;
+        let mut seen = HashSet::new();
+        while !r.eof() {
+            let id = r.u8()?;
+            let len = r.var_u32()? as usize;
+            let mut sub = r.sub(len)?;
+            match id {
+                0 => module = Some(id_name(&mut sub)?),
+                1 => {
+                    for (index, name) in sub.vec(name_assoc)? {
+                        if let Some(slot) = self.func_name(index) {
+                            set_name(slot, name, &mut seen);
+                        }
+                    }
+                }
+                2 => {
+                    let names = sub.vec(|s| Ok((s.var_u32()?, s.vec(name_assoc)?)))?;
+                    for (func, locals) in names {
+                        self.local_names(func, locals);
+                    }
+                }
+                _ => continue,
+            }
+            if !sub.eof() {
+                return Err(sub.error("section size mismatch"));
+            }
+        }
+        Ok(module.filter(|id| !id.name().is_empty()))
+    }
+
+    /// Returns the identifier of the function at `index` in the function index
+    /// space, which starts with imported functions.
+    fn func_name(&mut self, index: u32) -> Option<&mut Option<Id<'a>>> {
+        let index = index as usize;
+        if index >= self.imported_funcs {
+            let func = self.funcs.get_mut(index - self.imported_funcs)?;
+            return Some(&mut func.name);
+        }
+        let mut funcs = self.imports.iter_mut().filter_map(|import| {
+            if let ImportKind::Func(_) = import.kind {
+                Some(&mut import.id)
+            } else {
+                None
+            }
+        });
+        funcs.nth(index)
+    }
+
+    fn local_names(&mut self, func: u32, names: Vec<(u32, Id<'a>)>) {
+        let index = match (func as usize).checked_sub(self.imported_funcs) {
+            Some(index) => index,
+            None => return,
+        };
+        let func = match self.funcs.get_mut(index) {
+            Some(func) => func,
+            None => return,
+        };
+        let params = &mut func.ty.ty.params;
+        let locals = match &mut func.kind {
+            FuncKind::Inline { locals, .. } => locals,
+            FuncKind::Import { .. } => return,
+        };
+        let mut seen = HashSet::new();
+        for (index, name) in names {
+            let index = index as usize;
+            let slot = if index < params.len() {
+                &mut params[index].0
+            } else {
+                match locals.get_mut(index - params.len()) {
+                    Some(local) => &mut local.0,
+                    None => continue,
+                }
+            };
+            set_name(slot, name, &mut seen);
+        }
+    }
+}
+
+fn name_assoc<'a>(r: &mut Reader<'a>) -> Result<(u32, Id<'a>)> {
+    Ok((r.var_u32()?, id_name(r)?))
+}
+
+fn id_name<'a>(r: &mut Reader<'a>) -> Result<Id<'a>> {
+    let offset = r.pos();
+    Ok(Id::new(r.name()?, Span { offset }))
+}
+
+fn set_name<'a>(slot: &mut Option<Id<'a>>, name: Id<'a>, seen: &mut HashSet<String>) {
+    if slot.is_none() && !name.name().is_empty() && seen.insert(name.name().to_string()) {
+        *slot = Some(name);
+    }
+}
+
+fn no_exports<'a>() -> InlineExport<'a> {
+    InlineExport { names: Vec::new() }
+}
+
+fn empty_func_type<'a>() -> FunctionType<'a> {
+    FunctionType {
+        params: Vec::new(),
+        results: Vec::new(),
+    }
+}
+
+pub fn valtype(r: &mut Reader<'_>) -> Result<ValType> {
+    match r.u8()? {
+        0x7f => Ok(ValType::I32),
+        0x7e => Ok(ValType::I64),
+        0x7d => Ok(ValType::F32),
+        0x7c => Ok(ValType::F64),
+        0x7b => Ok(ValType::V128),
+        0x70 => Ok(ValType::Funcref),
+        0x6f => Ok(ValType::Anyref),
+        0x6e => Ok(ValType::Nullref),
+        _ => Err(r.error_at(r.pos() - 1, "malformed value type")),
+    }
+}
+
+pub fn ref_type(r: &mut Reader<'_>) -> Result<TableElemType> {
+    match r.u8()? {
+        0x70 => Ok(TableElemType::Funcref),
+        0x6f => Ok(TableElemType::Anyref),
+        0x6e => Ok(TableElemType::Nullref),
+        _ => Err(r.error_at(r.pos() - 1, "malformed reference type")),
+    }
+}
+
+pub fn func_type<'a>(r: &mut Reader<'a>) -> Result<FunctionType<'a>> {
+    if r.u8()? != 0x60 {
+        return Err(r.error_at(r.pos() - 1, "malformed function type"));
+    }
+    Ok(FunctionType {
+        params: r.vec(|r| Ok((None, valtype(r)?)))?,
+        results: r.vec(valtype)?,
+    })
+}
+
+/// Reads the limits of a table or memory type, after `flags` has been read.
+fn limits(r: &mut Reader<'_>, flags: u8) -> Result<Limits> {
+    let min = r.var_u32()?;
+    let max = if flags & 0x01 != 0 {
+        Some(r.var_u32()?)
+    } else {
+        None
+    };
+    Ok(Limits { min, max })
+}
+
+pub fn table_type(r: &mut Reader<'_>) -> Result<TableType> {
+    let elem = ref_type(r)?;
+    let limits = match r.u8()? {
+        flags @ 0x00..=0x01 => limits(r, flags)?,
+        _ => return Err(r.error_at(r.pos() - 1, "integer too large")),
+    };
+    Ok(TableType { limits, elem })
+}
+
+pub fn memory_type(r: &mut Reader<'_>) -> Result<MemoryType> {
+    let (limits, shared) = match r.u8()? {
+        flags @ 0x00..=0x01 => (limits(r, flags)?, false),
+        flags @ 0x03 => (limits(r, flags)?, true),
+        _ => return Err(r.error_at(r.pos() - 1, "integer too large")),
+    };
+    Ok(MemoryType { limits, shared })
+}
+
+pub fn global_type(r: &mut Reader<'_>) -> Result<GlobalType> {
+    let ty = valtype(r)?;
+    let mutable = match r.u8()? {
+        0x00 => false,
+        0x01 => true,
+        _ => return Err(r.error_at(r.pos() - 1, "malformed mutability")),
+    };
+    Ok(GlobalType { ty, mutable })
+}
+
+pub fn export<'a>(r: &mut Reader<'a>) -> Result<Export<'a>> {
+    let start = r.pos();
+    let name = r.name()?;
+    let kind = match r.u8()? {
+        0x00 => ExportKind::Func(Index::decode(r)?),
+        0x01 => ExportKind::Table(Index::decode(r)?),
+        0x02 => ExportKind::Memory(Index::decode(r)?),
+        0x03 => ExportKind::Global(Index::decode(r)?),
+        _ => return Err(r.error_at(r.pos() - 1, "malformed export kind")),
+    };
+    Ok(Export {
+        span: Span { offset: start },
+        name: Cow::Borrowed(name),
+        kind,
+    })
+}
+
+/// Reads an element segment, returning its flags along with it.
+pub fn elem_segment<'a>(r: &mut Reader<'a>) -> Result<(u32, Elem<'a>)> {
+    let start = r.pos();
+    let flags = r.var_u32()?;
+    if flags > 7 {
+        return Err(r.error_at(start, "malformed elements segment kind"));
+    }
+    let passive = flags & 0b001 != 0;
+    let explicit_table = flags & 0b010 != 0;
+    let exprs = flags & 0b100 != 0;
+
+    let kind = if passive {
+        ElemKind::Passive
+    } else {
+        let table = if explicit_table { r.var_u32()? } else { 0 };
+        ElemKind::Active {
+            table: Index::Num(table),
+            offset: Expression::decode(r)?,
+        }
+    };
+    let mut ty = TableElemType::Funcref;
+    if passive || explicit_table {
+        if exprs {
+            ty = ref_type(r)?;
+        } else {
+            r.reserved(0x00)?;
+        }
+    }
+    let payload = if exprs {
+        ElemPayload::Exprs {
+            ty,
+            exprs: r.vec(elem_expr)?,
+        }
+    } else {
+        ElemPayload::Indices(r.vec(Index::decode)?)
+    };
+    let elem = Elem {
+        span: Span { offset: start },
+        name: None,
+        kind,
+        payload,
+    };
+    Ok((flags, elem))
+}
+
+/// Reads one expression of an element segment, which is either `ref.func` or
+/// `ref.null`.
+fn elem_expr<'a>(r: &mut Reader<'a>) -> Result<Option<Index<'a>>> {
+    let start = r.pos();
+    let index = match Instruction::decode(r)? {
+        Instruction::RefFunc(index) => Some(index),
+        Instruction::RefNull => None,
+        _ => return Err(r.error_at(start, "malformed element expression")),
+    };
+    r.reserved(0x0b)?;
+    Ok(index)
+}
+
+pub fn data_segment<'a>(r: &mut Reader<'a>) -> Result<Data<'a>> {
+    let start = r.pos();
+    let kind = match r.var_u32()? {
+        0x00 => DataKind::Active {
+            memory: Index::Num(0),
+            offset: Expression::decode(r)?,
+        },
+        0x01 => DataKind::Passive,
+        0x02 => DataKind::Active {
+            memory: Index::decode(r)?,
+            offset: Expression::decode(r)?,
+        },
+        _ => return Err(r.error_at(start, "malformed data segment kind")),
+    };
+    let len = r.var_u32()? as usize;
+    Ok(Data {
+        span: Span { offset: start },
+        name: None,
+        kind,
+        data: vec![Cow::Borrowed(r.bytes(len)?)],
+    })
+}
+
+impl<'a> Decode<'a> for Expression<'a> {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        // Read up to and including the `end` which finishes the expression,
+        // which like in the text format isn't included in `instrs`.
+        let mut instrs = Vec::new();
+        let mut depth = 0u32;
+        loop {
+            let instr = Instruction::decode(r)?;
+            match instr {
+                Instruction::Block(_) | Instruction::Loop(_) | Instruction::If(_) => depth += 1,
+                Instruction::End(_) if depth == 0 => return Ok(Expression { instrs }),
+                Instruction::End(_) => depth -= 1,
+                _ => {}
+            }
+            instrs.push(instr);
+        }
+    }
+}
+
+impl<'a> Decode<'a> for Index<'a> {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        Ok(Index::Num(r.var_u32()?))
+    }
+}
+
+impl<'a> Decode<'a> for Option<Id<'a>> {
+    fn decode(_r: &mut Reader<'a>) -> Result<Self> {
+        // labels on `else` and `end` only exist in the text format
+        Ok(None)
+    }
+}
+
+impl<'a> Decode<'a> for ValType {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        valtype(r)
+    }
+}
+
+impl<'a> Decode<'a> for BlockType<'a> {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        let mut ty = TypeUse {
+            index_span: None,
+            index: None,
+            ty: empty_func_type(),
+        };
+        match r.peek_u8()? {
+            0x40 => {
+                r.u8()?;
+            }
+            0x7b..=0x7f | 0x6e..=0x70 => ty.ty.results.push(valtype(r)?),
+            _ => {
+                let start = r.pos();
+                let index = r.var_s33()?;
+                if index < 0 {
+                    return Err(r.error_at(start, "malformed block type"));
+                }
+                ty.index = Some(Index::Num(index as u32));
+            }
+        }
+        Ok(BlockType { label: None, ty })
+    }
+}
+
+impl<'a> Decode<'a> for BrTableIndices<'a> {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        Ok(BrTableIndices {
+            labels: r.vec(Index::decode)?,
+            default: Index::decode(r)?,
+        })
+    }
+}
+
+impl<'a> Decode<'a> for CallIndirect<'a> {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        let ty = TypeUse {
+            index_span: None,
+            index: Some(Index::decode(r)?),
+            ty: empty_func_type(),
+        };
+        Ok(CallIndirect {
+            table: Index::decode(r)?,
+            ty,
+        })
+    }
+}
+
+impl<'a> Decode<'a> for SelectTypes {
+    fn decode(_r: &mut Reader<'a>) -> Result<Self> {
+        // `select` with result types has its own opcode, which is decoded
+        // separately
+        Ok(SelectTypes { tys: Vec::new() })
+    }
+}
+
+impl<'a> Decode<'a> for MemArg {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        let start = r.pos();
+        let align = r.var_u32()?;
+        if align >= 32 {
+            return Err(r.error_at(start, "malformed memop flags"));
+        }
+        Ok(MemArg {
+            align: 1 << align,
+            offset: r.var_u32()?,
+        })
+    }
+}
+
+impl<'a> Decode<'a> for MemoryInit<'a> {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        // see the `Encode` impl for why the reserved byte comes last
+        let data = Index::decode(r)?;
+        r.reserved(0x00)?;
+        Ok(MemoryInit { data })
+    }
+}
+
+impl<'a> Decode<'a> for TableInit<'a> {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        let elem = Index::decode(r)?;
+        r.reserved(0x00)?;
+        Ok(TableInit { elem })
+    }
+}
+
+impl<'a> Decode<'a> for u32 {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        r.var_u32()
+    }
+}
+
+impl<'a> Decode<'a> for i32 {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        r.var_i32()
+    }
+}
+
+impl<'a> Decode<'a> for i64 {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        r.var_i64()
+    }
+}
+
+impl<'a> Decode<'a> for Float32 {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        let mut bits = [0; 4];
+        bits.copy_from_slice(r.bytes(4)?);
+        Ok(Float32 {
+            bits: u32::from_le_bytes(bits),
+        })
+    }
+}
+
+impl<'a> Decode<'a> for Float64 {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        let mut bits = [0; 8];
+        bits.copy_from_slice(r.bytes(8)?);
+        Ok(Float64 {
+            bits: u64::from_le_bytes(bits),
+        })
+    }
+}
+
+impl<'a> Decode<'a> for V128Const {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        let bytes = r.bytes(16)?;
+        let mut lanes = [0; 4];
+        for (lane, chunk) in lanes.iter_mut().zip(bytes.chunks(4)) {
+            *lane = i32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        Ok(V128Const::I32x4(lanes))
+    }
+}
+
+impl<'a> Decode<'a> for V8x16Shuffle {
+    fn decode(r: &mut Reader<'a>) -> Result<Self> {
+        let mut lanes = [0; 16];
+        lanes.copy_from_slice(r.bytes(16)?);
+        Ok(V8x16Shuffle { lanes })
+    }
+}
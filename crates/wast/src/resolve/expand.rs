@@ -49,12 +49,13 @@ impl<'a> Expander<'a> {
     pub fn deinline_import(&mut self, item: &mut ModuleField<'a>) {
         match item {
             ModuleField::Func(f) => {
-                let (module, name) = match f.kind {
-                    FuncKind::Import { module, name } => (module, name),
+                let (module, name) = match &f.kind {
+                    FuncKind::Import { module, name } => (module.clone(), name.clone()),
                     _ => return,
                 };
-                for name in f.exports.names.drain(..) {
+                for (span, name) in f.exports.names.drain(..) {
                     self.to_append.push(ModuleField::Export(Export {
+                        span,
                         name,
                         kind: ExportKind::Func(Index::Num(self.funcs)),
                     }));
@@ -63,19 +64,20 @@ impl<'a> Expander<'a> {
                     span: f.span,
                     module,
                     name,
-                    id: f.name,
+                    id: f.name.clone(),
                     kind: ImportKind::Func(f.ty.clone()),
                 });
                 self.funcs += 1;
             }
 
             ModuleField::Memory(m) => {
-                let (module, name, ty) = match m.kind {
-                    MemoryKind::Import { module, name, ty } => (module, name, ty),
+                let (module, name, ty) = match &m.kind {
+                    MemoryKind::Import { module, name, ty } => (module.clone(), name.clone(), *ty),
                     _ => return,
                 };
-                for name in m.exports.names.drain(..) {
+                for (span, name) in m.exports.names.drain(..) {
                     self.to_append.push(ModuleField::Export(Export {
+                        span,
                         name,
                         kind: ExportKind::Memory(Index::Num(self.memories)),
                     }));
@@ -84,19 +86,20 @@ impl<'a> Expander<'a> {
                     span: m.span,
                     module,
                     name,
-                    id: m.name,
+                    id: m.name.clone(),
                     kind: ImportKind::Memory(ty),
                 });
                 self.memories += 1;
             }
 
             ModuleField::Table(t) => {
-                let (module, name, ty) = match t.kind {
-                    TableKind::Import { module, name, ty } => (module, name, ty),
+                let (module, name, ty) = match &t.kind {
+                    TableKind::Import { module, name, ty } => (module.clone(), name.clone(), *ty),
                     _ => return,
                 };
-                for name in t.exports.names.drain(..) {
+                for (span, name) in t.exports.names.drain(..) {
                     self.to_append.push(ModuleField::Export(Export {
+                        span,
                         name,
                         kind: ExportKind::Table(Index::Num(self.tables)),
                     }));
@@ -105,19 +108,20 @@ impl<'a> Expander<'a> {
                     span: t.span,
                     module,
                     name,
-                    id: t.name,
+                    id: t.name.clone(),
                     kind: ImportKind::Table(ty),
                 });
                 self.tables += 1;
             }
 
             ModuleField::Global(g) => {
-                let (module, name) = match g.kind {
-                    GlobalKind::Import { module, name } => (module, name),
+                let (module, name) = match &g.kind {
+                    GlobalKind::Import { module, name } => (module.clone(), name.clone()),
                     _ => return,
                 };
-                for name in g.exports.names.drain(..) {
+                for (span, name) in g.exports.names.drain(..) {
                     self.to_append.push(ModuleField::Export(Export {
+                        span,
                         name,
                         kind: ExportKind::Global(Index::Num(self.globals)),
                     }));
@@ -126,7 +130,7 @@ impl<'a> Expander<'a> {
                     span: g.span,
                     module,
                     name,
-                    id: g.name,
+                    id: g.name.clone(),
                     kind: ImportKind::Global(g.ty),
                 });
                 self.globals += 1;
@@ -151,8 +155,9 @@ impl<'a> Expander<'a> {
     pub fn deinline_export(&mut self, item: &mut ModuleField<'a>) {
         match item {
             ModuleField::Func(f) => {
-                for name in f.exports.names.drain(..) {
+                for (span, name) in f.exports.names.drain(..) {
                     self.to_append.push(ModuleField::Export(Export {
+                        span,
                         name,
                         kind: ExportKind::Func(Index::Num(self.funcs)),
                     }));
@@ -161,8 +166,9 @@ impl<'a> Expander<'a> {
             }
 
             ModuleField::Memory(m) => {
-                for name in m.exports.names.drain(..) {
+                for (span, name) in m.exports.names.drain(..) {
                     self.to_append.push(ModuleField::Export(Export {
+                        span,
                         name,
                         kind: ExportKind::Memory(Index::Num(self.memories)),
                     }));
@@ -201,8 +207,9 @@ impl<'a> Expander<'a> {
             }
 
             ModuleField::Table(t) => {
-                for name in t.exports.names.drain(..) {
+                for (span, name) in t.exports.names.drain(..) {
                     self.to_append.push(ModuleField::Export(Export {
+                        span,
                         name,
                         kind: ExportKind::Table(Index::Num(self.tables)),
                     }));
@@ -243,8 +250,9 @@ impl<'a> Expander<'a> {
             }
 
             ModuleField::Global(g) => {
-                for name in g.exports.names.drain(..) {
+                for (span, name) in g.exports.names.drain(..) {
                     self.to_append.push(ModuleField::Export(Export {
+                        span,
                         name,
                         kind: ExportKind::Global(Index::Num(self.globals)),
                     }));
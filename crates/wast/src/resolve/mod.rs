@@ -1,11 +1,22 @@
 use crate::ast::*;
+use crate::visit::VisitMut;
 use crate::Error;
+use std::collections::HashMap;
 
 mod expand;
 mod names;
 mod tyexpand;
 
-pub fn resolve<'a>(module: &mut Module<'a>) -> Result<Names<'a>, Error> {
+pub use self::tyexpand::key as type_key;
+
+/// Resolves every name in `module` to an index, expanding inline imports,
+/// exports, and type uses along the way.
+///
+/// Passes over a resolved module rely on its layout: every [`Index`] is
+/// numeric, and imports come before all other fields, including the types
+/// their signatures use, so a pass which needs the types of imports has to
+/// collect types before it visits them.
+pub fn resolve<'a>(module: &mut Module<'a>, options: &ResolveOptions) -> Result<Names<'a>, Error> {
     let fields = match &mut module.kind {
         ModuleKind::Text(fields) => fields,
         _ => return Ok(Default::default()),
@@ -44,6 +55,24 @@ pub fn resolve<'a>(module: &mut Module<'a>) -> Result<Names<'a>, Error> {
         return Err(Error::new(span, format!("import after {}", name)));
     }
 
+    // Export names must be unique across all kinds of exports, which at this
+    // point are all explicit `Export` fields.
+    let mut exports = HashMap::new();
+    for field in fields.iter() {
+        let export = match field {
+            ModuleField::Export(e) => e,
+            _ => continue,
+        };
+        if let Some(prev) = exports.insert(&export.name, export.span) {
+            let mut err = Error::new(
+                export.span,
+                format!("duplicate export name `{}`", export.name),
+            );
+            err.add_label(prev, "previous export here".to_string());
+            return Err(err);
+        }
+    }
+
     // For the second pass we resolve all inline type annotations. This will, in
     // the order that we see them, append to the list of types. Note that types
     // are indexed so we're careful to always insert new types just before the
@@ -55,7 +84,7 @@ pub fn resolve<'a>(module: &mut Module<'a>) -> Result<Names<'a>, Error> {
     let mut expander = tyexpand::Expander::default();
     move_types_first(fields);
     while cur < fields.len() {
-        expander.expand(&mut fields[cur]);
+        expander.visit_module_field(&mut fields[cur]);
         for new in expander.to_prepend.drain(..) {
             fields.insert(cur, new);
             cur += 1;
@@ -69,9 +98,9 @@ pub fn resolve<'a>(module: &mut Module<'a>) -> Result<Names<'a>, Error> {
     // For this operation we do need to make sure that imports are sorted first
     // because otherwise we'll be calculating indices in the wrong order.
     move_imports_first(fields);
-    let mut resolver = names::Resolver::default();
+    let mut resolver = names::Resolver::new(options);
     for field in fields.iter_mut() {
-        resolver.register(field);
+        resolver.register(field)?;
     }
     for field in fields.iter_mut() {
         resolver.resolve(field)?;
@@ -79,6 +108,15 @@ pub fn resolve<'a>(module: &mut Module<'a>) -> Result<Names<'a>, Error> {
     Ok(Names { resolver })
 }
 
+/// Returns the number of an index which is known to be numeric, as every
+/// index in a resolved or decoded module is.
+pub(crate) fn num(index: &Index<'_>) -> u32 {
+    match index {
+        Index::Num(n) => *n,
+        Index::Id(_) => unreachable!("resolved modules only have numeric indices"),
+    }
+}
+
 fn move_imports_first(fields: &mut [ModuleField<'_>]) {
     fields.sort_by_key(|f| match f {
         ModuleField::Import(_) => false,
@@ -93,6 +131,34 @@ fn move_types_first(fields: &mut [ModuleField<'_>]) {
     });
 }
 
+/// Options to configure name resolution of a module.
+///
+/// This is passed to [`Module::resolve_with`](crate::Module::resolve_with),
+/// and the default set of options is used by
+/// [`Module::resolve`](crate::Module::resolve).
+#[derive(Clone, Debug, Default)]
+pub struct ResolveOptions {
+    reject_label_shadowing: bool,
+}
+
+impl ResolveOptions {
+    /// Creates a new set of default options.
+    pub fn new() -> ResolveOptions {
+        ResolveOptions::default()
+    }
+
+    /// Configures whether a block label which reuses the name of a label of
+    /// an enclosing block is rejected as a duplicate identifier.
+    ///
+    /// The WebAssembly specification allows this, with branches referring to
+    /// the innermost label of that name, so it's allowed by default. It's
+    /// usually a mistake though, and this can be enabled to catch it.
+    pub fn reject_label_shadowing(&mut self, reject: bool) -> &mut ResolveOptions {
+        self.reject_label_shadowing = reject;
+        self
+    }
+}
+
 /// Representation of the results of name resolution for a module.
 ///
 /// This structure is returned from the
@@ -1,4 +1,6 @@
 use crate::ast::*;
+use crate::resolve::ResolveOptions;
+use crate::visit::{IndexSpace, VisitMut};
 use crate::Error;
 use std::collections::HashMap;
 
@@ -31,6 +33,7 @@ impl Ns {
 pub struct Resolver<'a> {
     ns: [Namespace<'a>; 7],
     tys: Vec<Type<'a>>,
+    options: ResolveOptions,
 }
 
 struct Type<'a> {
@@ -45,33 +48,39 @@ struct Namespace<'a> {
 }
 
 impl<'a> Resolver<'a> {
-    pub fn register(&mut self, item: &ModuleField<'a>) {
-        let mut register = |ns: Ns, name: Option<Id<'a>>| {
-            self.ns_mut(ns).register(name);
-        };
+    pub fn new(options: &ResolveOptions) -> Resolver<'a> {
+        Resolver {
+            options: options.clone(),
+            ..Resolver::default()
+        }
+    }
+
+    pub fn register(&mut self, item: &ModuleField<'a>) -> Result<(), Error> {
+        let mut register = |ns: Ns, name: Option<Id<'a>>| self.ns_mut(ns).register(name, ns.desc());
         match item {
             ModuleField::Import(i) => match i.kind {
-                ImportKind::Func(_) => register(Ns::Func, i.id),
-                ImportKind::Memory(_) => register(Ns::Memory, i.id),
-                ImportKind::Table(_) => register(Ns::Table, i.id),
-                ImportKind::Global(_) => register(Ns::Global, i.id),
+                ImportKind::Func(_) => register(Ns::Func, i.id.clone())?,
+                ImportKind::Memory(_) => register(Ns::Memory, i.id.clone())?,
+                ImportKind::Table(_) => register(Ns::Table, i.id.clone())?,
+                ImportKind::Global(_) => register(Ns::Global, i.id.clone())?,
             },
-            ModuleField::Global(i) => register(Ns::Global, i.name),
-            ModuleField::Memory(i) => register(Ns::Memory, i.name),
-            ModuleField::Func(i) => register(Ns::Func, i.name),
-            ModuleField::Table(i) => register(Ns::Table, i.name),
+            ModuleField::Global(i) => register(Ns::Global, i.name.clone())?,
+            ModuleField::Memory(i) => register(Ns::Memory, i.name.clone())?,
+            ModuleField::Func(i) => register(Ns::Func, i.name.clone())?,
+            ModuleField::Table(i) => register(Ns::Table, i.name.clone())?,
             ModuleField::Type(i) => {
-                register(Ns::Type, i.name);
+                register(Ns::Type, i.name.clone())?;
                 self.tys.push(Type {
                     params: i.func.params.clone(),
                     results: i.func.results.clone(),
                 });
             }
-            ModuleField::Elem(e) => register(Ns::Elem, e.name),
-            ModuleField::Data(d) => register(Ns::Data, d.name),
+            ModuleField::Elem(e) => register(Ns::Elem, e.name.clone())?,
+            ModuleField::Data(d) => register(Ns::Data, d.name.clone())?,
             ModuleField::Start(_) => {}
             ModuleField::Export(_) => {}
         }
+        Ok(())
     }
 
     fn ns_mut(&mut self, ns: Ns) -> &mut Namespace<'a> {
@@ -83,88 +92,11 @@ impl<'a> Resolver<'a> {
     }
 
     pub fn resolve(&self, field: &mut ModuleField<'a>) -> Result<(), Error> {
-        match field {
-            ModuleField::Import(i) => {
-                if let ImportKind::Func(f) = &mut i.kind {
-                    self.resolve_type_use(i.span, f)?;
-                }
-                Ok(())
-            }
-
-            ModuleField::Func(f) => {
-                self.resolve_type_use(f.span, &mut f.ty)?;
-                if let FuncKind::Inline { locals, expression } = &mut f.kind {
-                    let mut resolver = ExprResolver::new(self, f.span);
-
-                    // Parameters come first in the local namespace...
-                    for (name, _) in f.ty.ty.params.iter() {
-                        resolver.locals.register(*name);
-                    }
-
-                    // .. followed by locals themselves
-                    for (name, _) in locals {
-                        resolver.locals.register(*name);
-                    }
-
-                    // and then we can resolve the expression!
-                    resolver.resolve(expression)?;
-                }
-                Ok(())
-            }
-
-            ModuleField::Elem(e) => {
-                match &mut e.kind {
-                    ElemKind::Active { table, offset } => {
-                        self.resolve_idx(table, Ns::Table)?;
-                        self.resolve_expr(e.span, offset)?;
-                    }
-                    ElemKind::Passive { .. } => {}
-                }
-                match &mut e.payload {
-                    ElemPayload::Indices(elems) => {
-                        for idx in elems {
-                            self.resolve_idx(idx, Ns::Func)?;
-                        }
-                    }
-                    ElemPayload::Exprs { exprs, .. } => {
-                        for funcref in exprs {
-                            if let Some(idx) = funcref {
-                                self.resolve_idx(idx, Ns::Func)?;
-                            }
-                        }
-                    }
-                }
-                Ok(())
-            }
-
-            ModuleField::Data(d) => {
-                if let DataKind::Active { memory, offset } = &mut d.kind {
-                    self.resolve_idx(memory, Ns::Memory)?;
-                    self.resolve_expr(d.span, offset)?;
-                }
-                Ok(())
-            }
-
-            ModuleField::Start(i) => {
-                self.resolve_idx(i, Ns::Func)?;
-                Ok(())
-            }
-
-            ModuleField::Export(e) => match &mut e.kind {
-                ExportKind::Func(f) => self.resolve_idx(f, Ns::Func),
-                ExportKind::Memory(f) => self.resolve_idx(f, Ns::Memory),
-                ExportKind::Global(f) => self.resolve_idx(f, Ns::Global),
-                ExportKind::Table(f) => self.resolve_idx(f, Ns::Table),
-            },
-
-            ModuleField::Global(g) => {
-                if let GlobalKind::Inline(expr) = &mut g.kind {
-                    self.resolve_expr(g.span, expr)?;
-                }
-                Ok(())
-            }
-
-            ModuleField::Table(_) | ModuleField::Memory(_) | ModuleField::Type(_) => Ok(()),
+        let mut resolver = FieldResolver::new(self);
+        resolver.visit_module_field(field);
+        match resolver.err {
+            Some(err) => Err(err),
+            None => Ok(()),
         }
     }
 
@@ -173,7 +105,7 @@ impl<'a> Resolver<'a> {
         let idx = self
             .ns(Ns::Type)
             .resolve(ty.index.as_mut().unwrap())
-            .map_err(|id| self.resolve_error(id, "type"))?;
+            .map_err(|id| self.resolve_error(id, Ns::Type))?;
 
         // If the type was listed inline *and* it was specified via a type index
         // we need to assert they're the same.
@@ -202,31 +134,48 @@ impl<'a> Resolver<'a> {
         Ok(idx)
     }
 
-    fn resolve_expr(&self, span: Span, expr: &mut Expression<'a>) -> Result<(), Error> {
-        ExprResolver::new(self, span).resolve(expr)
-    }
-
     pub fn resolve_idx(&self, idx: &mut Index<'a>, ns: Ns) -> Result<(), Error> {
         match self.ns(ns).resolve(idx) {
             Ok(_n) => Ok(()),
-            Err(id) => Err(self.resolve_error(id, ns.desc())),
+            Err(id) => Err(self.resolve_error(id, ns)),
         }
     }
 
-    fn resolve_error(&self, id: Id<'a>, ns: &str) -> Error {
-        Error::new(
-            id.span(),
-            format!("failed to find {} named `${}`", ns, id.name()),
-        )
+    fn resolve_error(&self, id: Id<'a>, ns: Ns) -> Error {
+        not_found(&id, ns.desc(), self.ns(ns).names())
     }
 }
 
+fn duplicate(id: Id<'_>, prev: Id<'_>, desc: &str) -> Error {
+    let mut err = Error::new(
+        id.span(),
+        format!("duplicate {} identifier `${}`", desc, id.name()),
+    );
+    err.add_label(prev.span(), "previous definition here".to_string());
+    err
+}
+
+fn not_found<'b>(id: &Id<'_>, desc: &str, candidates: impl Iterator<Item = &'b str>) -> Error {
+    let mut err = Error::new(
+        id.span(),
+        format!("failed to find {} named `${}`", desc, id.name()),
+    );
+    if let Some(name) = crate::suggest::closest(id.name(), candidates) {
+        err.add_help(format!("did you mean `${}`?", name));
+    }
+    err
+}
+
 impl<'a> Namespace<'a> {
-    fn register(&mut self, name: Option<Id<'a>>) {
+    fn register(&mut self, name: Option<Id<'a>>, desc: &str) -> Result<(), Error> {
         if let Some(name) = name {
+            if let Some((prev, _)) = self.names.get_key_value(&name) {
+                return Err(duplicate(name, prev.clone(), desc));
+            }
             self.names.insert(name, self.count);
         }
         self.count += 1;
+        Ok(())
     }
 
     fn resolve(&self, idx: &mut Index<'a>) -> Result<u32, Id<'a>> {
@@ -238,164 +187,235 @@ impl<'a> Namespace<'a> {
             *idx = Index::Num(n);
             return Ok(n);
         }
-        Err(*id)
+        Err(id.clone())
+    }
+
+    fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.keys().map(|id| id.name())
     }
 }
 
-struct ExprResolver<'a, 'b> {
+struct FieldResolver<'a, 'b> {
     resolver: &'b Resolver<'a>,
     locals: Namespace<'a>,
     labels: Vec<Option<Id<'a>>>,
+    /// The field being resolved, for errors about its type uses.
     span: Span,
+    /// The first error found, after which nothing else is resolved.
+    err: Option<Error>,
 }
 
-impl<'a, 'b> ExprResolver<'a, 'b> {
-    fn new(resolver: &'b Resolver<'a>, span: Span) -> ExprResolver<'a, 'b> {
-        ExprResolver {
-            resolver,
-            locals: Default::default(),
-            labels: Vec::new(),
-            span,
-        }
+impl<'a, 'b> VisitMut<'a> for FieldResolver<'a, 'b> {
+    fn visit_import(&mut self, import: &mut Import<'a>) {
+        self.span = import.span;
+        self.walk_import(import);
     }
 
-    fn resolve(&mut self, expr: &mut Expression<'a>) -> Result<(), Error> {
-        for instr in expr.instrs.iter_mut() {
-            self.resolve_instr(instr)?;
+    fn visit_func(&mut self, func: &mut Func<'a>) {
+        self.span = func.span;
+        self.visit_type_use(&mut func.ty);
+        if self.err.is_some() {
+            return;
+        }
+        if let FuncKind::Inline { locals, expression } = &mut func.kind {
+            // Parameters come first in the local namespace, followed by
+            // locals themselves, and then we can resolve the expression!
+            for (name, _) in func.ty.ty.params.iter().chain(locals.iter()) {
+                if let Err(e) = self.locals.register(name.clone(), "local") {
+                    self.err = Some(e);
+                    return;
+                }
+            }
+            self.visit_expression(expression);
         }
-        Ok(())
     }
 
-    fn resolve_instr(&mut self, instr: &mut Instruction<'a>) -> Result<(), Error> {
-        use crate::ast::Instruction::*;
+    fn visit_global(&mut self, global: &mut Global<'a>) {
+        self.span = global.span;
+        self.walk_global(global);
+    }
 
-        match instr {
-            MemoryInit(i) => self.resolver.resolve_idx(&mut i.data, Ns::Data),
-            DataDrop(i) => self.resolver.resolve_idx(i, Ns::Data),
+    fn visit_elem(&mut self, elem: &mut Elem<'a>) {
+        self.span = elem.span;
+        self.walk_elem(elem);
+    }
 
-            TableInit(i) => self.resolver.resolve_idx(&mut i.elem, Ns::Elem),
-            ElemDrop(i) => self.resolver.resolve_idx(i, Ns::Elem),
+    fn visit_data(&mut self, data: &mut Data<'a>) {
+        self.span = data.span;
+        self.walk_data(data);
+    }
 
-            TableFill(i) | TableSet(i) | TableGet(i) | TableSize(i) | TableGrow(i) => {
-                self.resolver.resolve_idx(i, Ns::Table)
-            }
+    fn visit_type_use(&mut self, ty: &mut TypeUse<'a>) {
+        let result = self.resolver.resolve_type_use(self.span, ty).map(|_| ());
+        self.check(result);
+    }
 
-            GlobalSet(i) | GlobalGet(i) => self.resolver.resolve_idx(i, Ns::Global),
+    fn visit_instruction(&mut self, instr: &mut Instruction<'a>) {
+        if self.err.is_none() {
+            self.walk_instruction(instr);
+        }
+    }
 
-            LocalSet(i) | LocalGet(i) | LocalTee(i) => self
+    fn visit_index(&mut self, index: &mut Index<'a>, space: IndexSpace) {
+        if self.err.is_some() {
+            return;
+        }
+        let result = match space {
+            IndexSpace::Func => self.resolver.resolve_idx(index, Ns::Func),
+            IndexSpace::Table => self.resolver.resolve_idx(index, Ns::Table),
+            IndexSpace::Memory => self.resolver.resolve_idx(index, Ns::Memory),
+            IndexSpace::Global => self.resolver.resolve_idx(index, Ns::Global),
+            IndexSpace::Type => self.resolver.resolve_idx(index, Ns::Type),
+            IndexSpace::Elem => self.resolver.resolve_idx(index, Ns::Elem),
+            IndexSpace::Data => self.resolver.resolve_idx(index, Ns::Data),
+            IndexSpace::Local => self
                 .locals
-                .resolve(i)
+                .resolve(index)
                 .map(|_| ())
-                .map_err(|id| self.resolver.resolve_error(id, "local")),
+                .map_err(|id| not_found(&id, "local", self.locals.names())),
+            IndexSpace::Label => self.resolve_label(index),
+        };
+        self.check(result);
+    }
 
-            Call(i) | RefFunc(i) | ReturnCall(i) => self.resolver.resolve_idx(i, Ns::Func),
+    fn visit_block(&mut self, bt: &mut BlockType<'a>) {
+        let result = self.resolve_block_type(bt);
+        self.check(result);
+    }
 
-            CallIndirect(c) | ReturnCallIndirect(c) => {
-                self.resolver.resolve_idx(&mut c.table, Ns::Table)?;
-                self.resolver.resolve_type_use(self.span, &mut c.ty)?;
-                Ok(())
-            }
+    fn visit_if(&mut self, bt: &mut BlockType<'a>) {
+        let result = self.resolve_block_type(bt);
+        self.check(result);
+    }
 
-            Block(bt) | If(bt) | Loop(bt) => {
-                self.labels.push(bt.label);
-
-                // Ok things get interesting here. First off when parsing `bt`
-                // *optionally* has an index and a function type listed. If
-                // they're both not present it's equivalent to 0 params and 0
-                // results.
-                //
-                // In MVP wasm blocks can have 0 params and 0-1 results. Now
-                // there's also multi-value. We want to prefer MVP wasm wherever
-                // possible (for backcompat) so we want to list this block as
-                // being an "MVP" block if we can. The encoder only has
-                // `BlockType` to work with, so it'll be looking at `params` and
-                // `results` to figure out what to encode. If `params` and
-                // `results` fit within MVP, then it uses MVP encoding
-                //
-                // To put all that together, here we handle:
-                //
-                // * If the `index` was specified, resolve it and use it as the
-                //   source of truth. If this turns out to be an MVP type,
-                //   record it as such.
-                // * Otherwise use `params` and `results` as the source of
-                //   truth. *If* this were a non-MVP compatible block `index`
-                //   would be filled by by `tyexpand.rs`.
-                //
-                // tl;dr; we handle the `index` here if it's set and then fill
-                // out `params` and `results` if we can, otherwise no work
-                // happens.
-                if bt.ty.index.is_some() {
-                    let ty = self.resolver.resolve_type_use(self.span, &mut bt.ty)?;
-                    let ty = match self.resolver.tys.get(ty as usize) {
-                        Some(ty) => ty,
-                        None => return Ok(()),
-                    };
-                    if ty.params.len() == 0 && ty.results.len() <= 1 {
-                        bt.ty.ty.params.truncate(0);
-                        bt.ty.ty.results = ty.results.clone();
-                        bt.ty.index = None;
-                    }
-                }
-                Ok(())
-            }
+    fn visit_loop(&mut self, bt: &mut BlockType<'a>) {
+        let result = self.resolve_block_type(bt);
+        self.check(result);
+    }
 
-            // On `End` instructions we pop a label from the stack, and for both
-            // `End` and `Else` instructions if they have labels listed we
-            // verify that they match the label at the beginning of the block.
-            Else(_) | End(_) => {
-                let (matching_label, label) = match instr {
-                    Else(label) => (self.labels.last().cloned(), label),
-                    End(label) => (self.labels.pop(), label),
-                    _ => unreachable!(),
-                };
-                let matching_label = match matching_label {
-                    Some(l) => l,
-                    None => return Ok(()),
-                };
-                let label = match label {
-                    Some(l) => l,
-                    None => return Ok(()),
-                };
-                if Some(*label) == matching_label {
-                    return Ok(());
-                }
-                return Err(Error::new(
-                    label.span(),
-                    "mismatching labels between end and block".to_string(),
-                ));
-            }
+    fn visit_else(&mut self, label: &mut Option<Id<'a>>) {
+        let matching_label = self.labels.last().cloned();
+        let result = check_end_label(label, matching_label);
+        self.check(result);
+    }
 
-            Br(i) | BrIf(i) => self.resolve_label(i),
+    fn visit_end(&mut self, label: &mut Option<Id<'a>>) {
+        let matching_label = self.labels.pop();
+        let result = check_end_label(label, matching_label);
+        self.check(result);
+    }
+}
 
-            BrTable(i) => {
-                for label in i.labels.iter_mut() {
-                    self.resolve_label(label)?;
-                }
-                self.resolve_label(&mut i.default)
+impl<'a, 'b> FieldResolver<'a, 'b> {
+    fn new(resolver: &'b Resolver<'a>) -> FieldResolver<'a, 'b> {
+        FieldResolver {
+            resolver,
+            locals: Default::default(),
+            labels: Vec::new(),
+            span: Span { offset: 0 },
+            err: None,
+        }
+    }
+
+    fn check(&mut self, result: Result<(), Error>) {
+        if let Err(e) = result {
+            if self.err.is_none() {
+                self.err = Some(e);
             }
+        }
+    }
 
-            _ => Ok(()),
+    fn resolve_block_type(&mut self, bt: &mut BlockType<'a>) -> Result<(), Error> {
+        if let Some(label) = &bt.label {
+            let prev = self.labels.iter().rev().flatten().find(|l| *l == label);
+            if let Some(prev) = prev {
+                if self.resolver.options.reject_label_shadowing {
+                    return Err(duplicate(label.clone(), prev.clone(), "label"));
+                }
+            }
+        }
+        self.labels.push(bt.label.clone());
+
+        // Ok things get interesting here. First off when parsing `bt`
+        // *optionally* has an index and a function type listed. If they're
+        // both not present it's equivalent to 0 params and 0 results.
+        //
+        // In MVP wasm blocks can have 0 params and 0-1 results. Now there's
+        // also multi-value. We want to prefer MVP wasm wherever possible (for
+        // backcompat) so we want to list this block as being an "MVP" block if
+        // we can. The encoder only has `BlockType` to work with, so it'll be
+        // looking at `params` and `results` to figure out what to encode. If
+        // `params` and `results` fit within MVP, then it uses MVP encoding
+        //
+        // To put all that together, here we handle:
+        //
+        // * If the `index` was specified, resolve it and use it as the source
+        //   of truth. If this turns out to be an MVP type, record it as such.
+        // * Otherwise use `params` and `results` as the source of truth. *If*
+        //   this were a non-MVP compatible block `index` would be filled by by
+        //   `tyexpand.rs`.
+        //
+        // tl;dr; we handle the `index` here if it's set and then fill out
+        // `params` and `results` if we can, otherwise no work happens.
+        if bt.ty.index.is_some() {
+            let ty = self.resolver.resolve_type_use(self.span, &mut bt.ty)?;
+            let ty = match self.resolver.tys.get(ty as usize) {
+                Some(ty) => ty,
+                None => return Ok(()),
+            };
+            if ty.params.len() == 0 && ty.results.len() <= 1 {
+                bt.ty.ty.params.truncate(0);
+                bt.ty.ty.results = ty.results.clone();
+                bt.ty.index = None;
+            }
         }
+        Ok(())
     }
 
     fn resolve_label(&self, label: &mut Index<'a>) -> Result<(), Error> {
         let id = match label {
             Index::Num(_) => return Ok(()),
-            Index::Id(id) => *id,
+            Index::Id(id) => id.clone(),
         };
         let idx = self
             .labels
             .iter()
             .rev()
             .enumerate()
-            .filter_map(|(i, l)| l.map(|l| (i, l)))
-            .find(|(_, l)| *l == id);
+            .filter_map(|(i, l)| l.as_ref().map(|l| (i, l)))
+            .find(|(_, l)| **l == id);
         match idx {
             Some((idx, _)) => {
                 *label = Index::Num(idx as u32);
                 Ok(())
             }
-            None => Err(self.resolver.resolve_error(id, "label")),
+            None => {
+                let labels = self.labels.iter().flatten().map(|l| l.name());
+                Err(not_found(&id, "label", labels))
+            }
         }
     }
 }
+
+/// Verifies that the label listed on an `else` or `end`, if any, matches the
+/// label at the beginning of its block.
+fn check_end_label<'a>(
+    label: &Option<Id<'a>>,
+    matching_label: Option<Option<Id<'a>>>,
+) -> Result<(), Error> {
+    let matching_label = match matching_label {
+        Some(l) => l,
+        None => return Ok(()),
+    };
+    let label = match label {
+        Some(l) => l,
+        None => return Ok(()),
+    };
+    if Some(label) == matching_label.as_ref() {
+        return Ok(());
+    }
+    Err(Error::new(
+        label.span(),
+        "mismatching labels between end and block".to_string(),
+    ))
+}
@@ -1,4 +1,5 @@
 use crate::ast::*;
+use crate::visit::VisitMut;
 use std::collections::HashMap;
 
 #[derive(Default)]
@@ -8,99 +9,48 @@ pub struct Expander<'a> {
     ntypes: u32,
 }
 
-impl<'a> Expander<'a> {
-    pub fn expand(&mut self, item: &mut ModuleField<'a>) {
-        match item {
-            ModuleField::Type(t) => self.register_type(t),
-            ModuleField::Import(i) => self.expand_import(i),
-            ModuleField::Func(f) => self.expand_func(f),
-            ModuleField::Global(g) => self.expand_global(g),
-            ModuleField::Data(d) => self.expand_data(d),
-            ModuleField::Elem(e) => self.expand_elem(e),
-            _ => {}
-        }
-    }
-
-    fn register_type(&mut self, ty: &Type<'a>) {
-        let key = self.key(&ty.func);
+impl<'a> VisitMut<'a> for Expander<'a> {
+    fn visit_type(&mut self, ty: &mut Type<'a>) {
+        let key = key(&ty.func);
         if !self.types.contains_key(&key) {
             self.types.insert(key, self.ntypes);
         }
         self.ntypes += 1;
     }
 
-    fn expand_import(&mut self, import: &mut Import<'a>) {
-        match &mut import.kind {
-            ImportKind::Func(f) => self.expand_type_use(f),
-            _ => {}
-        }
-    }
-
-    fn expand_func(&mut self, func: &mut Func<'a>) {
-        self.expand_type_use(&mut func.ty);
-        if let FuncKind::Inline { expression, .. } = &mut func.kind {
-            self.expand_expression(expression);
-        }
-    }
-
-    fn expand_global(&mut self, global: &mut Global<'a>) {
-        if let GlobalKind::Inline(expr) = &mut global.kind {
-            self.expand_expression(expr);
-        }
-    }
-
-    fn expand_elem(&mut self, elem: &mut Elem<'a>) {
-        if let ElemKind::Active { offset, .. } = &mut elem.kind {
-            self.expand_expression(offset);
+    fn visit_type_use(&mut self, item: &mut TypeUse<'a>) {
+        if item.index.is_some() {
+            return;
         }
+        let key = key(&item.ty);
+        item.index = Some(Index::Num(match self.types.get(&key) {
+            Some(i) => *i,
+            None => self.prepend(key),
+        }));
     }
 
-    fn expand_data(&mut self, data: &mut Data<'a>) {
-        if let DataKind::Active { offset, .. } = &mut data.kind {
-            self.expand_expression(offset);
-        }
+    fn visit_block(&mut self, bt: &mut BlockType<'a>) {
+        self.expand_block_type(bt)
     }
 
-    fn expand_expression(&mut self, expr: &mut Expression<'a>) {
-        for instr in expr.instrs.iter_mut() {
-            self.expand_instr(instr);
-        }
+    fn visit_if(&mut self, bt: &mut BlockType<'a>) {
+        self.expand_block_type(bt)
     }
 
-    fn expand_instr(&mut self, instr: &mut Instruction<'a>) {
-        match instr {
-            Instruction::Block(bt) | Instruction::If(bt) | Instruction::Loop(bt) => {
-                // Only actually expand `TypeUse` with an index which appends a
-                // type if it looks like we need one. This way if the
-                // multi-value proposal isn't enabled and/or used we won't
-                // encode it.
-                if bt.ty.ty.params.len() == 0 && bt.ty.ty.results.len() <= 1 {
-                    return;
-                }
-                self.expand_type_use(&mut bt.ty)
-            }
-            Instruction::CallIndirect(c) | Instruction::ReturnCallIndirect(c) => {
-                self.expand_type_use(&mut c.ty)
-            }
-            _ => {}
-        }
+    fn visit_loop(&mut self, bt: &mut BlockType<'a>) {
+        self.expand_block_type(bt)
     }
+}
 
-    fn expand_type_use(&mut self, item: &mut TypeUse<'a>) {
-        if item.index.is_some() {
+impl<'a> Expander<'a> {
+    fn expand_block_type(&mut self, bt: &mut BlockType<'a>) {
+        // Only actually expand `TypeUse` with an index which appends a type if
+        // it looks like we need one. This way if the multi-value proposal isn't
+        // enabled and/or used we won't encode it.
+        if bt.ty.ty.params.len() == 0 && bt.ty.ty.results.len() <= 1 {
             return;
         }
-        let key = self.key(&item.ty);
-        item.index = Some(Index::Num(match self.types.get(&key) {
-            Some(i) => *i,
-            None => self.prepend(key),
-        }));
-    }
-
-    fn key(&self, ty: &FunctionType) -> (Vec<ValType>, Vec<ValType>) {
-        let params = ty.params.iter().map(|p| p.1).collect::<Vec<_>>();
-        let results = ty.results.clone();
-        (params, results)
+        self.visit_type_use(&mut bt.ty)
     }
 
     fn prepend(&mut self, key: (Vec<ValType>, Vec<ValType>)) -> u32 {
@@ -116,3 +66,11 @@ impl<'a> Expander<'a> {
         return self.ntypes - 1;
     }
 }
+
+/// Returns the key which identifies the function type `ty`, ignoring the names
+/// of its parameters, so that identical types can be deduplicated.
+pub fn key(ty: &FunctionType) -> (Vec<ValType>, Vec<ValType>) {
+    let params = ty.params.iter().map(|p| p.1).collect::<Vec<_>>();
+    let results = ty.results.clone();
+    (params, results)
+}
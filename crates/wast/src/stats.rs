@@ -0,0 +1,273 @@
+//! Statistics about where the bytes of a binary module go.
+
+use crate::ast::*;
+use crate::dump::SECTION_NAMES;
+use crate::reader::{self, Decode, Reader, Result};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Statistics about a binary module, as returned by
+/// [`Module::stats`](crate::Module::stats).
+///
+/// This is displayed as a text report, and with the `serde` feature it can
+/// also be serialized, for example as JSON.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stats {
+    /// The size of the whole module in bytes.
+    pub size: usize,
+    /// Each section in the order they appear in the module.
+    pub sections: Vec<SectionStats>,
+    /// Each function defined by the module, in index order.
+    pub funcs: Vec<FuncStats>,
+    /// How often each instruction is used in function bodies, with the most
+    /// frequent first.
+    pub instructions: Vec<InstructionCount>,
+    /// The number of each kind of item which is imported.
+    pub imports: ItemCounts,
+    /// The number of each kind of item which is exported.
+    pub exports: ItemCounts,
+}
+
+/// The size of one section of a module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SectionStats {
+    /// The name of the section, such as `Type`, or `Custom "name"` for custom
+    /// sections.
+    pub name: String,
+    /// The size of the section's contents in bytes, not counting its id and
+    /// size.
+    pub size: usize,
+}
+
+/// The size of one function body.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FuncStats {
+    /// The index of the function, counting imported functions.
+    pub index: u32,
+    /// The name of the function from the `name` section, if it has one.
+    pub name: Option<String>,
+    /// The size of the function body in bytes, including its locals but not
+    /// its size.
+    pub size: usize,
+    /// The number of instructions in the body.
+    pub instructions: usize,
+}
+
+/// The number of times one instruction is used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstructionCount {
+    /// The name of the instruction, such as `i32.add`, which there's one of
+    /// for each [`Instruction`] variant.
+    pub name: String,
+    /// The number of times the instruction is used.
+    pub count: usize,
+}
+
+/// The number of each kind of item imported or exported by a module.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ItemCounts {
+    /// The number of functions.
+    pub funcs: u32,
+    /// The number of tables.
+    pub tables: u32,
+    /// The number of memories.
+    pub memories: u32,
+    /// The number of globals.
+    pub globals: u32,
+}
+
+/// Collects statistics about the binary module `bytes`.
+///
+/// Only the structure of the module is checked, as with [`reader::check`],
+/// and function bodies are read straight from the code section, so modules
+/// with items the AST can't represent are fine.
+pub fn stats(bytes: &[u8]) -> Result<Stats> {
+    reader::check(bytes)?;
+    let mut stats = Stats {
+        size: bytes.len(),
+        ..Stats::default()
+    };
+    let mut bodies = Vec::new();
+    let mut names = HashMap::new();
+    let mut histogram = HashMap::new();
+    reader::sections(bytes, |id, s| {
+        let size = s.end() - s.pos();
+        let name = match id {
+            0 => {
+                let name = s.name()?;
+                if name == "name" {
+                    func_names(s, &mut names)?;
+                }
+                format!("Custom {:?}", name)
+            }
+            _ => SECTION_NAMES[usize::from(id)].to_string(),
+        };
+        stats.sections.push(SectionStats { name, size });
+        match id {
+            2 => {
+                for kind in s.vec(import_kind)? {
+                    stats.imports.add(kind);
+                }
+            }
+            7 => {
+                for export in s.vec(reader::export)? {
+                    stats.exports.add(match export.kind {
+                        ExportKind::Func(_) => 0,
+                        ExportKind::Table(_) => 1,
+                        ExportKind::Memory(_) => 2,
+                        ExportKind::Global(_) => 3,
+                    });
+                }
+            }
+            10 => {
+                bodies = s.vec(|s| {
+                    let size = s.var_u32()? as usize;
+                    let mut body = s.sub(size)?;
+                    body.vec(|body| {
+                        body.var_u32()?;
+                        reader::valtype(body)
+                    })?;
+                    let expression = Expression::decode(&mut body)?;
+                    for instr in expression.instrs.iter() {
+                        *histogram.entry(instr.name()).or_insert(0) += 1;
+                    }
+                    Ok((size, expression.instrs.len()))
+                })?;
+            }
+            _ => {}
+        }
+        s.skip_to_end();
+        Ok(())
+    })?;
+
+    for (i, (size, instructions)) in bodies.into_iter().enumerate() {
+        let index = stats.imports.funcs + i as u32;
+        stats.funcs.push(FuncStats {
+            index,
+            name: names.remove(&index),
+            size,
+            instructions,
+        });
+    }
+    stats.instructions = histogram
+        .into_iter()
+        .map(|(name, count)| InstructionCount {
+            name: name.to_string(),
+            count,
+        })
+        .collect();
+    stats
+        .instructions
+        .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    Ok(stats)
+}
+
+/// Reads an import, returning its kind as the byte it's encoded with.
+fn import_kind(r: &mut Reader<'_>) -> Result<u8> {
+    r.name()?;
+    r.name()?;
+    let kind = r.u8()?;
+    match kind {
+        0x00 => {
+            r.var_u32()?;
+        }
+        0x01 => {
+            reader::table_type(r)?;
+        }
+        0x02 => {
+            reader::memory_type(r)?;
+        }
+        0x03 => {
+            reader::global_type(r)?;
+        }
+        _ => return Err(r.error_at(r.pos() - 1, "malformed import kind")),
+    }
+    Ok(kind)
+}
+
+/// Reads the function names of the `name` section `r` into `names`, skipping
+/// its other subsections.
+///
+/// Names which are empty are ignored, as are any but the first name given to
+/// a function.
+fn func_names(r: &mut Reader<'_>, names: &mut HashMap<u32, String>) -> Result<()> {
+    while !r.eof() {
+        let id = r.u8()?;
+        let len = r.var_u32()? as usize;
+        let mut sub = r.sub(len)?;
+        if id == 1 {
+            for (index, name) in sub.vec(|s| Ok((s.var_u32()?, s.name()?)))? {
+                if !name.is_empty() {
+                    names.entry(index).or_insert_with(|| name.to_string());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+impl ItemCounts {
+    /// Counts one more item of the kind encoded as `kind` in the binary
+    /// format.
+    fn add(&mut self, kind: u8) {
+        match kind {
+            0x00 => self.funcs += 1,
+            0x01 => self.tables += 1,
+            0x02 => self.memories += 1,
+            _ => self.globals += 1,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Size: {} bytes", self.size)?;
+        writeln!(f, "Imports: {}", self.imports)?;
+        writeln!(f, "Exports: {}", self.exports)?;
+
+        writeln!(f, "\nSections:")?;
+        let width = self
+            .sections
+            .iter()
+            .map(|s| s.name.len())
+            .max()
+            .unwrap_or(0);
+        for s in self.sections.iter() {
+            writeln!(f, " {:<width$} {:>8} bytes", s.name, s.size, width = width)?;
+        }
+
+        writeln!(f, "\nFunctions:")?;
+        for func in self.funcs.iter() {
+            let name = match &func.name {
+                Some(name) => format!(" <{}>", name),
+                None => String::new(),
+            };
+            writeln!(
+                f,
+                " func[{}]{}: {} bytes, {} instructions",
+                func.index, name, func.size, func.instructions
+            )?;
+        }
+
+        writeln!(f, "\nInstructions:")?;
+        for instr in self.instructions.iter() {
+            writeln!(f, " {:<24} {:>8}", instr.name, instr.count)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ItemCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} functions, {} tables, {} memories, {} globals",
+            self.funcs, self.tables, self.memories, self.globals
+        )
+    }
+}
@@ -0,0 +1,34 @@
+//! Helpers for suggesting fixes for misspelled names in error messages.
+
+/// Returns the entry of `candidates` which is closest to `name`, if any is
+/// close enough to plausibly be what was intended.
+///
+/// Closeness is measured with the Levenshtein edit distance, and a candidate
+/// is only considered if it's within a third of the length of `name`. Ties are
+/// broken alphabetically so the suggestion doesn't depend on the iteration
+/// order of `candidates`.
+pub fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let max = std::cmp::max(name.len(), 3) / 3;
+    candidates
+        .into_iter()
+        .filter(|c| *c != name)
+        .map(|c| (distance(name, c), c))
+        .filter(|(d, _)| *d <= max)
+        .min()
+        .map(|(_, c)| c)
+}
+
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let subst = prev[j] + if ca == *cb { 0 } else { 1 };
+            cur[j + 1] = subst.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
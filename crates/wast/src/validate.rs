@@ -0,0 +1,782 @@
+use crate::ast::*;
+use crate::Error;
+use crate::WasmFeatures;
+
+type Signature = (Vec<ValType>, Vec<ValType>);
+
+/// Type checks a module which has already been resolved, returning an error
+/// pointing at the first invalid item.
+pub fn validate(module: &Module<'_>) -> Result<(), Error> {
+    validate_with(module, &WasmFeatures::default())
+}
+
+/// Same as [`validate`], except that multiple tables are only allowed if
+/// `features` enables the reference types proposal.
+pub fn validate_with(module: &Module<'_>, features: &WasmFeatures) -> Result<(), Error> {
+    let fields = match &module.kind {
+        ModuleKind::Text(fields) => fields,
+        ModuleKind::Binary(bytes) => {
+            return decode_binary(module.span, bytes, |m| validate_with(m, features))
+        }
+    };
+
+    check_counts(fields, features)?;
+    let cx = Context::new(fields)?;
+    for field in fields {
+        match field {
+            ModuleField::Func(f) => {
+                if let FuncKind::Inline { locals, expression } = &f.kind {
+                    cx.check_func(f, locals, expression, |_| {})
+                        .map_err(|msg| Error::new(f.span, msg))?;
+                }
+            }
+            ModuleField::Global(g) => {
+                if let GlobalKind::Inline(expr) = &g.kind {
+                    cx.check_const(expr, g.ty.ty)
+                        .map_err(|msg| Error::new(g.span, msg))?;
+                }
+            }
+            ModuleField::Elem(e) => cx.check_elem(e).map_err(|msg| Error::new(e.span, msg))?,
+            ModuleField::Data(d) => {
+                if let DataKind::Active { memory, offset } = &d.kind {
+                    cx.memory(memory)
+                        .and_then(|()| cx.check_const(offset, ValType::I32))
+                        .map_err(|msg| Error::new(d.span, msg))?;
+                }
+            }
+            ModuleField::Export(e) => {
+                let result = match &e.kind {
+                    ExportKind::Func(i) => cx.func(i).map(drop),
+                    ExportKind::Table(i) => cx.table(i).map(drop),
+                    ExportKind::Memory(i) => cx.memory(i),
+                    ExportKind::Global(i) => cx.global(i).map(drop),
+                };
+                result.map_err(|msg| Error::new(e.span, msg))?;
+            }
+            ModuleField::Start(i) => {
+                let ty = cx.func(i).map_err(|msg| Error::new(module.span, msg))?;
+                if !ty.0.is_empty() || !ty.1.is_empty() {
+                    return Err(Error::new(
+                        module.span,
+                        "start function must take no parameters and return nothing".to_string(),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Decodes the contents `bytes` of a `(module binary ...)` and calls `f` with
+/// the decoded module.
+///
+/// Errors, including those returned by `f`, point at `span` and mention the
+/// byte offset of the problem within the contents.
+pub(crate) fn decode_binary(
+    span: Span,
+    bytes: &[std::borrow::Cow<'_, [u8]>],
+    f: impl FnOnce(&Module<'_>) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let bytes = bytes.concat();
+    let module = crate::reader::decode(&bytes)
+        .map_err(|e| Error::new(span, format!("{} at byte offset {}", e.message, e.offset)))?;
+    f(&module).map_err(|e| e.in_binary(span))
+}
+
+/// Checks that `fields` have at most one memory, and at most one table unless
+/// the reference types proposal is enabled.
+fn check_counts(fields: &[ModuleField<'_>], features: &WasmFeatures) -> Result<(), Error> {
+    let (mut tables, mut memories) = (0, 0);
+    for field in fields {
+        let (span, table) = match field {
+            ModuleField::Import(i) => match i.kind {
+                ImportKind::Table(_) => (i.span, true),
+                ImportKind::Memory(_) => (i.span, false),
+                _ => continue,
+            },
+            ModuleField::Table(t) => (t.span, true),
+            ModuleField::Memory(m) => (m.span, false),
+            _ => continue,
+        };
+        if table {
+            tables += 1;
+            if tables > 1 && !features.reference_types {
+                return Err(Error::new(
+                    span,
+                    "multiple tables require the reference types proposal, which is not enabled"
+                        .to_string(),
+                ));
+            }
+        } else {
+            memories += 1;
+            if memories > 1 {
+                return Err(Error::new(
+                    span,
+                    "multiple memories are not supported".to_string(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns, for each instruction of each function body in `module`, whether
+/// the operand stack holds exactly the values its innermost enclosing block
+/// ends with when the instruction is reached.
+///
+/// Bodies are listed in the order of their functions, and the module must be
+/// resolved and valid.
+pub fn block_ends(module: &Module<'_>) -> Result<Vec<Vec<bool>>, Error> {
+    let fields = match &module.kind {
+        ModuleKind::Text(fields) => fields,
+        ModuleKind::Binary(_) => return Ok(Vec::new()),
+    };
+    let cx = Context::new(fields)?;
+    let mut bodies = Vec::new();
+    for field in fields {
+        if let ModuleField::Func(f) = field {
+            if let FuncKind::Inline { locals, expression } = &f.kind {
+                let mut ends = Vec::new();
+                cx.check_func(f, locals, expression, |c| ends.push(c.at_block_end()))
+                    .map_err(|msg| Error::new(f.span, msg))?;
+                bodies.push(ends);
+            }
+        }
+    }
+    Ok(bodies)
+}
+
+#[derive(Default)]
+struct Context {
+    types: Vec<Signature>,
+    funcs: Vec<u32>,
+    tables: Vec<TableElemType>,
+    memories: u32,
+    /// The type of each global, and whether it's imported.
+    globals: Vec<(GlobalType, bool)>,
+    elems: u32,
+    datas: u32,
+}
+
+impl Context {
+    fn new(fields: &[ModuleField<'_>]) -> Result<Context, Error> {
+        // Types are collected first; see `resolve` for why.
+        let mut cx = Context::default();
+        for field in fields {
+            if let ModuleField::Type(t) = field {
+                let params = t.func.params.iter().map(|p| p.1).collect();
+                cx.types.push((params, t.func.results.clone()));
+            }
+        }
+        for field in fields {
+            match field {
+                ModuleField::Import(i) => match &i.kind {
+                    ImportKind::Func(ty) => {
+                        let idx = cx.func_type(ty).map_err(|msg| Error::new(i.span, msg))?;
+                        cx.funcs.push(idx);
+                    }
+                    ImportKind::Table(ty) => cx.tables.push(ty.elem),
+                    ImportKind::Memory(_) => cx.memories += 1,
+                    ImportKind::Global(ty) => cx.globals.push((*ty, true)),
+                },
+                ModuleField::Func(f) => {
+                    let idx = cx.func_type(&f.ty).map_err(|msg| Error::new(f.span, msg))?;
+                    cx.funcs.push(idx);
+                }
+                ModuleField::Table(t) => match &t.kind {
+                    TableKind::Import { ty, .. } | TableKind::Normal(ty) => cx.tables.push(ty.elem),
+                    TableKind::Inline { elem, .. } => cx.tables.push(*elem),
+                },
+                ModuleField::Memory(_) => cx.memories += 1,
+                ModuleField::Global(g) => {
+                    let imported = match g.kind {
+                        GlobalKind::Import { .. } => true,
+                        GlobalKind::Inline(_) => false,
+                    };
+                    cx.globals.push((g.ty, imported));
+                }
+                ModuleField::Elem(_) => cx.elems += 1,
+                ModuleField::Data(_) => cx.datas += 1,
+                ModuleField::Type(_) | ModuleField::Export(_) | ModuleField::Start(_) => {}
+            }
+        }
+        Ok(cx)
+    }
+
+    fn func_type(&self, ty: &TypeUse<'_>) -> Result<u32, String> {
+        let index = match &ty.index {
+            Some(index) => index,
+            None => return Err("function type was not resolved".to_string()),
+        };
+        let idx = num(index)?;
+        if idx as usize >= self.types.len() {
+            return Err(format!("unknown type {}", idx));
+        }
+        Ok(idx)
+    }
+
+    fn type_use(&self, ty: &TypeUse<'_>) -> Result<Signature, String> {
+        match &ty.index {
+            Some(_) => Ok(self.types[self.func_type(ty)? as usize].clone()),
+            None => Ok((
+                ty.ty.params.iter().map(|p| p.1).collect(),
+                ty.ty.results.clone(),
+            )),
+        }
+    }
+
+    fn func(&self, index: &Index<'_>) -> Result<&Signature, String> {
+        let idx = num(index)?;
+        match self.funcs.get(idx as usize) {
+            Some(ty) => Ok(&self.types[*ty as usize]),
+            None => Err(format!("unknown function {}", idx)),
+        }
+    }
+
+    fn table(&self, index: &Index<'_>) -> Result<ValType, String> {
+        let idx = num(index)?;
+        match self.tables.get(idx as usize) {
+            Some(ty) => Ok(elem_valtype(*ty)),
+            None => Err(format!("unknown table {}", idx)),
+        }
+    }
+
+    fn memory(&self, index: &Index<'_>) -> Result<(), String> {
+        let idx = num(index)?;
+        if idx >= self.memories {
+            return Err(format!("unknown memory {}", idx));
+        }
+        Ok(())
+    }
+
+    fn global(&self, index: &Index<'_>) -> Result<GlobalType, String> {
+        let idx = num(index)?;
+        match self.globals.get(idx as usize) {
+            Some((ty, _)) => Ok(*ty),
+            None => Err(format!("unknown global {}", idx)),
+        }
+    }
+
+    /// Checks that the global `index` may be read by a constant expression,
+    /// which is only the case for imported immutable globals.
+    fn const_global(&self, index: &Index<'_>) -> Result<(), String> {
+        let idx = num(index)?;
+        match self.globals.get(idx as usize) {
+            Some((ty, true)) if !ty.mutable => Ok(()),
+            Some((_, true)) => Err(format!(
+                "constant expression cannot read mutable global {}",
+                idx
+            )),
+            Some((_, false)) => Err(format!(
+                "constant expression cannot read global {}, which isn't imported",
+                idx
+            )),
+            None => Err(format!("unknown global {}", idx)),
+        }
+    }
+
+    fn elem(&self, index: &Index<'_>) -> Result<(), String> {
+        let idx = num(index)?;
+        if idx >= self.elems {
+            return Err(format!("unknown elem segment {}", idx));
+        }
+        Ok(())
+    }
+
+    fn data(&self, index: &Index<'_>) -> Result<(), String> {
+        let idx = num(index)?;
+        if idx >= self.datas {
+            return Err(format!("unknown data segment {}", idx));
+        }
+        Ok(())
+    }
+
+    fn check_func(
+        &self,
+        func: &Func<'_>,
+        locals: &[(Option<Id<'_>>, ValType)],
+        expr: &Expression<'_>,
+        before: impl FnMut(&FuncChecker<'_>),
+    ) -> Result<(), String> {
+        let (params, results) = self.type_use(&func.ty)?;
+        let mut all = params;
+        all.extend(locals.iter().map(|l| l.1));
+        let mut checker = FuncChecker::new(self, all, results);
+        checker.check_with(expr, before)
+    }
+
+    fn check_const(&self, expr: &Expression<'_>, ty: ValType) -> Result<(), String> {
+        for instr in expr.instrs.iter() {
+            match instr {
+                Instruction::I32Const(_)
+                | Instruction::I64Const(_)
+                | Instruction::F32Const(_)
+                | Instruction::F64Const(_)
+                | Instruction::V128Const(_)
+                | Instruction::RefNull
+                | Instruction::RefFunc(_) => {}
+                Instruction::GlobalGet(index) => self.const_global(index)?,
+                _ => {
+                    return Err(format!(
+                        "constant expression required, found `{}`",
+                        instr.name()
+                    ))
+                }
+            }
+        }
+        FuncChecker::new(self, Vec::new(), vec![ty]).check(expr)
+    }
+
+    fn check_elem(&self, elem: &Elem<'_>) -> Result<(), String> {
+        if let ElemKind::Active { table, offset } = &elem.kind {
+            self.table(table)?;
+            self.check_const(offset, ValType::I32)?;
+        }
+        match &elem.payload {
+            ElemPayload::Indices(indices) => {
+                for index in indices {
+                    self.func(index)?;
+                }
+            }
+            ElemPayload::Exprs { exprs, .. } => {
+                for index in exprs.iter().filter_map(|e| e.as_ref()) {
+                    self.func(index)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(PartialEq)]
+enum FrameKind {
+    Func,
+    Block,
+    Loop,
+    If,
+    Else,
+}
+
+struct Frame {
+    kind: FrameKind,
+    params: Vec<ValType>,
+    results: Vec<ValType>,
+    height: usize,
+    unreachable: bool,
+}
+
+impl Frame {
+    fn label_types(&self) -> &[ValType] {
+        match self.kind {
+            FrameKind::Loop => &self.params,
+            _ => &self.results,
+        }
+    }
+}
+
+/// Tracks the operand stack while checking a function body, where `None`
+/// stands for a value of unknown type in unreachable code.
+struct FuncChecker<'c> {
+    cx: &'c Context,
+    locals: Vec<ValType>,
+    results: Vec<ValType>,
+    stack: Vec<Option<ValType>>,
+    frames: Vec<Frame>,
+}
+
+impl<'c> FuncChecker<'c> {
+    fn new(cx: &'c Context, locals: Vec<ValType>, results: Vec<ValType>) -> FuncChecker<'c> {
+        FuncChecker {
+            cx,
+            locals,
+            frames: vec![Frame {
+                kind: FrameKind::Func,
+                params: Vec::new(),
+                results: results.clone(),
+                height: 0,
+                unreachable: false,
+            }],
+            results,
+            stack: Vec::new(),
+        }
+    }
+
+    fn check(&mut self, expr: &Expression<'_>) -> Result<(), String> {
+        self.check_with(expr, |_| {})
+    }
+
+    /// Same as `check`, except that `before` is called with the state of the
+    /// checker before each instruction is checked.
+    fn check_with(
+        &mut self,
+        expr: &Expression<'_>,
+        mut before: impl FnMut(&FuncChecker<'c>),
+    ) -> Result<(), String> {
+        for (i, instr) in expr.instrs.iter().enumerate() {
+            if self.frames.is_empty() {
+                return Err(format!(
+                    "`{}` found after the end of the function",
+                    instr.name()
+                ));
+            }
+            before(self);
+            self.instr(instr)
+                .map_err(|msg| format!("{} at instruction {} (`{}`)", msg, i, instr.name()))?;
+        }
+        if self.frames.len() != 1 {
+            return Err("unclosed block at the end of the function".to_string());
+        }
+        self.pop_frame()
+            .map(drop)
+            .map_err(|msg| format!("{} at the end of the function", msg))
+    }
+
+    /// Returns whether the stack holds exactly the values which the
+    /// innermost block ends with, so falling through to its end is the same
+    /// as branching to it.
+    fn at_block_end(&self) -> bool {
+        let frame = self.frames.last().unwrap();
+        let len = frame.height + frame.results.len();
+        self.stack.len() == len || (frame.unreachable && self.stack.len() < len)
+    }
+
+    fn push(&mut self, ty: ValType) {
+        self.stack.push(Some(ty));
+    }
+
+    fn pop(&mut self) -> Result<Option<ValType>, String> {
+        let frame = self.frames.last().unwrap();
+        if self.stack.len() == frame.height {
+            if frame.unreachable {
+                return Ok(None);
+            }
+            return Err("type mismatch: expected a value but the stack is empty".to_string());
+        }
+        Ok(self.stack.pop().unwrap())
+    }
+
+    fn pop_expect(&mut self, expected: ValType) -> Result<Option<ValType>, String> {
+        match self.pop()? {
+            Some(actual) if !subtype(actual, expected) => Err(format!(
+                "type mismatch: expected {}, found {}",
+                expected.name(),
+                actual.name()
+            )),
+            _ => Ok(Some(expected)),
+        }
+    }
+
+    fn pop_all(&mut self, tys: &[ValType]) -> Result<(), String> {
+        for ty in tys.iter().rev() {
+            self.pop_expect(*ty)?;
+        }
+        Ok(())
+    }
+
+    fn push_all(&mut self, tys: &[ValType]) {
+        for ty in tys {
+            self.push(*ty);
+        }
+    }
+
+    fn unreachable(&mut self) {
+        let frame = self.frames.last_mut().unwrap();
+        self.stack.truncate(frame.height);
+        frame.unreachable = true;
+    }
+
+    fn push_frame(&mut self, kind: FrameKind, (params, results): Signature) -> Result<(), String> {
+        self.pop_all(&params)?;
+        self.frames.push(Frame {
+            kind,
+            height: self.stack.len(),
+            unreachable: false,
+            params,
+            results,
+        });
+        let params = self.frames.last().unwrap().params.clone();
+        self.push_all(&params);
+        Ok(())
+    }
+
+    fn pop_frame(&mut self) -> Result<Frame, String> {
+        let results = self.frames.last().unwrap().results.clone();
+        self.pop_all(&results)?;
+        let frame = self.frames.pop().unwrap();
+        if self.stack.len() != frame.height {
+            return Err("type mismatch: values remaining on the stack".to_string());
+        }
+        Ok(frame)
+    }
+
+    fn label(&self, index: &Index<'_>) -> Result<Vec<ValType>, String> {
+        let depth = num(index)? as usize;
+        if depth >= self.frames.len() {
+            return Err(format!("unknown label {}", depth));
+        }
+        let frame = &self.frames[self.frames.len() - 1 - depth];
+        Ok(frame.label_types().to_vec())
+    }
+
+    fn local(&self, index: &Index<'_>) -> Result<ValType, String> {
+        let idx = num(index)?;
+        match self.locals.get(idx as usize) {
+            Some(ty) => Ok(*ty),
+            None => Err(format!("unknown local {}", idx)),
+        }
+    }
+
+    fn call(&mut self, (params, results): &Signature) -> Result<(), String> {
+        self.pop_all(params)?;
+        self.push_all(results);
+        Ok(())
+    }
+
+    fn return_call(&mut self, (params, results): &Signature) -> Result<(), String> {
+        if *results != self.results {
+            return Err("type mismatch: tail call must return the same types".to_string());
+        }
+        self.pop_all(params)?;
+        self.unreachable();
+        Ok(())
+    }
+
+    fn call_indirect(&self, c: &CallIndirect<'_>) -> Result<Signature, String> {
+        if self.cx.table(&c.table)? != ValType::Funcref {
+            return Err("indirect calls require a table of `funcref`".to_string());
+        }
+        self.cx.type_use(&c.ty)
+    }
+
+    fn instr(&mut self, instr: &Instruction<'_>) -> Result<(), String> {
+        use Instruction::*;
+
+        match instr {
+            Block(bt) => self.push_frame(FrameKind::Block, self.cx.type_use(&bt.ty)?)?,
+            Loop(bt) => self.push_frame(FrameKind::Loop, self.cx.type_use(&bt.ty)?)?,
+            If(bt) => {
+                self.pop_expect(ValType::I32)?;
+                self.push_frame(FrameKind::If, self.cx.type_use(&bt.ty)?)?;
+            }
+            Else(_) => {
+                if self.frames.last().unwrap().kind != FrameKind::If {
+                    return Err("`else` found outside of an `if` block".to_string());
+                }
+                let frame = self.pop_frame()?;
+                self.push_frame(FrameKind::Else, (frame.params, frame.results))?;
+            }
+            End(_) => {
+                if self.frames.len() == 1 {
+                    return Err("`end` found outside of a block".to_string());
+                }
+                let frame = self.pop_frame()?;
+                if frame.kind == FrameKind::If && frame.params != frame.results {
+                    return Err(
+                        "type mismatch: `if` without `else` must not change the stack".to_string(),
+                    );
+                }
+                self.push_all(&frame.results);
+            }
+            Unreachable => self.unreachable(),
+            Nop => {}
+            Br(index) => {
+                let tys = self.label(index)?;
+                self.pop_all(&tys)?;
+                self.unreachable();
+            }
+            BrIf(index) => {
+                self.pop_expect(ValType::I32)?;
+                let tys = self.label(index)?;
+                self.pop_all(&tys)?;
+                self.push_all(&tys);
+            }
+            BrTable(indices) => {
+                self.pop_expect(ValType::I32)?;
+                let default = self.label(&indices.default)?;
+                for label in indices.labels.iter() {
+                    let tys = self.label(label)?;
+                    if tys.len() != default.len() {
+                        return Err(
+                            "type mismatch: `br_table` labels have different arity".to_string()
+                        );
+                    }
+                    let saved = self.stack.clone();
+                    self.pop_all(&tys)?;
+                    self.stack = saved;
+                }
+                self.pop_all(&default)?;
+                self.unreachable();
+            }
+            Return => {
+                let results = self.results.clone();
+                self.pop_all(&results)?;
+                self.unreachable();
+            }
+            Call(index) => {
+                let sig = self.cx.func(index)?.clone();
+                self.call(&sig)?;
+            }
+            CallIndirect(c) => {
+                let sig = self.call_indirect(c)?;
+                self.pop_expect(ValType::I32)?;
+                self.call(&sig)?;
+            }
+            ReturnCall(index) => {
+                let sig = self.cx.func(index)?.clone();
+                self.return_call(&sig)?;
+            }
+            ReturnCallIndirect(c) => {
+                let sig = self.call_indirect(c)?;
+                self.pop_expect(ValType::I32)?;
+                self.return_call(&sig)?;
+            }
+            Drop => {
+                self.pop()?;
+            }
+            Select(s) => {
+                self.pop_expect(ValType::I32)?;
+                if let Some(ty) = s.tys.first() {
+                    if s.tys.len() > 1 {
+                        return Err("invalid result arity for `select`".to_string());
+                    }
+                    self.pop_expect(*ty)?;
+                    self.pop_expect(*ty)?;
+                    self.push(*ty);
+                } else {
+                    let a = self.pop()?;
+                    let b = self.pop()?;
+                    let ty = match (a, b) {
+                        (Some(a), Some(b)) if a != b => {
+                            return Err(format!(
+                                "type mismatch: `select` operands are {} and {}",
+                                b.name(),
+                                a.name()
+                            ))
+                        }
+                        (a, b) => a.or(b),
+                    };
+                    if let Some(ty) = ty {
+                        if is_ref(ty) {
+                            return Err("type mismatch: `select` without types requires \
+                                        numeric operands"
+                                .to_string());
+                        }
+                    }
+                    self.stack.push(ty);
+                }
+            }
+            LocalGet(index) => {
+                let ty = self.local(index)?;
+                self.push(ty);
+            }
+            LocalSet(index) => {
+                let ty = self.local(index)?;
+                self.pop_expect(ty)?;
+            }
+            LocalTee(index) => {
+                let ty = self.local(index)?;
+                self.pop_expect(ty)?;
+                self.push(ty);
+            }
+            GlobalGet(index) => {
+                let ty = self.cx.global(index)?;
+                self.push(ty.ty);
+            }
+            GlobalSet(index) => {
+                let ty = self.cx.global(index)?;
+                if !ty.mutable {
+                    return Err("global is immutable".to_string());
+                }
+                self.pop_expect(ty.ty)?;
+            }
+            TableGet(index) => {
+                let ty = self.cx.table(index)?;
+                self.pop_expect(ValType::I32)?;
+                self.push(ty);
+            }
+            TableSet(index) => {
+                let ty = self.cx.table(index)?;
+                self.pop_expect(ty)?;
+                self.pop_expect(ValType::I32)?;
+            }
+            TableSize(index) => {
+                self.cx.table(index)?;
+                self.push(ValType::I32);
+            }
+            TableGrow(index) => {
+                let ty = self.cx.table(index)?;
+                self.pop_expect(ValType::I32)?;
+                self.pop_expect(ty)?;
+                self.push(ValType::I32);
+            }
+            TableFill(index) => {
+                let ty = self.cx.table(index)?;
+                self.pop_expect(ValType::I32)?;
+                self.pop_expect(ty)?;
+                self.pop_expect(ValType::I32)?;
+            }
+            TableInit(init) => {
+                self.cx.table(&Index::Num(0))?;
+                self.cx.elem(&init.elem)?;
+                self.pop_all(&[ValType::I32; 3])?;
+            }
+            TableCopy => {
+                self.cx.table(&Index::Num(0))?;
+                self.pop_all(&[ValType::I32; 3])?;
+            }
+            ElemDrop(index) => self.cx.elem(index)?,
+            MemoryInit(init) => {
+                self.cx.memory(&Index::Num(0))?;
+                self.cx.data(&init.data)?;
+                self.pop_all(&[ValType::I32; 3])?;
+            }
+            DataDrop(index) => self.cx.data(index)?,
+            RefFunc(index) => {
+                self.cx.func(index)?;
+                self.push(ValType::Funcref);
+            }
+            RefHost(_) => self.push(ValType::Anyref),
+            _ => {
+                let sig = match instr.signature() {
+                    Some(sig) => sig,
+                    None => return Err("cannot validate this instruction".to_string()),
+                };
+                if instr.uses_memory() {
+                    self.cx.memory(&Index::Num(0))?;
+                }
+                self.pop_all(sig.params)?;
+                self.push_all(sig.results);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn num(index: &Index<'_>) -> Result<u32, String> {
+    match index {
+        Index::Num(n) => Ok(*n),
+        Index::Id(id) => Err(format!("unresolved identifier `${}`", id.name())),
+    }
+}
+
+fn elem_valtype(ty: TableElemType) -> ValType {
+    match ty {
+        TableElemType::Funcref => ValType::Funcref,
+        TableElemType::Anyref => ValType::Anyref,
+        TableElemType::Nullref => ValType::Nullref,
+    }
+}
+
+fn is_ref(ty: ValType) -> bool {
+    ty == ValType::Anyref || ty == ValType::Funcref || ty == ValType::Nullref
+}
+
+fn subtype(a: ValType, b: ValType) -> bool {
+    match (a, b) {
+        _ if a == b => true,
+        (ValType::Nullref, ValType::Funcref) => true,
+        (ValType::Nullref, ValType::Anyref) | (ValType::Funcref, ValType::Anyref) => true,
+        _ => false,
+    }
+}
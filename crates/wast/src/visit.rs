@@ -0,0 +1,387 @@
+//! Traits for traversing the AST of a module.
+//!
+//! The [`Visit`] and [`VisitMut`] traits have one method per kind of node in
+//! the AST of a [`Module`], including one method per kind of [`Instruction`].
+//! Every method has a default implementation which recursively visits the
+//! children of that node, so implementations only need to override the
+//! methods for the nodes they're interested in.
+//!
+//! Methods for nodes with children, such as [`Visit::visit_func`], have a
+//! `walk_*` counterpart which performs the default traversal so overridden
+//! methods can continue into the children of a node. Instruction methods are
+//! named after the instruction in the text format, such as `visit_i32_add`
+//! for `i32.add` or `visit_call` for `call`.
+//!
+//! All references to other items in the module are funneled through
+//! [`Visit::visit_index`] along with the [`IndexSpace`] the index refers to.
+//!
+//! # Examples
+//!
+//! Counting the `call` instructions in a module:
+//!
+//! ```
+//! use wast::visit::Visit;
+//! use wast::Index;
+//!
+//! #[derive(Default)]
+//! struct CountCalls(usize);
+//!
+//! impl<'a> Visit<'a> for CountCalls {
+//!     fn visit_call(&mut self, _func: &Index<'a>) {
+//!         self.0 += 1;
+//!     }
+//! }
+//!
+//! # fn foo() -> Result<(), wast::Error> {
+//! let buf = wast::parser::ParseBuffer::new("(module (func $f call $f call $f))")?;
+//! let wat = wast::parser::parse::<wast::Wat>(&buf)?;
+//! let mut count = CountCalls::default();
+//! count.visit_module(&wat.module);
+//! assert_eq!(count.0, 2);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::ast::{self, *};
+
+/// The different namespaces an [`Index`] can refer to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum IndexSpace {
+    Func,
+    Table,
+    Memory,
+    Global,
+    Type,
+    Local,
+    Label,
+    Elem,
+    Data,
+}
+
+macro_rules! visitors {
+    (pub enum Instruction<'a> {
+        $(
+            $(#[$doc:meta])*
+            $name:ident $(($($arg:tt)*))? : [$($binary:tt)*] : $instr:tt $( | $deprecated:tt )? : $visit:ident
+                : $proposal:ident $($memory:ident)? $( : [$($param:ident)*] -> [$($result:ident)*] )?,
+        )*
+    }) => (
+        visitors! {
+            @define
+
+            /// A trait for traversing the AST of a module by shared
+            /// reference.
+            ///
+            /// See the [module documentation](self) for more information.
+            Visit []
+
+            $($name [] $visit $(($($arg)*))?,)*
+        }
+        visitors! {
+            @define
+
+            /// A trait for traversing and modifying the AST of a module.
+            ///
+            /// This is the same as [`Visit`] except that all nodes are
+            /// visited by mutable reference.
+            VisitMut [mut]
+
+            $($name [mut] $visit $(($($arg)*))?,)*
+        }
+    );
+
+    (
+        @define
+        $(#[$attr:meta])*
+        $trait:ident [$($m:tt)*]
+        $($name:ident $ms:tt $visit:ident $(($($arg:tt)*))?,)*
+    ) => (
+        $(#[$attr])*
+        pub trait $trait<'a> {
+            /// Visits an entire module.
+            fn visit_module(&mut self, module: &$($m)* Module<'a>) {
+                self.walk_module(module)
+            }
+
+            /// Visits each field of `module`.
+            fn walk_module(&mut self, module: &$($m)* Module<'a>) {
+                self.visit_span(&$($m)* module.span);
+                if let ModuleKind::Text(fields) = &$($m)* module.kind {
+                    for field in fields {
+                        self.visit_module_field(field);
+                    }
+                }
+            }
+
+            /// Visits one field of a module.
+            fn visit_module_field(&mut self, field: &$($m)* ModuleField<'a>) {
+                self.walk_module_field(field)
+            }
+
+            /// Dispatches to the method for the kind of `field`.
+            fn walk_module_field(&mut self, field: &$($m)* ModuleField<'a>) {
+                match field {
+                    ModuleField::Type(t) => self.visit_type(t),
+                    ModuleField::Import(i) => self.visit_import(i),
+                    ModuleField::Func(f) => self.visit_func(f),
+                    ModuleField::Table(t) => self.visit_table(t),
+                    ModuleField::Memory(m) => self.visit_memory(m),
+                    ModuleField::Global(g) => self.visit_global(g),
+                    ModuleField::Export(e) => self.visit_export(e),
+                    ModuleField::Start(i) => self.visit_start(i),
+                    ModuleField::Elem(e) => self.visit_elem(e),
+                    ModuleField::Data(d) => self.visit_data(d),
+                }
+            }
+
+            /// Visits a `type` definition.
+            fn visit_type(&mut self, ty: &$($m)* Type<'a>) {
+                let _ = ty;
+            }
+
+            /// Visits an `import` field.
+            fn visit_import(&mut self, import: &$($m)* Import<'a>) {
+                self.walk_import(import)
+            }
+
+            /// Visits the type of a function import.
+            fn walk_import(&mut self, import: &$($m)* Import<'a>) {
+                self.visit_span(&$($m)* import.span);
+                if let ImportKind::Func(ty) = &$($m)* import.kind {
+                    self.visit_type_use(ty);
+                }
+            }
+
+            /// Visits a function.
+            fn visit_func(&mut self, func: &$($m)* Func<'a>) {
+                self.walk_func(func)
+            }
+
+            /// Visits the type and body of `func`.
+            fn walk_func(&mut self, func: &$($m)* Func<'a>) {
+                self.visit_span(&$($m)* func.span);
+                self.visit_inline_export(&$($m)* func.exports);
+                self.visit_type_use(&$($m)* func.ty);
+                if let FuncKind::Inline { expression, .. } = &$($m)* func.kind {
+                    self.visit_expression(expression);
+                }
+            }
+
+            /// Visits a table.
+            fn visit_table(&mut self, table: &$($m)* Table<'a>) {
+                self.walk_table(table)
+            }
+
+            /// Visits the inline element segment of `table`, if any.
+            fn walk_table(&mut self, table: &$($m)* Table<'a>) {
+                self.visit_span(&$($m)* table.span);
+                self.visit_inline_export(&$($m)* table.exports);
+                if let TableKind::Inline { payload, .. } = &$($m)* table.kind {
+                    self.visit_elem_payload(payload);
+                }
+            }
+
+            /// Visits a memory.
+            fn visit_memory(&mut self, memory: &$($m)* Memory<'a>) {
+                self.walk_memory(memory)
+            }
+
+            /// Visits the span and inline exports of `memory`.
+            fn walk_memory(&mut self, memory: &$($m)* Memory<'a>) {
+                self.visit_span(&$($m)* memory.span);
+                self.visit_inline_export(&$($m)* memory.exports);
+            }
+
+            /// Visits a global.
+            fn visit_global(&mut self, global: &$($m)* Global<'a>) {
+                self.walk_global(global)
+            }
+
+            /// Visits the initializer of `global`, if any.
+            fn walk_global(&mut self, global: &$($m)* Global<'a>) {
+                self.visit_span(&$($m)* global.span);
+                self.visit_inline_export(&$($m)* global.exports);
+                if let GlobalKind::Inline(expr) = &$($m)* global.kind {
+                    self.visit_expression(expr);
+                }
+            }
+
+            /// Visits an `export` field.
+            fn visit_export(&mut self, export: &$($m)* Export<'a>) {
+                self.walk_export(export)
+            }
+
+            /// Visits the item exported by `export`.
+            fn walk_export(&mut self, export: &$($m)* Export<'a>) {
+                self.visit_span(&$($m)* export.span);
+                let (index, space) = match &$($m)* export.kind {
+                    ExportKind::Func(i) => (i, IndexSpace::Func),
+                    ExportKind::Table(i) => (i, IndexSpace::Table),
+                    ExportKind::Memory(i) => (i, IndexSpace::Memory),
+                    ExportKind::Global(i) => (i, IndexSpace::Global),
+                };
+                self.visit_index(index, space);
+            }
+
+            /// Visits the function of a `start` field.
+            fn visit_start(&mut self, func: &$($m)* Index<'a>) {
+                self.visit_index(func, IndexSpace::Func)
+            }
+
+            /// Visits an element segment.
+            fn visit_elem(&mut self, elem: &$($m)* Elem<'a>) {
+                self.walk_elem(elem)
+            }
+
+            /// Visits the table, offset, and payload of `elem`.
+            fn walk_elem(&mut self, elem: &$($m)* Elem<'a>) {
+                self.visit_span(&$($m)* elem.span);
+                if let ElemKind::Active { table, offset } = &$($m)* elem.kind {
+                    self.visit_index(table, IndexSpace::Table);
+                    self.visit_expression(offset);
+                }
+                self.visit_elem_payload(&$($m)* elem.payload);
+            }
+
+            /// Visits the functions listed in an element segment.
+            fn visit_elem_payload(&mut self, payload: &$($m)* ElemPayload<'a>) {
+                match payload {
+                    ElemPayload::Indices(indices) => {
+                        for index in indices {
+                            self.visit_index(index, IndexSpace::Func);
+                        }
+                    }
+                    ElemPayload::Exprs { exprs, .. } => {
+                        for index in exprs {
+                            if let Some(index) = index {
+                                self.visit_index(index, IndexSpace::Func);
+                            }
+                        }
+                    }
+                }
+            }
+
+            /// Visits a data segment.
+            fn visit_data(&mut self, data: &$($m)* Data<'a>) {
+                self.walk_data(data)
+            }
+
+            /// Visits the memory and offset of `data`.
+            fn walk_data(&mut self, data: &$($m)* Data<'a>) {
+                self.visit_span(&$($m)* data.span);
+                if let DataKind::Active { memory, offset } = &$($m)* data.kind {
+                    self.visit_index(memory, IndexSpace::Memory);
+                    self.visit_expression(offset);
+                }
+            }
+
+            /// Visits a reference to a type, such as the type of a function.
+            fn visit_type_use(&mut self, ty: &$($m)* TypeUse<'a>) {
+                if let Some(span) = &$($m)* ty.index_span {
+                    self.visit_span(span);
+                }
+                if let Some(index) = &$($m)* ty.index {
+                    self.visit_index(index, IndexSpace::Type);
+                }
+            }
+
+            /// Visits a list of instructions.
+            fn visit_expression(&mut self, expr: &$($m)* Expression<'a>) {
+                for instr in &$($m)* expr.instrs {
+                    self.visit_instruction(instr);
+                }
+            }
+
+            /// Visits an instruction.
+            fn visit_instruction(&mut self, instr: &$($m)* Instruction<'a>) {
+                self.walk_instruction(instr)
+            }
+
+            /// Dispatches to the method for the kind of `instr`.
+            #[allow(non_snake_case)]
+            fn walk_instruction(&mut self, instr: &$($m)* Instruction<'a>) {
+                match instr {
+                    $(
+                        Instruction::$name $((instructions!(@first $($arg)*)))? => {
+                            self.$visit($(instructions!(@first $($arg)*))?)
+                        }
+                    )*
+                }
+            }
+
+            /// Visits the inline `export` annotations of a definition.
+            fn visit_inline_export(&mut self, exports: &$($m)* InlineExport<'a>) {
+                for (span, _name) in &$($m)* exports.names {
+                    self.visit_span(span);
+                }
+            }
+
+            /// Visits the location of a node in the original source.
+            fn visit_span(&mut self, span: &$($m)* Span) {
+                let _ = span;
+            }
+
+            /// Visits a reference to an item in the `space` namespace.
+            fn visit_index(&mut self, index: &$($m)* Index<'a>, space: IndexSpace) {
+                let _ = (index, space);
+            }
+
+            $(visitors!(@method $ms $name $visit $(($($arg)*))?);)*
+        }
+    );
+
+    (@method $ms:tt $name:ident $visit:ident) => (
+        #[allow(missing_docs)]
+        fn $visit(&mut self) {}
+    );
+    (@method [$($m:tt)*] $name:ident $visit:ident ($($arg:tt)*)) => (
+        #[allow(missing_docs)]
+        fn $visit(&mut self, arg: &$($m)* instructions!(@ty $($arg)*)) {
+            visitors!(@walk [$($m)*] self arg $name $($arg)*);
+        }
+    );
+
+    (@walk [$($m:tt)*] $v:ident $a:ident Br $($t:tt)*) => ($v.visit_index($a, IndexSpace::Label));
+    (@walk [$($m:tt)*] $v:ident $a:ident BrIf $($t:tt)*) => ($v.visit_index($a, IndexSpace::Label));
+    (@walk [$($m:tt)*] $v:ident $a:ident Call $($t:tt)*) => ($v.visit_index($a, IndexSpace::Func));
+    (@walk [$($m:tt)*] $v:ident $a:ident ReturnCall $($t:tt)*) => ($v.visit_index($a, IndexSpace::Func));
+    (@walk [$($m:tt)*] $v:ident $a:ident RefFunc $($t:tt)*) => ($v.visit_index($a, IndexSpace::Func));
+    (@walk [$($m:tt)*] $v:ident $a:ident LocalGet $($t:tt)*) => ($v.visit_index($a, IndexSpace::Local));
+    (@walk [$($m:tt)*] $v:ident $a:ident LocalSet $($t:tt)*) => ($v.visit_index($a, IndexSpace::Local));
+    (@walk [$($m:tt)*] $v:ident $a:ident LocalTee $($t:tt)*) => ($v.visit_index($a, IndexSpace::Local));
+    (@walk [$($m:tt)*] $v:ident $a:ident GlobalGet $($t:tt)*) => ($v.visit_index($a, IndexSpace::Global));
+    (@walk [$($m:tt)*] $v:ident $a:ident GlobalSet $($t:tt)*) => ($v.visit_index($a, IndexSpace::Global));
+    (@walk [$($m:tt)*] $v:ident $a:ident TableGet $($t:tt)*) => ($v.visit_index($a, IndexSpace::Table));
+    (@walk [$($m:tt)*] $v:ident $a:ident TableSet $($t:tt)*) => ($v.visit_index($a, IndexSpace::Table));
+    (@walk [$($m:tt)*] $v:ident $a:ident TableFill $($t:tt)*) => ($v.visit_index($a, IndexSpace::Table));
+    (@walk [$($m:tt)*] $v:ident $a:ident TableSize $($t:tt)*) => ($v.visit_index($a, IndexSpace::Table));
+    (@walk [$($m:tt)*] $v:ident $a:ident TableGrow $($t:tt)*) => ($v.visit_index($a, IndexSpace::Table));
+    (@walk [$($m:tt)*] $v:ident $a:ident DataDrop $($t:tt)*) => ($v.visit_index($a, IndexSpace::Data));
+    (@walk [$($m:tt)*] $v:ident $a:ident ElemDrop $($t:tt)*) => ($v.visit_index($a, IndexSpace::Elem));
+    (@walk [$($m:tt)*] $v:ident $a:ident $name:ident ast::Index $($t:tt)*) => (
+        compile_error!(concat!("unknown index space for `", stringify!($name), "`"))
+    );
+    (@walk [$($m:tt)*] $v:ident $a:ident $name:ident BlockType $($t:tt)*) => (
+        $v.visit_type_use(&$($m)* $a.ty)
+    );
+    (@walk [$($m:tt)*] $v:ident $a:ident $name:ident BrTableIndices $($t:tt)*) => ({
+        for label in &$($m)* $a.labels {
+            $v.visit_index(label, IndexSpace::Label);
+        }
+        $v.visit_index(&$($m)* $a.default, IndexSpace::Label);
+    });
+    (@walk [$($m:tt)*] $v:ident $a:ident $name:ident CallIndirect $($t:tt)*) => ({
+        $v.visit_index(&$($m)* $a.table, IndexSpace::Table);
+        $v.visit_type_use(&$($m)* $a.ty);
+    });
+    (@walk [$($m:tt)*] $v:ident $a:ident $name:ident TableInit $($t:tt)*) => (
+        $v.visit_index(&$($m)* $a.elem, IndexSpace::Elem)
+    );
+    (@walk [$($m:tt)*] $v:ident $a:ident $name:ident MemoryInit $($t:tt)*) => (
+        $v.visit_index(&$($m)* $a.data, IndexSpace::Data)
+    );
+    (@walk [$($m:tt)*] $v:ident $a:ident $($t:tt)*) => (let _ = $a;);
+}
+
+for_each_instruction!(visitors);
@@ -0,0 +1,62 @@
+use anyhow::Context;
+use std::env;
+
+fn main() -> anyhow::Result<()> {
+    // Use the `getopts` crate to parse the `-h` and `--imports` options
+    let program = env::args().next().unwrap();
+    let mut opts = getopts::Options::new();
+    opts.optflag("h", "help", "print this help menu");
+    opts.optopt(
+        "",
+        "imports",
+        "print the imports the export NAME may call, instead of the graph",
+        "NAME",
+    );
+    let matches = opts.parse(env::args_os().skip(1))?;
+    if matches.opt_present("h") {
+        print_usage(&program, opts);
+        return Ok(());
+    }
+    let input = match matches.free.len() {
+        0 => {
+            print_usage(&program, opts);
+            std::process::exit(1);
+        }
+        1 => &matches.free[0],
+        _ => anyhow::bail!("more than one input file specified on command line"),
+    };
+
+    // Read a binary module, or parse a text one...
+    let contents = std::fs::read(input).context(format!("failed to read: {}", input))?;
+    let text;
+    let buf;
+    let module = if contents.starts_with(b"\0asm") {
+        wast::Module::decode(&contents)?
+    } else {
+        text = String::from_utf8(contents).context("input file isn't utf-8")?;
+        buf = wast::parser::ParseBuffer::new(&text)?;
+        let mut module = wast::parser::parse::<wast::Wat>(&buf)?.module;
+        module.resolve()?;
+        module
+    };
+
+    // ... and print out its call graph.
+    let graph = module.call_graph()?;
+    match matches.opt_str("imports") {
+        Some(export) => match graph.reachable_imports(&export) {
+            Some(imports) => {
+                for (module, name) in imports {
+                    println!("{} {}", module, name);
+                }
+            }
+            None => anyhow::bail!("no function is exported as `{}`", export),
+        },
+        None => print!("{}", graph.to_dot()),
+    }
+    Ok(())
+}
+
+fn print_usage(program: &str, opts: getopts::Options) {
+    let brief = format!("Usage: {} FILE [options]", program);
+    print!("{}", opts.usage(&brief));
+}
@@ -0,0 +1,61 @@
+use anyhow::Context;
+use std::env;
+
+fn main() -> anyhow::Result<()> {
+    // Use the `getopts` crate to parse the `-h` and `--func` options
+    let program = env::args().next().unwrap();
+    let mut opts = getopts::Options::new();
+    opts.optflag("h", "help", "print this help menu");
+    opts.optopt(
+        "",
+        "func",
+        "only print the graph of the function with index or name FUNC",
+        "FUNC",
+    );
+    let matches = opts.parse(env::args_os().skip(1))?;
+    if matches.opt_present("h") {
+        print_usage(&program, opts);
+        return Ok(());
+    }
+    let input = match matches.free.len() {
+        0 => {
+            print_usage(&program, opts);
+            std::process::exit(1);
+        }
+        1 => &matches.free[0],
+        _ => anyhow::bail!("more than one input file specified on command line"),
+    };
+
+    // Read a binary module, or parse a text one...
+    let contents = std::fs::read(input).context(format!("failed to read: {}", input))?;
+    let text;
+    let buf;
+    let module = if contents.starts_with(b"\0asm") {
+        wast::Module::decode(&contents)?
+    } else {
+        text = String::from_utf8(contents).context("input file isn't utf-8")?;
+        buf = wast::parser::ParseBuffer::new(&text)?;
+        let mut module = wast::parser::parse::<wast::Wat>(&buf)?.module;
+        module.resolve()?;
+        module
+    };
+
+    // ... and print out the control-flow graph of each function.
+    let mut cfgs = module.cfgs()?;
+    if let Some(func) = matches.opt_str("func") {
+        let name = func.trim_start_matches('$');
+        cfgs.retain(|cfg| cfg.func().to_string() == func || cfg.name() == Some(name));
+        if cfgs.is_empty() {
+            anyhow::bail!("no function body is numbered or named `{}`", func);
+        }
+    }
+    for cfg in cfgs {
+        print!("{}", cfg.to_dot());
+    }
+    Ok(())
+}
+
+fn print_usage(program: &str, opts: getopts::Options) {
+    let brief = format!("Usage: {} FILE [options]", program);
+    print!("{}", opts.usage(&brief));
+}
@@ -0,0 +1,48 @@
+use anyhow::Context;
+use std::env;
+
+fn main() -> anyhow::Result<()> {
+    // Use the `getopts` crate to parse the `-h` option
+    let program = env::args().next().unwrap();
+    let mut opts = getopts::Options::new();
+    opts.optflag("h", "help", "print this help menu");
+    let matches = opts.parse(env::args_os().skip(1))?;
+    if matches.opt_present("h") {
+        print_usage(&program, opts);
+        return Ok(());
+    }
+    let (expected, actual) = match matches.free.len() {
+        2 => (&matches.free[0], &matches.free[1]),
+        _ => {
+            print_usage(&program, opts);
+            std::process::exit(1);
+        }
+    };
+
+    // Compare the two modules, exiting with an error if they differ.
+    let expected = read(expected)?;
+    let actual = read(actual)?;
+    let differences = wast::Module::diff(&expected, &actual)?;
+    for difference in differences.iter() {
+        println!("{}", difference);
+    }
+    if !differences.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Reads a binary module, or parses a text one into a binary.
+fn read(file: &str) -> anyhow::Result<Vec<u8>> {
+    let contents = std::fs::read(file).context(format!("failed to read: {}", file))?;
+    if contents.starts_with(b"\0asm") {
+        Ok(contents)
+    } else {
+        Ok(wat::parse_file(file)?)
+    }
+}
+
+fn print_usage(program: &str, opts: getopts::Options) {
+    let brief = format!("Usage: {} EXPECTED ACTUAL [options]", program);
+    print!("{}", opts.usage(&brief));
+}
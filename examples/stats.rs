@@ -0,0 +1,45 @@
+use anyhow::Context;
+use std::env;
+
+fn main() -> anyhow::Result<()> {
+    // Use the `getopts` crate to parse the `-h` and `--json` options
+    let program = env::args().next().unwrap();
+    let mut opts = getopts::Options::new();
+    opts.optflag("h", "help", "print this help menu");
+    opts.optflag("", "json", "print the statistics as JSON");
+    let matches = opts.parse(env::args_os().skip(1))?;
+    if matches.opt_present("h") {
+        print_usage(&program, opts);
+        return Ok(());
+    }
+    let input = match matches.free.len() {
+        0 => {
+            print_usage(&program, opts);
+            std::process::exit(1);
+        }
+        1 => &matches.free[0],
+        _ => anyhow::bail!("more than one input file specified on command line"),
+    };
+
+    // Read a binary module, or parse a text one into a binary...
+    let contents = std::fs::read(input).context(format!("failed to read: {}", input))?;
+    let binary = if contents.starts_with(b"\0asm") {
+        contents
+    } else {
+        wat::parse_file(input)?
+    };
+
+    // ... and report where its bytes go.
+    let stats = wast::Module::stats(&binary)?;
+    if matches.opt_present("json") {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        print!("{}", stats);
+    }
+    Ok(())
+}
+
+fn print_usage(program: &str, opts: getopts::Options) {
+    let brief = format!("Usage: {} FILE [options]", program);
+    print!("{}", opts.usage(&brief));
+}
@@ -15,7 +15,8 @@ fuzz_target!(|data: &[u8]| {
     let wasm = td.path().join("foo.wasm");
     std::fs::write(&wat, &s).unwrap();
 
-    match wat::parse_str(s) {
+    // see comments in the test suite for why we don't emit the name section
+    match wat::Parser::new().generate_name_section(false).parse_str(s) {
         // If we succesfully parsed the binary, then we want to make sure that
         // `wabt` agrees on the binary encoding of the input wat file.
         Ok(binary) => {
@@ -35,9 +36,7 @@ fuzz_target!(|data: &[u8]| {
                 .unwrap();
             if output.status.success() {
                 let wabt_bytes = std::fs::read(&wasm).unwrap();
-                // see comments in the test suite for why we remove the name
-                // section
-                assert_eq!(wast_fuzz::remove_name_section(&binary), wabt_bytes);
+                assert_eq!(binary, wabt_bytes);
             }
         }
 
@@ -0,0 +1,116 @@
+#![no_main]
+
+use libfuzzer_sys::*;
+use std::str;
+use wast::{Func, FuncKind, Index, Instruction, Module, ModuleField, ModuleKind};
+
+fuzz_target!(|data: &[u8]| {
+    let s = match str::from_utf8(data) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    let buf = match wast::parser::ParseBuffer::new(s) {
+        Ok(b) => b,
+        Err(_) => return,
+    };
+    let mut module = match wast::parser::parse::<wast::Wat>(&buf) {
+        Ok(wat) => wat.module,
+        Err(_) => return,
+    };
+
+    // Only valid modules are optimized, and the rewrites they go through must
+    // leave them valid.
+    if module.resolve().is_err() || module.validate().is_err() {
+        return;
+    }
+    let before = module.clone();
+    module.optimize().unwrap();
+    if let Err(e) = module.validate() {
+        panic!("optimized module is invalid: {}", e);
+    }
+    module.encode().unwrap();
+
+    // Without running the module, check that each function keeps its type
+    // and the structure and types of its blocks, and that bodies without
+    // control flow compute the same values as before.
+    for (before, after) in funcs(&before).zip(funcs(&module)) {
+        assert_eq!(before.ty, after.ty);
+        assert_eq!(blocks(before), blocks(after));
+        if let Some(expected) = eval(before) {
+            assert_eq!(Some(expected), eval(after));
+        }
+    }
+});
+
+fn funcs<'a, 'b>(module: &'b Module<'a>) -> impl Iterator<Item = &'b Func<'a>> {
+    let fields = match &module.kind {
+        ModuleKind::Text(fields) => &fields[..],
+        ModuleKind::Binary(_) => &[],
+    };
+    fields.iter().filter_map(|f| match f {
+        ModuleField::Func(f) => Some(f),
+        _ => None,
+    })
+}
+
+/// Returns the instructions which delimit the blocks of `func`.
+fn blocks<'a, 'b>(func: &'b Func<'a>) -> Vec<&'b Instruction<'a>> {
+    let instrs = match &func.kind {
+        FuncKind::Inline { expression, .. } => &expression.instrs[..],
+        FuncKind::Import { .. } => &[],
+    };
+    instrs
+        .iter()
+        .filter(|i| match i {
+            Instruction::Block(_)
+            | Instruction::Loop(_)
+            | Instruction::If(_)
+            | Instruction::Else(_)
+            | Instruction::End(_) => true,
+            _ => false,
+        })
+        .collect()
+}
+
+/// Runs the body of `func` with every parameter and local set to zero,
+/// returning the final operand stack and locals, or `None` if the body uses
+/// an instruction other than constants, `i32` arithmetic, locals, `nop`, and
+/// `drop`.
+fn eval(func: &Func<'_>) -> Option<(Vec<i64>, Vec<i64>)> {
+    let (locals, expression) = match &func.kind {
+        FuncKind::Inline { locals, expression } => (locals, expression),
+        FuncKind::Import { .. } => return None,
+    };
+    let mut locals = vec![0; func.ty.ty.params.len() + locals.len()];
+    let mut stack = Vec::new();
+    let local = |index: &Index<'_>| match index {
+        Index::Num(n) => *n as usize,
+        Index::Id(_) => unreachable!("the module is resolved"),
+    };
+    for instr in expression.instrs.iter() {
+        match instr {
+            Instruction::I32Const(n) => stack.push(*n as i64),
+            Instruction::I64Const(n) => stack.push(*n),
+            Instruction::F32Const(f) => stack.push(f.bits as i64),
+            Instruction::F64Const(f) => stack.push(f.bits as i64),
+            Instruction::I32Add | Instruction::I32Sub | Instruction::I32Mul => {
+                let b = stack.pop()? as i32;
+                let a = stack.pop()? as i32;
+                stack.push(match instr {
+                    Instruction::I32Add => a.wrapping_add(b),
+                    Instruction::I32Sub => a.wrapping_sub(b),
+                    _ => a.wrapping_mul(b),
+                } as i64);
+            }
+            Instruction::Nop => {}
+            Instruction::Drop => {
+                stack.pop()?;
+            }
+            Instruction::LocalGet(i) => stack.push(locals[local(i)]),
+            Instruction::LocalSet(i) => locals[local(i)] = stack.pop()?,
+            Instruction::LocalTee(i) => locals[local(i)] = *stack.last()?,
+            _ => return None,
+        }
+    }
+    Some((stack, locals))
+}
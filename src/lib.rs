@@ -44,12 +44,14 @@
 //! WAT files.
 //!
 //! Future WebAssembly features will be accepted to this parser **and they will
-//! not require a feature gate to opt-in**. All implemented WebAssembly features
-//! will be enabled at all times. Using a future WebAssembly feature in the WAT
-//! format may cause breakage because while specifications are in development
-//! the WAT syntax (and/or binary encoding) will often change. This crate will
-//! do its best to keep up with these proposals, but breaking textual changes
-//! will be published as non-breaking semver changes to this crate.
+//! not require a Cargo feature gate to opt-in**. All implemented WebAssembly
+//! features are enabled by default. If your target only supports a subset of
+//! proposals, [`Parser::features`] can be used to reject modules which use
+//! anything else. Using a future WebAssembly feature in the WAT format may
+//! cause breakage because while specifications are in development the WAT
+//! syntax (and/or binary encoding) will often change. This crate will do its
+//! best to keep up with these proposals, but breaking textual changes will be
+//! published as non-breaking semver changes to this crate.
 //!
 //! ## Stability
 //!
@@ -73,6 +75,8 @@ use std::path::Path;
 use std::str;
 use wast::parser::{self, ParseBuffer};
 
+pub use wast::WasmFeatures;
+
 /// Parses a file on disk as a [WebAssembly Text format][wat] file, or a binary
 /// WebAssembly file
 ///
@@ -96,25 +100,7 @@ use wast::parser::{self, ParseBuffer};
 ///
 /// [wat]: http://webassembly.github.io/spec/core/text/index.html
 pub fn parse_file(file: impl AsRef<Path>) -> Result<Vec<u8>> {
-    _parse_file(file.as_ref())
-}
-
-fn _parse_file(file: &Path) -> Result<Vec<u8>> {
-    let contents = std::fs::read(file).map_err(|err| Error {
-        kind: Box::new(ErrorKind::Io {
-            err,
-            msg: format!("failed to read `{}` to a string", file.display()),
-        }),
-    })?;
-    match parse_bytes(&contents) {
-        Ok(bytes) => Ok(bytes.into_owned()),
-        Err(mut e) => {
-            if let ErrorKind::Wast(e) = &mut *e.kind {
-                e.set_path(file);
-            }
-            Err(e)
-        }
-    }
+    Parser::new().parse_file(file)
 }
 
 /// Parses in-memory bytes as either the [WebAssembly Text format][wat], or a
@@ -157,15 +143,7 @@ fn _parse_file(file: &Path) -> Result<Vec<u8>> {
 ///
 /// [wat]: http://webassembly.github.io/spec/core/text/index.html
 pub fn parse_bytes(bytes: &[u8]) -> Result<Cow<'_, [u8]>> {
-    if bytes.starts_with(b"\0asm") {
-        return Ok(bytes.into());
-    }
-    match str::from_utf8(bytes) {
-        Ok(s) => _parse_str(s).map(|s| s.into()),
-        Err(_) => Err(Error {
-            kind: Box::new(ErrorKind::Custom(format!("input bytes aren't valid utf-8"))),
-        }),
-    }
+    Parser::new().parse_bytes(bytes)
 }
 
 /// Parses an in-memory string as the [WebAssembly Text format][wat], returning
@@ -211,13 +189,187 @@ pub fn parse_bytes(bytes: &[u8]) -> Result<Cow<'_, [u8]>> {
 ///
 /// [wat]: http://webassembly.github.io/spec/core/text/index.html
 pub fn parse_str(wat: impl AsRef<str>) -> Result<Vec<u8>> {
-    _parse_str(wat.as_ref())
+    Parser::new().parse_str(wat)
+}
+
+/// A configurable parser for the [WebAssembly Text format][wat].
+///
+/// The [`parse_file`], [`parse_bytes`], and [`parse_str`] functions use the
+/// default configuration, and this builder can be used to change how modules
+/// are checked and encoded.
+///
+/// # Examples
+///
+/// ```
+/// # fn foo() -> wat::Result<()> {
+/// let mut features = wat::WasmFeatures::mvp();
+/// features.sign_extension(true);
+///
+/// let binary = wat::Parser::new()
+///     .generate_name_section(false)
+///     .validate(true)
+///     .features(features)
+///     .parse_str("(module (func $f (result i32) i32.const 1))")?;
+/// // ...
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [wat]: http://webassembly.github.io/spec/core/text/index.html
+#[derive(Clone, Debug)]
+pub struct Parser {
+    generate_name_section: bool,
+    validate: bool,
+    features: WasmFeatures,
+    check_binary: bool,
+}
+
+impl Default for Parser {
+    fn default() -> Parser {
+        Parser {
+            generate_name_section: true,
+            validate: false,
+            features: WasmFeatures::default(),
+            check_binary: false,
+        }
+    }
 }
 
-fn _parse_str(wat: &str) -> Result<Vec<u8>> {
-    let buf = ParseBuffer::new(&wat).map_err(|e| Error::cvt(e, wat))?;
-    let mut ast = parser::parse::<wast::Wat>(&buf).map_err(|e| Error::cvt(e, wat))?;
-    Ok(ast.module.encode().map_err(|e| Error::cvt(e, wat))?)
+impl Parser {
+    /// Creates a new parser with the default configuration.
+    pub fn new() -> Parser {
+        Parser::default()
+    }
+
+    /// Configures whether the `name` custom section is emitted, recording the
+    /// `$identifiers` of the text file for debuggers.
+    ///
+    /// This is enabled by default.
+    pub fn generate_name_section(&mut self, generate: bool) -> &mut Parser {
+        self.generate_name_section = generate;
+        self
+    }
+
+    /// Configures whether modules are type checked after they're parsed.
+    ///
+    /// Without validation a text file which parses successfully is encoded
+    /// even if, for example, a function body pops a value of the wrong type,
+    /// producing a binary that engines will reject. This is disabled by
+    /// default.
+    ///
+    /// Binary input to [`Parser::parse_bytes`] and [`Parser::parse_file`],
+    /// and `(module binary ...)` modules in text files, are decoded in order
+    /// to be validated. Errors for them report the byte offset of the
+    /// problem, and binary modules which can't be decoded, such as those with
+    /// declarative element segments, are rejected.
+    pub fn validate(&mut self, validate: bool) -> &mut Parser {
+        self.validate = validate;
+        self
+    }
+
+    /// Configures which WebAssembly proposals text files may use.
+    ///
+    /// Using an instruction, type, or section from a proposal which isn't
+    /// enabled in `features` is an error. All proposals are enabled by default.
+    ///
+    /// Binary modules are checked too, and are decoded in the same way as
+    /// with [`Parser::validate`] when any proposal is disabled.
+    pub fn features(&mut self, features: WasmFeatures) -> &mut Parser {
+        self.features = features;
+        self
+    }
+
+    /// Configures whether the structure of binary input to
+    /// [`Parser::parse_bytes`] and [`Parser::parse_file`] is checked, along
+    /// with `(module binary ...)` modules in text files.
+    ///
+    /// This checks the header and version, section ids and ordering, LEB128
+    /// integers, and that section sizes match their contents. Errors for
+    /// binary input report the byte offset of the problem. By default anything
+    /// which starts with `b"\0asm"` is returned verbatim.
+    pub fn check_binary(&mut self, check: bool) -> &mut Parser {
+        self.check_binary = check;
+        self
+    }
+
+    /// Same as the [`parse_file`] function, except with this parser's
+    /// configuration.
+    pub fn parse_file(&self, file: impl AsRef<Path>) -> Result<Vec<u8>> {
+        self._parse_file(file.as_ref())
+    }
+
+    fn _parse_file(&self, file: &Path) -> Result<Vec<u8>> {
+        let contents = std::fs::read(file).map_err(|err| Error {
+            kind: Box::new(ErrorKind::Io {
+                err,
+                msg: format!("failed to read `{}` to a string", file.display()),
+            }),
+        })?;
+        match self.parse_bytes(&contents) {
+            Ok(bytes) => Ok(bytes.into_owned()),
+            Err(mut e) => {
+                if let ErrorKind::Wast(e) = &mut *e.kind {
+                    e.set_path(file);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Same as the [`parse_bytes`] function, except with this parser's
+    /// configuration.
+    pub fn parse_bytes<'a>(&self, bytes: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+        if bytes.starts_with(b"\0asm") {
+            let cvt = |e| Error {
+                kind: Box::new(ErrorKind::Wast(e)),
+            };
+            if self.check_binary {
+                wast::Module::check_binary(bytes).map_err(cvt)?;
+            }
+            if self.validate || self.features != WasmFeatures::default() {
+                let mut module = wast::Module::decode(bytes).map_err(cvt)?;
+                let mut options = wast::EncodeOptions::new();
+                options.features(self.features.clone());
+                module.encode_with(&options).map_err(cvt)?;
+                if self.validate {
+                    module.validate_with(&self.features).map_err(cvt)?;
+                }
+            }
+            return Ok(bytes.into());
+        }
+        match str::from_utf8(bytes) {
+            Ok(s) => self._parse_str(s).map(|s| s.into()),
+            Err(_) => Err(Error {
+                kind: Box::new(ErrorKind::Custom(format!("input bytes aren't valid utf-8"))),
+            }),
+        }
+    }
+
+    /// Same as the [`parse_str`] function, except with this parser's
+    /// configuration.
+    pub fn parse_str(&self, wat: impl AsRef<str>) -> Result<Vec<u8>> {
+        self._parse_str(wat.as_ref())
+    }
+
+    fn _parse_str(&self, wat: &str) -> Result<Vec<u8>> {
+        let buf = ParseBuffer::new(&wat).map_err(|e| Error::cvt(e, wat))?;
+        let mut ast = parser::parse::<wast::Wat>(&buf).map_err(|e| Error::cvt(e, wat))?;
+        let mut options = wast::EncodeOptions::new();
+        options
+            .name_section(self.generate_name_section)
+            .features(self.features.clone())
+            .check_binary(self.check_binary);
+        let binary = ast
+            .module
+            .encode_with(&options)
+            .map_err(|e| Error::cvt(e, wat))?;
+        if self.validate {
+            ast.module
+                .validate_with(&self.features)
+                .map_err(|e| Error::cvt(e, wat))?;
+        }
+        Ok(binary)
+    }
 }
 
 /// A convenience type definition for `Result` where the error is [`Error`]
@@ -0,0 +1,52 @@
+use wast::*;
+
+#[test]
+fn matches_text() -> anyhow::Result<()> {
+    let mut builder = ModuleBuilder::new();
+    let log = builder.import_func("env", "log", &[ValType::I32], &[]);
+    let memory = builder.memory(MemoryType {
+        limits: Limits { min: 1, max: None },
+        shared: false,
+    });
+    let counter = builder.global(
+        GlobalType {
+            ty: ValType::I32,
+            mutable: true,
+        },
+        vec![Instruction::I32Const(0)],
+    );
+    builder
+        .func("run", &[ValType::I32], &[ValType::I32])
+        .local(ValType::I32)
+        .instr(Instruction::LocalGet(Index::Num(0)))
+        .instr(Instruction::LocalTee(Index::Num(1)))
+        .instr(Instruction::Call(log))
+        .instr(Instruction::GlobalGet(counter))
+        .instr(Instruction::LocalGet(Index::Num(1)))
+        .instr(Instruction::I32Add)
+        .export("run")
+        .finish();
+    builder.export("memory", ExportKind::Memory(memory.clone()));
+    builder.data(memory, vec![Instruction::I32Const(8)], b"hello");
+    let actual = builder.build().encode()?;
+
+    let expected = wat::parse_str(
+        r#"
+        (module
+          (import "env" "log" (func (param i32)))
+          (memory 1)
+          (global (mut i32) i32.const 0)
+          (func $run (export "run") (param i32) (result i32) (local i32)
+            local.get 0
+            local.tee 1
+            call 0
+            global.get 0
+            local.get 1
+            i32.add)
+          (export "memory" (memory 0))
+          (data (i32.const 8) "hello"))
+        "#,
+    )?;
+    assert_eq!(actual, expected);
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use wast::parser::{self, ParseBuffer};
+use wast::{CallGraph, CallKind, Edge, Wat};
+
+const TEXT: &str = r#"
+    (module
+        (import "env" "log" (func $log (param i32)))
+        (import "env" "abort" (func $abort))
+        (type $unary (func (param i32) (result i32)))
+        (table 3 funcref)
+        (elem (i32.const 0) $double $triple $noop)
+        (func $double (type $unary) (i32.mul (local.get 0) (i32.const 2)))
+        (func $triple (type $unary)
+            (call $log (local.get 0))
+            (i32.mul (local.get 0) (i32.const 3)))
+        (func $noop)
+        (func $fail (call $abort))
+        (func $apply (export "apply") (param i32 i32) (result i32)
+            (call_indirect (type $unary) (local.get 1) (local.get 0)))
+        (func $main (export "main")
+            (drop (call $apply (i32.const 1) (i32.const 0)))
+            (return_call $fail)))
+"#;
+
+fn call_graph(text: &str) -> Result<CallGraph, wast::Error> {
+    let buf = ParseBuffer::new(text)?;
+    let mut module = parser::parse::<Wat>(&buf)?.module;
+    module.resolve()?;
+    module.call_graph()
+}
+
+fn edge(caller: u32, callee: u32, kind: CallKind) -> Edge {
+    Edge {
+        caller,
+        callee,
+        kind,
+    }
+}
+
+#[test]
+fn direct_and_indirect_edges() {
+    let graph = call_graph(TEXT).unwrap();
+    assert_eq!(
+        graph.edges(),
+        [
+            edge(3, 0, CallKind::Direct),
+            edge(5, 1, CallKind::Direct),
+            edge(6, 2, CallKind::Indirect),
+            edge(6, 3, CallKind::Indirect),
+            edge(7, 5, CallKind::Direct),
+            edge(7, 6, CallKind::Direct),
+        ]
+    );
+    assert_eq!(graph.reachable(6), [0, 2, 3, 6]);
+}
+
+#[test]
+fn reachable_imports() {
+    let graph = call_graph(TEXT).unwrap();
+    assert_eq!(graph.reachable_imports("apply"), Some(vec![("env", "log")]));
+    assert_eq!(
+        graph.reachable_imports("main"),
+        Some(vec![("env", "log"), ("env", "abort")])
+    );
+    assert_eq!(graph.reachable_imports("missing"), None);
+}
+
+#[test]
+fn passive_segments_may_fill_any_table() {
+    let graph = call_graph(
+        r#"
+        (module
+            (table $t 1 funcref)
+            (elem funcref (ref.func $a))
+            (func $a)
+            (func $b (call_indirect $t (i32.const 0))))
+        "#,
+    )
+    .unwrap();
+    assert_eq!(graph.edges(), [edge(1, 0, CallKind::Indirect)]);
+}
+
+#[test]
+fn recursion_terminates() {
+    let graph = call_graph(
+        r#"
+        (module
+            (import "env" "f" (func $f))
+            (func $a (export "a") (call $b))
+            (func $b (call $a) (call $f)))
+        "#,
+    )
+    .unwrap();
+    assert_eq!(graph.reachable(1), [0, 1, 2]);
+    assert_eq!(graph.reachable_imports("a"), Some(vec![("env", "f")]));
+}
+
+#[test]
+fn dot() {
+    let graph = call_graph(
+        r#"
+        (module
+            (import "env" "log" (func $log))
+            (table 1 funcref)
+            (elem (i32.const 0) 1)
+            (func (export "run") (call $log) (call_indirect (i32.const 0))))
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        graph.to_dot(),
+        r#"digraph {
+    f0 [label="$log\n(import \"env\" \"log\")", shape=box];
+    f1 [label="func 1\n(export \"run\")"];
+    f1 -> f0;
+    f1 -> f1 [style=dashed];
+}
+"#
+    );
+}
+
+#[test]
+fn invalid_modules_are_rejected() {
+    let err = call_graph("(module (func (call 1)))").unwrap_err();
+    assert!(err.to_string().contains("unknown function 1"), "{}", err);
+}
+
+#[test]
+fn shared_and_written_tables_may_hold_any_function() {
+    let tables = [
+        (r#"(import "env" "t" (table 1 funcref))"#, ""),
+        (r#"(table (export "t") 1 funcref)"#, ""),
+        (
+            "(table 1 funcref)",
+            "(table.set 0 (i32.const 0) (ref.func $a))",
+        ),
+    ];
+    for (table, set) in tables.iter() {
+        let text = format!(
+            r#"
+            (module
+                {}
+                (func $a)
+                (func $b (param i32))
+                (func $c (result i32)
+                    {}
+                    (call_indirect (i32.const 0))
+                    (i32.const 0)))
+            "#,
+            table, set
+        );
+        let graph = call_graph(&text).unwrap();
+        assert_eq!(graph.edges(), [edge(2, 0, CallKind::Indirect)], "{}", table);
+    }
+}
@@ -0,0 +1,161 @@
+use wast::parser::{self, ParseBuffer};
+use wast::{Cfg, CfgEdge, FlowKind, Wat};
+
+fn cfgs(text: &str) -> Result<Vec<Cfg>, wast::Error> {
+    let buf = ParseBuffer::new(text)?;
+    let mut module = parser::parse::<Wat>(&buf)?.module;
+    module.resolve()?;
+    module.cfgs()
+}
+
+fn edge(from: usize, to: usize, kind: FlowKind) -> CfgEdge {
+    CfgEdge { from, to, kind }
+}
+
+fn ranges(cfg: &Cfg) -> Vec<std::ops::Range<usize>> {
+    cfg.blocks().iter().map(|b| b.range.clone()).collect()
+}
+
+#[test]
+fn loop_back_edge() {
+    let cfgs = cfgs(
+        r#"
+        (module
+            (import "env" "tick" (func))
+            (func $countdown (param i32)
+                (loop $top
+                    (local.set 0 (i32.sub (local.get 0) (i32.const 1)))
+                    (br_if $top (local.get 0)))))
+        "#,
+    )
+    .unwrap();
+    assert_eq!(cfgs.len(), 1);
+    let cfg = &cfgs[0];
+    assert_eq!(cfg.func(), 1);
+    assert_eq!(cfg.name(), Some("countdown"));
+    assert_eq!(ranges(cfg), [0..7, 7..8, 8..8]);
+    assert_eq!(cfg.exit(), 2);
+    assert_eq!(
+        cfg.edges(),
+        [
+            edge(0, 0, FlowKind::Branch),
+            edge(0, 1, FlowKind::Fallthrough),
+            edge(1, 2, FlowKind::Fallthrough),
+        ]
+    );
+}
+
+#[test]
+fn if_else() {
+    let cfgs = cfgs(
+        r#"
+        (module
+            (func (param i32) (result i32)
+                (if (result i32) (local.get 0)
+                    (then (i32.const 1))
+                    (else (i32.const 2)))))
+        "#,
+    )
+    .unwrap();
+    let cfg = &cfgs[0];
+    assert_eq!(ranges(cfg), [0..2, 2..4, 4..5, 5..6, 6..6]);
+    assert_eq!(
+        cfg.edges(),
+        [
+            edge(0, 1, FlowKind::Fallthrough),
+            edge(0, 2, FlowKind::Branch),
+            edge(1, 3, FlowKind::Branch),
+            edge(2, 3, FlowKind::Fallthrough),
+            edge(3, 4, FlowKind::Fallthrough),
+        ]
+    );
+    let successors = cfg.successors(0).map(|e| e.to).collect::<Vec<_>>();
+    assert_eq!(successors, [1, 2]);
+}
+
+#[test]
+fn br_table_return_and_unreachable() {
+    let cfgs = cfgs(
+        r#"
+        (module
+            (func (param i32)
+                (block $outer
+                    (block $inner
+                        (br_table $inner $outer 2 (local.get 0)))
+                    (return))
+                (unreachable)))
+        "#,
+    )
+    .unwrap();
+    let cfg = &cfgs[0];
+    assert_eq!(ranges(cfg), [0..4, 4..6, 6..8, 8..8]);
+    assert_eq!(
+        cfg.edges(),
+        [
+            edge(0, 1, FlowKind::Branch),
+            edge(0, 2, FlowKind::Branch),
+            edge(0, 3, FlowKind::Branch),
+            edge(1, 3, FlowKind::Branch),
+        ]
+    );
+}
+
+#[test]
+fn empty_body() {
+    let cfgs = cfgs("(module (func) (func (br 0)))").unwrap();
+    assert_eq!(ranges(&cfgs[0]), vec![0..0]);
+    assert!(cfgs[0].edges().is_empty());
+    assert_eq!(ranges(&cfgs[1]), [0..1, 1..1]);
+    assert_eq!(cfgs[1].edges(), [edge(0, 1, FlowKind::Branch)]);
+    assert_eq!(cfgs[1].func(), 1);
+}
+
+#[test]
+fn spans() {
+    let text = "(module (func) (func $f nop (block $b (br $b) nop) nop))";
+    let cfgs = cfgs(text).unwrap();
+    assert_eq!(ranges(&cfgs[1]), [0..3, 3..4, 4..6, 6..6]);
+    let at = |block: usize| {
+        let (_, col) = cfgs[1].blocks()[block].span.linecol_in(text);
+        &text[col..][..5]
+    };
+    assert_eq!(at(0), "func ");
+    assert_eq!(at(1), "$b (b");
+    assert_eq!(at(2), "$b (b");
+    assert_eq!(at(3), "func ");
+}
+
+#[test]
+fn dot() {
+    let cfgs = cfgs(
+        r#"
+        (module
+            (func $f (param i32)
+                (block
+                    (br_if 0 (local.get 0))
+                    (call $f (i32.const 1)))))
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        cfgs[0].to_dot(),
+        r#"digraph "$f" {
+    node [shape=box];
+    b0 [label="block\llocal.get 0\lbr_if 0\l"];
+    b1 [label="i32.const 1\lcall 0\l"];
+    b2 [label="end\l"];
+    b3 [label="exit\l"];
+    b0 -> b1;
+    b0 -> b2 [style=dashed];
+    b1 -> b2;
+    b2 -> b3;
+}
+"#
+    );
+}
+
+#[test]
+fn invalid_modules_are_rejected() {
+    let err = cfgs("(module (func (result i32)))").unwrap_err();
+    assert!(err.to_string().contains("type mismatch"), "{}", err);
+}
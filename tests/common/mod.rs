@@ -0,0 +1,31 @@
+//! Helpers shared by the tests of passes which rewrite modules.
+
+#![allow(dead_code)]
+
+use wast::parser::{self, ParseBuffer};
+use wast::{Module, Wat};
+
+/// Parses the module `text` and rewrites it with `transform`, asserting that
+/// the result is valid and encodes to the same module as `expected`.
+///
+/// Returns whatever `transform` returned.
+pub fn assert_transformed<T>(
+    text: &str,
+    expected: &str,
+    transform: impl FnOnce(&mut Module<'_>) -> Result<T, wast::Error>,
+) -> T {
+    let buf = ParseBuffer::new(text).unwrap();
+    let mut module = parser::parse::<Wat>(&buf).unwrap().module;
+    let result = transform(&mut module).unwrap();
+    module.validate().unwrap();
+    assert_encodes(&module.encode().unwrap(), expected);
+    result
+}
+
+/// Asserts that the binary module `actual` is the same as the text module
+/// `expected`, apart from custom sections.
+pub fn assert_encodes(actual: &[u8], expected: &str) {
+    let expected = wat::parse_str(expected).unwrap();
+    let differences = Module::diff(&expected, actual).unwrap();
+    assert!(differences.is_empty(), "{:#?}", differences);
+}
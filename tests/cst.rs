@@ -0,0 +1,57 @@
+use std::fs;
+use wast::cst::SyntaxTree;
+use wast::parser::ParseBuffer;
+
+#[test]
+fn roundtrip_files() -> anyhow::Result<()> {
+    for dir in ["tests/regression", "tests/parse-fail"].iter() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("wat") {
+                continue;
+            }
+            let contents = fs::read_to_string(&path)?;
+            let buf = match ParseBuffer::new(&contents) {
+                Ok(buf) => buf,
+                Err(_) => continue,
+            };
+            // Files with syntax errors may or may not have a syntax tree, but
+            // every file which parses must have one.
+            let parses = wast::parser::parse::<wast::Wat>(&ParseBuffer::new(&contents)?).is_ok();
+            match SyntaxTree::new(&buf) {
+                Ok(tree) => assert_eq!(tree.to_string(), contents, "{}", path.display()),
+                Err(e) if parses => panic!("{}: {}", path.display(), e),
+                Err(_) => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn rename_preserves_everything_else() -> anyhow::Result<()> {
+    let wat = r#"
+(module
+  ;; the entry point
+  (func $f (param $x i32) (result i32)
+    (i32.add (get_local $x) (i32.const 1)))  (; folded ;)
+  (table 1 anyfunc)
+  (export "f" (func $f)))
+"#;
+    let buf = ParseBuffer::new(wat)?;
+    let mut tree = SyntaxTree::new(&buf)?;
+    tree.for_each_atom_mut(|atom| {
+        if atom.text == "$x" {
+            atom.text = "$input".into();
+        }
+    });
+    assert_eq!(tree.to_string(), wat.replace("$x", "$input"));
+    Ok(())
+}
+
+#[test]
+fn unbalanced() -> anyhow::Result<()> {
+    assert!(SyntaxTree::new(&ParseBuffer::new("(module")?).is_err());
+    assert!(SyntaxTree::new(&ParseBuffer::new("(module))")?).is_err());
+    Ok(())
+}
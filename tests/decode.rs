@@ -0,0 +1,155 @@
+use wast::{FuncKind, Instruction, Module, ModuleField, ModuleKind};
+
+fn round_trip(text: &str) -> Vec<u8> {
+    let wasm = wat::parse_str(text).unwrap();
+    let mut module = Module::decode(&wasm).unwrap();
+    assert_eq!(module.encode().unwrap(), wasm, "{}", text);
+    wasm
+}
+
+#[test]
+fn round_trips() {
+    round_trip("(module)");
+    round_trip(
+        r#"
+        (module
+            (type (func (param i32 i64) (result f32)))
+            (import "env" "f" (func (param i32)))
+            (import "env" "t" (table 1 2 funcref))
+            (import "env" "m" (memory 1 2 shared))
+            (import "env" "g" (global (mut i64)))
+            (table 3 anyref)
+            (global $g (mut f64) (f64.const -1.5))
+            (global f32 (f32.const nan:0x200000))
+            (global v128 (v128.const i64x2 -1 2))
+            (export "f" (func 0))
+            (export "g" (global 1))
+            (start 0)
+            (func $f (export "run") (param i32) (result i32) (local i64 i64 f32)
+                (block $b (result i32)
+                    (loop $l
+                        (br_if $l (i32.eqz (local.get 0)))
+                        (br_table $b $b (i32.const 1) (local.get 0)))
+                    i32.const 0)
+                (if (result i32) (local.get 0)
+                    (then (i32.const 1))
+                    (else (call_indirect (param i32) (result i32)
+                        (i32.const 2) (i32.const 0))))
+                i32.add
+                (i64.store32 offset=4 (i32.const 0) (i64.const -123456789))
+                (i32.atomic.rmw8.add_u offset=1 (i32.const 0) (i32.const 1))
+                drop
+                (memory.fill (i32.const 0) (i32.const 0) (i32.const 0))
+                (memory.init 0 (i32.const 0) (i32.const 0) (i32.const 0))
+                data.drop 1
+                (table.init 1 (i32.const 0) (i32.const 0) (i32.const 0))
+                elem.drop 0
+                (drop (table.get 1 (i32.const 0)))
+                (drop (select (result i64) (i64.const 1) (i64.const 2) (i32.const 0)))
+                (drop (i8x16.extract_lane_s 3 (v128.const i32x4 1 2 3 4)))
+                (drop (v8x16.shuffle 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+                    (v128.const i32x4 0 0 0 0) (v128.const i32x4 0 0 0 0)))
+                (drop (ref.func $f))
+                (i32.trunc_sat_f64_u (f64.const 1))
+                i32.extend8_s
+                return_call $f)
+            (func (param i32 i32) (result i32 i32)
+                (block (param i32 i32) (result i32 i32))
+                local.get 0
+                local.get 1)
+            (elem (i32.const 0) func 1)
+            (elem funcref (ref.func 1) (ref.null))
+            (elem (table 2) (i32.const 1) anyref (ref.null))
+            (data (i32.const 8) "hello")
+            (data "world"))
+        "#,
+    );
+}
+
+#[test]
+fn names() {
+    let wasm = round_trip(
+        r#"
+        (module $m
+            (import "env" "f" (func $imported))
+            (func $a (param $x i32) (local $y i64) (local i32))
+            (func $b))
+        "#,
+    );
+    let module = Module::decode(&wasm).unwrap();
+    assert_eq!(module.name.as_ref().unwrap().name(), "m");
+    let fields = match &module.kind {
+        ModuleKind::Text(fields) => fields,
+        ModuleKind::Binary(_) => unreachable!(),
+    };
+    let mut names = Vec::new();
+    for field in fields {
+        match field {
+            ModuleField::Import(i) => names.push(i.id.as_ref().unwrap().name()),
+            ModuleField::Func(f) => names.push(f.name.as_ref().unwrap().name()),
+            _ => {}
+        }
+    }
+    assert_eq!(names, ["imported", "a", "b"]);
+
+    let func = fields
+        .iter()
+        .filter_map(|f| match f {
+            ModuleField::Func(f) => Some(f),
+            _ => None,
+        })
+        .next()
+        .unwrap();
+    assert_eq!(func.ty.ty.params[0].0.as_ref().unwrap().name(), "x");
+    match &func.kind {
+        FuncKind::Inline { locals, .. } => {
+            assert_eq!(locals[0].0.as_ref().unwrap().name(), "y");
+            assert!(locals[1].0.is_none());
+        }
+        FuncKind::Import { .. } => unreachable!(),
+    }
+}
+
+#[test]
+fn decoded_modules_can_be_edited() {
+    let wasm = wat::parse_str("(module (func (result i32) i32.const 1))").unwrap();
+    let mut module = Module::decode(&wasm).unwrap();
+    if let ModuleKind::Text(fields) = &mut module.kind {
+        for field in fields {
+            if let ModuleField::Func(f) = field {
+                if let FuncKind::Inline { expression, .. } = &mut f.kind {
+                    expression.instrs[0] = Instruction::I32Const(2);
+                }
+            }
+        }
+    }
+    let expected = wat::parse_str("(module (func (result i32) i32.const 2))").unwrap();
+    assert_eq!(module.encode().unwrap(), expected);
+}
+
+#[test]
+fn errors() {
+    let error = |bytes: &[u8]| Module::decode(bytes).unwrap_err().to_string();
+
+    assert_eq!(
+        error(b"\0asm\x02\0\0\0"),
+        "unknown binary version at byte offset 4"
+    );
+    // a function body containing an unknown opcode
+    assert_eq!(
+        error(b"\0asm\x01\0\0\0\x01\x04\x01\x60\0\0\x03\x02\x01\0\x0a\x05\x01\x03\0\x06\x0b"),
+        "illegal opcode at byte offset 23"
+    );
+    // `memory.size` with a nonzero memory index
+    assert_eq!(
+        error(
+            b"\0asm\x01\0\0\0\x01\x04\x01\x60\0\0\x03\x02\x01\0\x0a\x07\x01\x05\0\x3f\x01\x1a\x0b"
+        ),
+        "zero byte expected at byte offset 24"
+    );
+    // a function body with bytes after its final `end`
+    assert_eq!(
+        error(b"\0asm\x01\0\0\0\x01\x04\x01\x60\0\0\x03\x02\x01\0\x0a\x05\x01\x03\0\x0b\x01"),
+        "function body size mismatch at byte offset 24"
+    );
+}
@@ -0,0 +1,94 @@
+use wast::Module;
+
+fn diff(expected: &str, actual: &str) -> Vec<String> {
+    let expected = wat::parse_str(expected).unwrap();
+    let actual = wat::parse_str(actual).unwrap();
+    Module::diff(&expected, &actual).unwrap()
+}
+
+#[test]
+fn same() {
+    let module = r#"
+        (module
+            (import "env" "f" (func $f (param i32)))
+            (memory 1)
+            (func (export "g") (param i32) (local i64)
+                (call $f (local.get 0)))
+            (data (i32.const 0) "hello"))
+    "#;
+    assert!(diff(module, module).is_empty());
+    // names aren't compared
+    assert!(diff("(module (func $a))", "(module (func $b))").is_empty());
+}
+
+#[test]
+fn sections() {
+    assert_eq!(
+        diff(
+            "(module (type (func)))",
+            "(module (type (func)) (type (func (param i32))))"
+        ),
+        ["type section has extra entry `(func (param i32))`"]
+    );
+    assert_eq!(
+        diff(
+            r#"(module (import "env" "m" (memory 1)) (global i32 (i32.const 0)))"#,
+            r#"(module (import "env" "m" (memory 2)))"#
+        ),
+        [
+            r#"import 0 differs: expected `(import "env" "m" (memory 1))`, got `(import "env" "m" (memory 2))`"#,
+            "global section is missing entry `(global i32 (i32.const 0))`",
+        ]
+    );
+    assert_eq!(
+        diff(
+            r#"(module (func (export "a")) (data (i32.const 1) "a\"\01"))"#,
+            r#"(module (func (export "b")) (data (i32.const 1) "a\"\02"))"#
+        ),
+        [
+            r#"export 0 differs: expected `(export "a" (func 0))`, got `(export "b" (func 0))`"#,
+            r#"data 0 differs: expected `(data (memory 0) (i32.const 1) "a\"\01")`, got `(data (memory 0) (i32.const 1) "a\"\02")`"#,
+        ]
+    );
+}
+
+#[test]
+fn function_bodies() {
+    let differences = diff(
+        r#"
+        (module
+            (import "env" "f" (func))
+            (func (param i32) (result i32)
+                local.get 0
+                i32.const 1
+                i32.add)
+            (func (local i32) nop))
+        "#,
+        r#"
+        (module
+            (import "env" "f" (func))
+            (func (param i32) (result i32)
+                local.get 0
+                i32.const 1
+                i32.sub)
+            (func (local i64) nop nop))
+        "#,
+    );
+    assert_eq!(
+        differences,
+        [
+            "function 2 differs: expected `(func (type 0) (local i32))`, got `(func (type 0) (local i64))`",
+            "function 1 body differs at instruction 2: expected `i32.add`, got `i32.sub`",
+            "function 2 body differs at instruction 1: expected end of body, got `nop`",
+        ]
+    );
+}
+
+#[test]
+fn errors() {
+    let error = Module::diff(b"\0asm\x01\0\0\0", b"wasm").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "failed to decode actual module: magic header not detected at byte offset 0"
+    );
+}
@@ -0,0 +1,71 @@
+use wast::Module;
+
+#[test]
+fn sections_and_code() {
+    let wasm = wat::parse_str(
+        r#"
+        (module
+            (import "env" "print" (func $print (param i32)))
+            (memory 1)
+            (func $main (export "main") (param i32) (local i64 i64)
+                (if (local.get 0)
+                    (then (call $print (i32.const 1))))
+                (i64.store offset=8 (i32.const 0) (i64.const -1)))
+            (data (i32.const 16) "hi\00"))
+        "#,
+    )
+    .unwrap();
+    let expected = "\
+Sections:
+
+     Type start=0x0000000a end=0x0000000f (size=0x00000005) count: 1
+   Import start=0x00000011 end=0x0000001e (size=0x0000000d) count: 1
+ Function start=0x00000020 end=0x00000022 (size=0x00000002) count: 1
+   Memory start=0x00000024 end=0x00000027 (size=0x00000003) count: 1
+   Export start=0x00000029 end=0x00000031 (size=0x00000008) count: 1
+     Code start=0x00000033 end=0x00000049 (size=0x00000016) count: 1
+     Data start=0x0000004b end=0x00000054 (size=0x00000009) count: 1
+   Custom start=0x00000056 end=0x0000006b (size=0x00000015) \"name\"
+
+Section Details:
+
+Type[1]:
+ - type[0] (i32) -> nil
+Import[1]:
+ - func[0] sig=0 <print> <- env.print
+Function[1]:
+ - func[1] sig=0 <main>
+Memory[1]:
+ - memory[0] pages: initial=1
+Export[1]:
+ - func[1] <main> -> \"main\"
+Code[1]:
+ - func[1] size=20 <main>
+Data[1]:
+ - segment[0] size=3 memory=0 - init i32.const 16
+  - 0000051: 6869 00                                  hi.
+Custom:
+ - name: \"name\"
+
+Code Disassembly:
+
+000035 func[1] <main>:
+ 000036: 02 7e                     | local[1..2] type=i64
+ 000038: 20 00                     | local.get 0
+ 00003a: 04 40                     | if
+ 00003c: 41 01                     |   i32.const 1
+ 00003e: 10 00                     |   call 0 <print>
+ 000040: 0b                        | end
+ 000041: 41 00                     | i32.const 0
+ 000043: 42 7f                     | i64.const -1
+ 000045: 37 03 08                  | i64.store offset=8 align=8
+ 000048: 0b                        | end
+";
+    assert_eq!(Module::dump(&wasm).unwrap(), expected);
+}
+
+#[test]
+fn errors() {
+    let error = Module::dump(b"\0asm\x01\0\0\0\x0d\0").unwrap_err();
+    assert_eq!(error.to_string(), "malformed section id at byte offset 8");
+}
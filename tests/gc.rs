@@ -0,0 +1,98 @@
+use wast::parser::{self, ParseBuffer};
+use wast::{Module, Wat};
+
+mod common;
+
+fn gc(text: &str) -> Result<Vec<u8>, wast::Error> {
+    let buf = ParseBuffer::new(text)?;
+    let mut module = parser::parse::<Wat>(&buf)?.module;
+    module.gc()?;
+    module.encode()
+}
+
+fn assert_gc(text: &str, expected: &str) {
+    common::assert_transformed(text, expected, |module| module.gc());
+}
+
+#[test]
+fn unreachable_items_are_removed() {
+    assert_gc(
+        r#"
+        (module
+            (import "env" "log" (func $log (param i32)))
+            (import "env" "unused" (func $unused_import))
+            (global $unused (mut i32) (i32.const 0))
+            (global $g i32 (i32.const 1))
+            (type $unused (func (param f32)))
+            (func $dead (param f64) (call $log (global.get $unused)))
+            (func $helper (param i32) (result i32) (i32.add (local.get 0) (global.get $g)))
+            (func $main (export "main")
+                (call $log (call $helper (i32.const 1)))))
+        "#,
+        r#"
+        (module
+            (type (func (param i32)))
+            (type (func))
+            (type (func (param i32) (result i32)))
+            (import "env" "log" (func (type 0)))
+            (import "env" "unused" (func (type 1)))
+            (global i32 (i32.const 1))
+            (func (type 2) (i32.add (local.get 0) (global.get 0)))
+            (func (type 1) (call 0 (call 2 (i32.const 1))))
+            (export "main" (func 3)))
+        "#,
+    );
+}
+
+#[test]
+fn roots() {
+    assert_gc(
+        r#"
+        (module
+            (table 1 funcref)
+            (func $dead)
+            (func $in_elem (result i32) i32.const 1)
+            (func $referenced)
+            (func $start (drop (ref.func $referenced)))
+            (global $exported (export "g") funcref (ref.func $in_global))
+            (func $in_global)
+            (elem (i32.const 0) $in_elem)
+            (start $start))
+        "#,
+        r#"
+        (module
+            (type (func))
+            (type (func (result i32)))
+            (table 1 funcref)
+            (func (type 1) i32.const 1)
+            (func (type 0))
+            (func (type 0) (drop (ref.func 1)))
+            (global funcref (ref.func 3))
+            (func (type 0))
+            (export "g" (global 0))
+            (elem (i32.const 0) 0)
+            (start 2))
+        "#,
+    );
+}
+
+#[test]
+fn names_follow_functions() {
+    let wasm = gc(r#"(module (func $a) (func $b (export "b")) (func $c (call $b)))"#).unwrap();
+    let module = Module::decode(&wasm).unwrap();
+    let mut names = Vec::new();
+    if let wast::ModuleKind::Text(fields) = &module.kind {
+        for field in fields {
+            if let wast::ModuleField::Func(f) = field {
+                names.push(f.name.as_ref().unwrap().name());
+            }
+        }
+    }
+    assert_eq!(names, ["b"]);
+}
+
+#[test]
+fn unknown_index() {
+    let err = gc(r#"(module (func (export "f") call 3))"#).unwrap_err();
+    assert_eq!(err.to_string(), "unknown function 3 at byte offset 9");
+}
@@ -0,0 +1,228 @@
+use wast::parser::{self, ParseBuffer};
+use wast::{Linker, Wat};
+
+mod common;
+
+fn link(modules: &[(&str, &str)]) -> Result<Vec<u8>, wast::Error> {
+    let buffers = modules
+        .iter()
+        .map(|(_, text)| ParseBuffer::new(text).unwrap())
+        .collect::<Vec<_>>();
+    let mut linker = Linker::new();
+    for ((name, _), buf) in modules.iter().zip(&buffers) {
+        linker.module(name, parser::parse::<Wat>(buf).unwrap().module);
+    }
+    linker.link()?.encode()
+}
+
+fn assert_linked(modules: &[(&str, &str)], expected: &str) {
+    common::assert_encodes(&link(modules).unwrap(), expected);
+}
+
+#[test]
+fn imports_are_satisfied() {
+    assert_linked(
+        &[
+            (
+                "math",
+                r#"
+                (module
+                    (import "env" "log" (func $log (param i32)))
+                    (global $scale i32 (i32.const 2))
+                    (func $double (export "double") (param i32) (result i32)
+                        (call $log (local.get 0))
+                        (i32.mul (local.get 0) (global.get $scale))))
+                "#,
+            ),
+            (
+                "main",
+                r#"
+                (module
+                    (import "env" "log" (func $log (param i32)))
+                    (import "math" "double" (func $double (param i32) (result i32)))
+                    (func $main (export "main") (result i32)
+                        (call $log (i32.const 0))
+                        (call $double (i32.const 21))))
+                "#,
+            ),
+        ],
+        r#"
+        (module
+            (type (func (param i32)))
+            (type (func (param i32) (result i32)))
+            (type (func (result i32)))
+            (import "env" "log" (func (type 0)))
+            (func (type 1)
+                (call 0 (local.get 0))
+                (i32.mul (local.get 0) (global.get 0)))
+            (func (type 2)
+                (call 0 (i32.const 0))
+                (call 1 (i32.const 21)))
+            (global i32 (i32.const 2))
+            (export "double" (func 1))
+            (export "main" (func 2)))
+        "#,
+    );
+}
+
+#[test]
+fn re_exported_imports() {
+    assert_linked(
+        &[
+            (
+                "a",
+                r#"(module (import "b" "g" (global i64)) (func (result i64) global.get 0))"#,
+            ),
+            (
+                "b",
+                r#"(module (import "c" "g2" (global $g i64)) (export "g" (global $g)))"#,
+            ),
+            (
+                "c",
+                r#"(module (global (export "h") i64 (i64.const 7)) (export "g2" (global 0)))"#,
+            ),
+        ],
+        r#"
+        (module
+            (func (result i64) global.get 0)
+            (global i64 (i64.const 7))
+            (export "g" (global 0))
+            (export "h" (global 0))
+            (export "g2" (global 0)))
+        "#,
+    );
+}
+
+#[test]
+fn segments_and_tables() {
+    assert_linked(
+        &[
+            (
+                "a",
+                r#"
+                (module
+                    (memory (export "mem") 1)
+                    (data (i32.const 0) "a")
+                    (data "b")
+                    (func data.drop 1))
+                "#,
+            ),
+            (
+                "b",
+                r#"
+                (module
+                    (import "a" "mem" (memory 1))
+                    (table 1 funcref)
+                    (elem (i32.const 0) $f)
+                    (data (i32.const 1) "c")
+                    (func $f data.drop 0))
+                "#,
+            ),
+        ],
+        r#"
+        (module
+            (memory 1)
+            (table 1 funcref)
+            (data (i32.const 0) "a")
+            (data "b")
+            (elem (i32.const 0) 1)
+            (data (i32.const 1) "c")
+            (func data.drop 1)
+            (func data.drop 2)
+            (export "mem" (memory 0)))
+        "#,
+    );
+}
+
+#[test]
+fn errors() {
+    let error = |modules: &[(&str, &str)]| link(modules).unwrap_err().to_string();
+    let lib = r#"(module (func (export "f") (param i32)))"#;
+
+    assert_eq!(
+        error(&[
+            ("lib", lib),
+            ("main", r#"(module (import "lib" "g" (func)))"#)
+        ]),
+        "failed to link import `lib` `g` of module `main`: no export of that name at byte offset 9"
+    );
+    assert_eq!(
+        error(&[("lib", lib), ("main", r#"(module (import "lib" "f" (global i32)))"#)]),
+        "failed to link import `lib` `f` of module `main`: expected a global, but the export is a function at byte offset 9"
+    );
+    assert_eq!(
+        error(&[("lib", lib), ("main", r#"(module (import "lib" "f" (func)))"#)]),
+        "failed to link import `lib` `f` of module `main`: expected `func [] -> []`, but found `func [i32] -> []` at byte offset 9"
+    );
+    assert_eq!(
+        error(&[(
+            "a",
+            r#"(module (import "a" "f" (func)) (export "f" (func 0)))"#
+        )]),
+        "failed to link import `a` `f` of module `a`: imports form a cycle at byte offset 9"
+    );
+    assert_eq!(
+        error(&[("lib", lib), ("main", lib)]),
+        "export `f` of module `main` has the same name as an export of module `lib` at byte offset 15"
+    );
+    assert!(error(&[("main", "(module (func call 1))")]).contains("in module `main`"));
+}
+
+#[test]
+fn limits() {
+    let error = |modules: &[(&str, &str)]| link(modules).unwrap_err().to_string();
+    let lib = r#"(module (memory (export "mem") 2 5) (table (export "tab") 1 funcref))"#;
+
+    link(&[
+        ("lib", lib),
+        (
+            "main",
+            r#"(module (import "lib" "mem" (memory 1 10)) (import "lib" "tab" (table 0 funcref)))"#,
+        ),
+    ])
+    .unwrap();
+    assert_eq!(
+        error(&[("lib", lib), ("main", r#"(module (import "lib" "mem" (memory 5 10)))"#)]),
+        "failed to link import `lib` `mem` of module `main`: expected `memory 5 10`, but found `memory 2 5` at byte offset 9"
+    );
+    assert_eq!(
+        error(&[("lib", lib), ("main", r#"(module (import "lib" "mem" (memory 1 4)))"#)]),
+        "failed to link import `lib` `mem` of module `main`: expected `memory 1 4`, but found `memory 2 5` at byte offset 9"
+    );
+    assert_eq!(
+        error(&[("lib", lib), ("main", r#"(module (import "lib" "tab" (table 1 1 funcref)))"#)]),
+        "failed to link import `lib` `tab` of module `main`: expected `table funcref 1 1`, but found `table funcref 1` at byte offset 9"
+    );
+}
+
+#[test]
+fn one_memory_and_table() {
+    let error = |modules: &[(&str, &str)]| link(modules).unwrap_err().to_string();
+    let a = "(module (memory 1) (table 1 funcref))";
+    let b = "(module (memory 1))";
+    assert_eq!(
+        error(&[("a", a), ("b", b)]),
+        "modules `a` and `b` both have a memory, but the linked module can only have one at byte offset 1"
+    );
+    assert_eq!(
+        error(&[("a", a), ("b", r#"(module (import "env" "mem" (memory 1)))"#)]),
+        "modules `b` and `a` both have a memory, but the linked module can only have one at byte offset 1"
+    );
+
+    // Multiple tables are fine unless reference types are disabled.
+    let tables = [("a", a), ("b", "(module (table 1 funcref))")];
+    link(&tables).unwrap();
+    let buffers = tables
+        .iter()
+        .map(|(_, text)| ParseBuffer::new(text).unwrap())
+        .collect::<Vec<_>>();
+    let mut linker = Linker::new();
+    linker.features(wast::WasmFeatures::mvp());
+    for ((name, _), buf) in tables.iter().zip(&buffers) {
+        linker.module(name, parser::parse::<Wat>(buf).unwrap().module);
+    }
+    assert_eq!(
+        linker.link().unwrap_err().to_string(),
+        "modules `a` and `b` both have a table, but the linked module can only have one at byte offset 1"
+    );
+}
@@ -0,0 +1,108 @@
+use wast::parser::{self, ParseBuffer};
+use wast::{Instruction, Opcode, Proposal, StackSignature, ValType, Wat};
+
+fn instructions(text: &str) -> Vec<Instruction<'static>> {
+    let buf = ParseBuffer::new(text).unwrap();
+    let wat = parser::parse::<Wat>(&buf).unwrap();
+    let fields = match wat.module.into_owned().kind {
+        wast::ModuleKind::Text(fields) => fields,
+        wast::ModuleKind::Binary(_) => unreachable!(),
+    };
+    let mut ret = Vec::new();
+    for field in fields {
+        if let wast::ModuleField::Func(f) = field {
+            if let wast::FuncKind::Inline { expression, .. } = f.kind {
+                ret.extend(expression.instrs);
+            }
+        }
+    }
+    ret
+}
+
+#[test]
+fn instruction_metadata() {
+    let instrs = instructions(
+        r#"
+        (module
+            (memory 1)
+            (func (local i32)
+                i32.const 1
+                i32.const 2
+                i32.add
+                f64.convert_i32_s
+                drop
+                local.get 0
+                i32.extend8_s
+                i32.trunc_sat_f32_s
+                memory.copy
+                return_call 0
+                i8x16.extract_lane_u 1
+                i32.atomic.rmw.cmpxchg))
+    "#,
+    );
+    let names = instrs.iter().map(|i| i.name()).collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "i32.const",
+            "i32.const",
+            "i32.add",
+            "f64.convert_i32_s",
+            "drop",
+            "local.get",
+            "i32.extend8_s",
+            "i32.trunc_sat_f32_s",
+            "memory.copy",
+            "return_call",
+            "i8x16.extract_lane_u",
+            "i32.atomic.rmw.cmpxchg",
+        ]
+    );
+
+    assert_eq!(
+        instrs[2].opcode(),
+        Opcode {
+            prefix: None,
+            code: 0x6a
+        }
+    );
+    assert_eq!(
+        instrs[8].opcode(),
+        Opcode {
+            prefix: Some(0xfc),
+            code: 0x0a
+        }
+    );
+
+    let proposals = instrs.iter().map(|i| i.proposal()).collect::<Vec<_>>();
+    assert_eq!(
+        proposals,
+        [
+            Proposal::Mvp,
+            Proposal::Mvp,
+            Proposal::Mvp,
+            Proposal::Mvp,
+            Proposal::Mvp,
+            Proposal::Mvp,
+            Proposal::SignExtension,
+            Proposal::SaturatingFloatToInt,
+            Proposal::BulkMemory,
+            Proposal::TailCall,
+            Proposal::Simd,
+            Proposal::Atomics,
+        ]
+    );
+
+    let sig = |params: &'static [ValType], results: &'static [ValType]| {
+        Some(StackSignature { params, results })
+    };
+    use ValType::*;
+    assert_eq!(instrs[0].signature(), sig(&[], &[I32]));
+    assert_eq!(instrs[2].signature(), sig(&[I32, I32], &[I32]));
+    assert_eq!(instrs[3].signature(), sig(&[I32], &[F64]));
+    assert_eq!(instrs[4].signature(), None);
+    assert_eq!(instrs[5].signature(), None);
+    assert_eq!(instrs[8].signature(), sig(&[I32, I32, I32], &[]));
+    assert_eq!(instrs[10].signature(), sig(&[V128], &[I32]));
+    assert_eq!(instrs[11].signature(), sig(&[I32, I32, I32], &[I32]));
+}
@@ -0,0 +1,147 @@
+use wast::parser::{self, ParseBuffer};
+use wast::{EncodeOptions, Wat};
+
+mod common;
+
+fn assert_minimized(text: &str, expected: &str) -> wast::SizeReport {
+    common::assert_transformed(text, expected, |module| module.minimize())
+}
+
+fn section<'a>(report: &'a wast::SizeReport, name: &str) -> &'a wast::SectionSize {
+    report.sections.iter().find(|s| s.name == name).unwrap()
+}
+
+#[test]
+fn unused_types_are_removed() {
+    let report = assert_minimized(
+        r#"
+        (module
+            (type $unused (func (param i64 i64 i64)))
+            (type $used (func (param i32)))
+            (func (type $used)))
+        "#,
+        r#"
+        (module
+            (type (func (param i32)))
+            (func (type 0)))
+        "#,
+    );
+    let types = section(&report, "Type");
+    assert_eq!((types.before, types.after), (13, 7));
+    assert_eq!(report.saved(), 6);
+}
+
+#[test]
+fn referenced_items_are_sorted_first() {
+    assert_minimized(
+        r#"
+        (module
+            (import "env" "f" (func $import))
+            (global $rare (mut i32) (i32.const 1))
+            (global $common (mut i32) (i32.const 2))
+            (func $rare)
+            (func $common (result i32)
+                (global.set $common (global.get $common))
+                (global.get $common))
+            (func $main (export "main")
+                call $import
+                call $rare
+                (drop (call $common))
+                (drop (call $common))))
+        "#,
+        r#"
+        (module
+            (type (func))
+            (type (func (result i32)))
+            (import "env" "f" (func (type 0)))
+            (global (mut i32) (i32.const 2))
+            (global (mut i32) (i32.const 1))
+            (func (type 1)
+                (global.set 0 (global.get 0))
+                (global.get 0))
+            (func (type 0))
+            (func (type 0)
+                call 0
+                call 2
+                (drop (call 1))
+                (drop (call 1)))
+            (export "main" (func 3)))
+        "#,
+    );
+}
+
+#[test]
+fn locals_are_grouped_by_type() {
+    let report = assert_minimized(
+        r#"
+        (module
+            (func (param f32) (result f32)
+                (local i32 f32 i32 f32)
+                (local.set 3 (i32.const 1))
+                (local.set 4 (local.get 0))
+                (local.get 2)))
+        "#,
+        r#"
+        (module
+            (func (param f32) (result f32)
+                (local i32 i32 f32 f32)
+                (local.set 2 (i32.const 1))
+                (local.set 4 (local.get 0))
+                (local.get 3)))
+        "#,
+    );
+    assert_eq!(section(&report, "Code").saved(), 4);
+}
+
+#[test]
+fn hot_functions_get_short_indices() {
+    // 201 functions, where only the last two have indices which need 2-byte
+    // LEBs, and the calls are all to one of them.
+    let mut text = String::from("(module\n");
+    for _ in 0..199 {
+        text.push_str("(func)\n");
+    }
+    text.push_str("(func $hot)\n(func (export \"run\")");
+    for _ in 0..10 {
+        text.push_str(" call $hot");
+    }
+    text.push_str("))");
+
+    let buf = ParseBuffer::new(&text).unwrap();
+    let mut module = parser::parse::<Wat>(&buf).unwrap().module;
+    let report = module.minimize().unwrap();
+    assert_eq!(section(&report, "Code").saved(), 10);
+    assert_eq!(section(&report, "Export").saved(), 1);
+    // the name section has the index of `$hot` too
+    assert_eq!(section(&report, "Custom").saved(), 1);
+    assert!(report.to_string().ends_with("Total: 12 bytes saved"));
+}
+
+#[test]
+fn encoding_can_minimize() {
+    let text = r#"
+        (module
+            (type (func (param i64)))
+            (func $a)
+            (func $b (local i32 i64 i32))
+            (start $b))
+    "#;
+    let buf = ParseBuffer::new(text).unwrap();
+    let mut module = parser::parse::<Wat>(&buf).unwrap().module;
+    let actual = module
+        .encode_with(EncodeOptions::new().minimize_size(true))
+        .unwrap();
+    let buf = ParseBuffer::new(text).unwrap();
+    let mut module = parser::parse::<Wat>(&buf).unwrap().module;
+    module.minimize().unwrap();
+    assert_eq!(actual, module.encode().unwrap());
+    assert!(actual.len() < wat::parse_str(text).unwrap().len());
+}
+
+#[test]
+fn unknown_functions_are_errors() {
+    let buf = ParseBuffer::new("(module (func call 3))").unwrap();
+    let mut module = parser::parse::<Wat>(&buf).unwrap().module;
+    let err = module.minimize().unwrap_err();
+    assert!(err.to_string().contains("unknown function 3"), "{}", err);
+}
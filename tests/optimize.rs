@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+use wast::parser::{self, ParseBuffer};
+use wast::{FuncKind, Index, Instruction, Module, ModuleField, ModuleKind, OptimizeOptions, Wat};
+
+mod common;
+
+fn optimize(text: &str, options: &OptimizeOptions) -> Result<Vec<u8>, wast::Error> {
+    let buf = ParseBuffer::new(text)?;
+    let mut module = parser::parse::<Wat>(&buf)?.module;
+    module.optimize_with(options)?;
+    module.validate()?;
+    module.encode()
+}
+
+fn assert_optimized_with(options: &OptimizeOptions, text: &str, expected: &str) {
+    common::assert_transformed(text, expected, |module| module.optimize_with(options));
+}
+
+fn assert_optimized(text: &str, expected: &str) {
+    assert_optimized_with(&OptimizeOptions::default(), text, expected)
+}
+
+#[test]
+fn constants_are_folded() {
+    assert_optimized(
+        "(module (func (result i32)
+            i32.const 1 i32.const 2 i32.add
+            i32.const 4 i32.mul
+            i32.const 2 i32.sub))",
+        "(module (func (result i32) i32.const 10))",
+    );
+    assert_optimized(
+        "(module (func (result i32) (i32.add (i32.const 0x7fffffff) (i32.const 1))))",
+        "(module (func (result i32) i32.const 0x80000000))",
+    );
+    // `i32.div_s` may trap, so it's left alone
+    assert_optimized(
+        "(module (func (result i32) (i32.div_s (i32.const 1) (i32.const 0))))",
+        "(module (func (result i32) (i32.div_s (i32.const 1) (i32.const 0))))",
+    );
+}
+
+#[test]
+fn nops_and_dropped_constants_are_removed() {
+    assert_optimized(
+        "(module (func (param i32)
+            nop
+            (drop (i32.const 1))
+            (drop (f64.const 2))
+            (drop (ref.null))
+            (drop (i32.add (i32.const 1) (i32.const 2)))
+            (drop (local.get 0))))",
+        "(module (func (param i32) (drop (local.get 0))))",
+    );
+}
+
+#[test]
+fn set_and_get_become_tee() {
+    assert_optimized(
+        "(module (func (param i32 i32) (result i32)
+            (local.set 0 (i32.const 1))
+            local.get 0
+            (local.set 1 (i32.const 2))
+            local.get 0
+            i32.add))",
+        "(module (func (param i32 i32) (result i32)
+            (local.tee 0 (i32.const 1))
+            (local.set 1 (i32.const 2))
+            local.get 0
+            i32.add))",
+    );
+}
+
+#[test]
+fn branches_to_the_following_end_are_removed() {
+    assert_optimized(
+        "(module (func (param i32) (result i32)
+            (block (result i32) (br 0 (local.get 0)))
+            (if (result i32) (local.get 0)
+                (then (br 0 (i32.const 1)))
+                (else (i32.const 2)))
+            i32.add
+            (br_if 0 (local.get 0))))",
+        "(module (func (param i32) (result i32)
+            (block (result i32) (local.get 0))
+            (if (result i32) (local.get 0)
+                (then (i32.const 1))
+                (else (i32.const 2)))
+            i32.add
+            (drop (local.get 0))))",
+    );
+}
+
+#[test]
+fn branches_which_discard_values_are_kept() {
+    let text = "(module (func (result i32)
+        (block (result i32) i32.const 1 i32.const 2 br 0)))";
+    assert_optimized(text, text);
+    let text = "(module (func (loop (br_if 0 (i32.const 0)))))";
+    assert_optimized(text, text);
+    // In unreachable code the branch isn't needed as long as no values would
+    // be left on the stack.
+    assert_optimized(
+        "(module (func (result i32) (block (result i32) unreachable br 0)))",
+        "(module (func (result i32) (block (result i32) unreachable)))",
+    );
+    let text = "(module (func (result i32)
+        (block (result i32) unreachable i32.const 1 i32.const 2 br 0)))";
+    assert_optimized(text, text);
+}
+
+#[test]
+fn rewrites_can_be_disabled() {
+    let text = "(module (func (param i32) (result i32)
+        nop
+        (drop (i32.const 1))
+        (local.set 0 (i32.add (i32.const 1) (i32.const 2)))
+        (block (result i32) (br 0 (local.get 0)))))";
+    let mut options = OptimizeOptions::new();
+    options
+        .fold_constants(false)
+        .remove_nops(false)
+        .remove_dropped_constants(false)
+        .local_tees(false)
+        .thread_branches(false);
+    assert_optimized_with(&options, text, text);
+}
+
+#[test]
+fn invalid_modules_are_rejected() {
+    let err = optimize(
+        "(module (func (result i32) i64.const 1))",
+        &OptimizeOptions::default(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("type mismatch"), "{}", err);
+}
+
+/// A small xorshift generator, so the randomized test is reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+const LOCALS: u64 = 3;
+
+/// Generates random function bodies which use the instructions that the
+/// optimizer rewrites, in the flat text format.
+struct Generator {
+    rng: Rng,
+    out: String,
+}
+
+impl Generator {
+    fn push(&mut self, instr: &str) {
+        self.out.push_str(instr);
+        self.out.push('\n');
+    }
+
+    /// Generates instructions which push one `i32`.
+    fn value(&mut self, depth: u32) {
+        let choice = if depth == 0 {
+            self.rng.below(2)
+        } else {
+            self.rng.below(12)
+        };
+        match choice {
+            0 => {
+                let value = self.rng.below(7) as i32 - 3;
+                self.push(&format!("i32.const {}", value));
+            }
+            1 => {
+                let local = self.rng.below(LOCALS);
+                self.push(&format!("local.get {}", local));
+            }
+            2..=4 => {
+                self.value(depth - 1);
+                self.value(depth - 1);
+                let op = ["i32.add", "i32.sub", "i32.mul"][self.rng.below(3) as usize];
+                self.push(op);
+            }
+            5 => {
+                self.value(depth - 1);
+                let local = self.rng.below(LOCALS);
+                self.push(&format!("local.tee {}", local));
+            }
+            6 | 7 => {
+                self.statement(depth - 1);
+                self.value(depth - 1);
+            }
+            8 => {
+                // `br 0` just before `end`, which may discard a value
+                self.push("block (result i32)");
+                self.statement(depth - 1);
+                if self.rng.below(2) == 0 {
+                    self.value(depth - 1);
+                }
+                self.value(depth - 1);
+                self.push("br 0");
+                self.push("end");
+            }
+            9 => {
+                // `br_if 0` which may or may not be taken
+                self.push("block (result i32)");
+                self.value(depth - 1);
+                self.value(depth - 1);
+                self.push("br_if 0");
+                if self.rng.below(2) == 0 {
+                    self.push("drop");
+                    self.value(depth - 1);
+                }
+                self.push("end");
+            }
+            10 => {
+                self.value(depth - 1);
+                self.push("if (result i32)");
+                self.value(depth - 1);
+                if self.rng.below(2) == 0 {
+                    self.push("br 0");
+                }
+                self.push("else");
+                self.value(depth - 1);
+                self.push("end");
+            }
+            _ => {
+                // a trap, with an unreachable branch after it
+                self.value(depth - 1);
+                self.push("if (result i32)");
+                self.push("unreachable");
+                self.push("br 0");
+                self.push("else");
+                self.value(depth - 1);
+                self.push("end");
+            }
+        }
+    }
+
+    /// Generates instructions which leave the stack unchanged.
+    fn statement(&mut self, depth: u32) {
+        let choice = if depth == 0 { 0 } else { self.rng.below(8) };
+        match choice {
+            0 => self.push("nop"),
+            1 => {
+                self.value(depth - 1);
+                self.push("drop");
+            }
+            2 | 3 => {
+                self.value(depth - 1);
+                let local = self.rng.below(LOCALS);
+                self.push(&format!("local.set {}", local));
+            }
+            4 => {
+                self.push("block");
+                self.statement(depth - 1);
+                self.push("br 0");
+                self.push("end");
+            }
+            5 => {
+                self.push("block");
+                self.value(depth - 1);
+                self.push("br_if 0");
+                self.statement(depth - 1);
+                self.push("end");
+            }
+            6 => {
+                self.push("loop");
+                self.statement(depth - 1);
+                self.push("end");
+            }
+            _ => {
+                self.statement(depth - 1);
+                self.statement(depth - 1);
+            }
+        }
+    }
+}
+
+/// Runs a function body which takes no parameters and returns one `i32`,
+/// returning `None` if it traps.
+fn run(instrs: &[Instruction<'_>], locals: usize) -> Option<i32> {
+    // The `else` and `end` of each block, and the `end` of each `else`.
+    let mut elses = HashMap::new();
+    let mut ends = HashMap::new();
+    let mut open = Vec::new();
+    for (i, instr) in instrs.iter().enumerate() {
+        match instr {
+            Instruction::Block(_) | Instruction::Loop(_) | Instruction::If(_) => open.push(i),
+            Instruction::Else(_) => {
+                elses.insert(*open.last().unwrap(), i);
+                open.push(i);
+            }
+            Instruction::End(_) => {
+                let start = open.pop().unwrap();
+                ends.insert(start, i);
+                if let Instruction::Else(_) = instrs[start] {
+                    ends.insert(open.pop().unwrap(), i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    struct Label {
+        height: usize,
+        arity: usize,
+        /// Where a branch to this label continues.
+        target: usize,
+        is_loop: bool,
+    }
+    let mut labels = Vec::new();
+    let mut stack = Vec::new();
+    let mut locals = vec![0i32; locals];
+    let mut pc = 0;
+    let num = |index: &Index<'_>| match index {
+        Index::Num(n) => *n as usize,
+        Index::Id(_) => panic!("unresolved index"),
+    };
+    while pc < instrs.len() {
+        let mut next = pc + 1;
+        let mut branch = None;
+        match &instrs[pc] {
+            Instruction::Block(bt) | Instruction::If(bt) => {
+                if let Instruction::If(_) = instrs[pc] {
+                    if stack.pop().unwrap() == 0 {
+                        next = match elses.get(&pc) {
+                            Some(&i) => i + 1,
+                            None => ends[&pc],
+                        };
+                    }
+                }
+                labels.push(Label {
+                    height: stack.len(),
+                    arity: bt.ty.ty.results.len(),
+                    target: ends[&pc] + 1,
+                    is_loop: false,
+                });
+            }
+            Instruction::Loop(_) => labels.push(Label {
+                height: stack.len(),
+                arity: 0,
+                target: pc + 1,
+                is_loop: true,
+            }),
+            Instruction::Else(_) => next = ends[&pc],
+            Instruction::End(_) => {
+                labels.pop();
+            }
+            Instruction::Unreachable => return None,
+            Instruction::Nop => {}
+            Instruction::Br(index) => branch = Some(num(index)),
+            Instruction::BrIf(index) => {
+                if stack.pop().unwrap() != 0 {
+                    branch = Some(num(index));
+                }
+            }
+            Instruction::Drop => {
+                stack.pop().unwrap();
+            }
+            Instruction::LocalGet(index) => stack.push(locals[num(index)]),
+            Instruction::LocalSet(index) => locals[num(index)] = stack.pop().unwrap(),
+            Instruction::LocalTee(index) => locals[num(index)] = *stack.last().unwrap(),
+            Instruction::I32Const(value) => stack.push(*value),
+            Instruction::I32Add | Instruction::I32Sub | Instruction::I32Mul => {
+                let b = stack.pop().unwrap();
+                let a = stack.pop().unwrap();
+                stack.push(match &instrs[pc] {
+                    Instruction::I32Add => a.wrapping_add(b),
+                    Instruction::I32Sub => a.wrapping_sub(b),
+                    _ => a.wrapping_mul(b),
+                });
+            }
+            other => panic!("unexpected instruction {:?}", other),
+        }
+        if let Some(depth) = branch {
+            // A branch to the function's own label returns.
+            if depth == labels.len() {
+                return stack.pop();
+            }
+            let label = labels.remove(labels.len() - 1 - depth);
+            labels.truncate(labels.len() - depth);
+            let values = stack.split_off(stack.len() - label.arity);
+            stack.truncate(label.height);
+            stack.extend(values);
+            next = label.target;
+            if label.is_loop {
+                // Branching to a loop re-enters it.
+                labels.push(label);
+            }
+        }
+        pc = next;
+    }
+    assert_eq!(stack.len(), 1);
+    stack.pop()
+}
+
+fn body<'a, 'b>(module: &'b Module<'a>) -> &'b [Instruction<'a>] {
+    let fields = match &module.kind {
+        ModuleKind::Text(fields) => fields,
+        ModuleKind::Binary(_) => unreachable!(),
+    };
+    for field in fields {
+        if let ModuleField::Func(func) = field {
+            if let FuncKind::Inline { expression, .. } = &func.kind {
+                return &expression.instrs;
+            }
+        }
+    }
+    unreachable!()
+}
+
+#[test]
+fn optimized_bodies_compute_the_same_results() {
+    let mut generator = Generator {
+        rng: Rng(0x2545_f491_4f6c_dd1d),
+        out: String::new(),
+    };
+    let mut changed = 0;
+    for _ in 0..2000 {
+        generator.out.clear();
+        let depth = 1 + generator.rng.below(6) as u32;
+        generator.value(depth);
+        if generator.rng.below(4) == 0 {
+            generator.push("br 0");
+        }
+        let text = format!(
+            "(module (func (result i32) (local i32 i32 i32)\n{}))",
+            generator.out
+        );
+
+        let buf = ParseBuffer::new(&text).unwrap();
+        let mut original = parser::parse::<Wat>(&buf).unwrap().module;
+        original.resolve().unwrap();
+        let buf = ParseBuffer::new(&text).unwrap();
+        let mut optimized = parser::parse::<Wat>(&buf).unwrap().module;
+        optimized.optimize().unwrap();
+        if let Err(e) = optimized.validate() {
+            panic!("optimized module is invalid: {}\n{}", e, text);
+        }
+
+        let expected = run(body(&original), LOCALS as usize);
+        let actual = run(body(&optimized), LOCALS as usize);
+        assert_eq!(expected, actual, "{}", text);
+        if body(&original).len() != body(&optimized).len() {
+            changed += 1;
+        }
+    }
+    // Make sure the generated programs actually exercise the optimizer.
+    assert!(changed > 1000, "only {} bodies were changed", changed);
+}
@@ -0,0 +1,4 @@
+(module
+  (func (export "run"))
+  (memory 1)
+  (export "run" (memory 0)))
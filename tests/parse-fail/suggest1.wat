@@ -0,0 +1 @@
+(module (func i32.ad))
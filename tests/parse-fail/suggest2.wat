@@ -0,0 +1,3 @@
+(module
+  (func $foo)
+  (func call $fooo))
@@ -0,0 +1,4 @@
+(module
+  (func (local $count i32)
+    local.get $cout
+    drop))
@@ -0,0 +1,250 @@
+use wat::{Parser, WasmFeatures};
+
+#[test]
+fn name_section() {
+    let text = "(module $m (func $f))";
+    let with_names = Parser::new().parse_str(text).unwrap();
+    let without_names = Parser::new()
+        .generate_name_section(false)
+        .parse_str(text)
+        .unwrap();
+    assert!(without_names.len() < with_names.len());
+    assert_eq!(without_names, wat::parse_str("(module (func))").unwrap());
+}
+
+#[test]
+fn features() {
+    let text = "(module (func (drop (v128.const i32x4 0 0 0 0))))";
+    assert!(Parser::new().parse_str(text).is_ok());
+    let err = Parser::new()
+        .features(WasmFeatures::mvp())
+        .parse_str(text)
+        .unwrap_err();
+    assert!(err.to_string().contains("simd"), "{}", err);
+}
+
+#[test]
+fn binary_header() {
+    let truncated = b"\0asm\x01\0";
+    assert_eq!(&*wat::parse_bytes(truncated).unwrap(), truncated);
+    assert!(Parser::new()
+        .check_binary(true)
+        .parse_bytes(truncated)
+        .is_err());
+
+    let version = b"\0asm\x02\0\0\0";
+    assert!(Parser::new()
+        .check_binary(true)
+        .parse_bytes(version)
+        .is_err());
+
+    let valid = b"\0asm\x01\0\0\0";
+    let parsed = Parser::new().check_binary(true).parse_bytes(valid).unwrap();
+    assert_eq!(&*parsed, valid);
+}
+
+fn binary_error(bytes: &[u8]) -> String {
+    wast::Module::check_binary(bytes).unwrap_err().to_string()
+}
+
+#[test]
+fn binary_structure() {
+    let wasm = wat::parse_str(
+        r#"
+        (module
+            (type (func (param i32) (result i32)))
+            (import "a" "b" (func (type 0)))
+            (table 1 funcref)
+            (memory 1)
+            (global i32 (i32.const 1))
+            (export "f" (func 1))
+            (elem (i32.const 0) 0 1)
+            (func (type 0) (local i64) local.get 0)
+            (data (i32.const 0) "abc"))
+        "#,
+    )
+    .unwrap();
+    wast::Module::check_binary(&wasm).unwrap();
+    let parsed = Parser::new().check_binary(true).parse_bytes(&wasm).unwrap();
+    assert_eq!(&*parsed, &wasm[..]);
+
+    assert!(binary_error(b"\0asm\x01\0\0\0\x0d\0").contains("malformed section id"));
+    assert!(binary_error(b"\0asm\x01\0\0\0\x05\x01\0\x05\x01\0")
+        .contains("unexpected content after last section"));
+    assert!(binary_error(b"\0asm\x01\0\0\0\x03\x02\0\0").contains("section size mismatch"));
+    assert!(
+        binary_error(b"\0asm\x01\0\0\0\x03\x01\x80").contains("unexpected end at byte offset 11")
+    );
+    assert!(binary_error(b"\0asm\x01\0\0\0\x08\x05\x80\x80\x80\x80\x10")
+        .contains("integer too large at byte offset 14"));
+    assert!(binary_error(b"\0asm\x01\0\0\0\x03\x02\x01\0")
+        .contains("function and code section have inconsistent lengths"));
+
+    let err = wast::parser::parse::<wast::Wat>(
+        &wast::parser::ParseBuffer::new(r#"(module binary "\00asm\01\00\00\00\0d\00")"#).unwrap(),
+    )
+    .and_then(|mut wat| {
+        wat.module
+            .encode_with(wast::EncodeOptions::new().check_binary(true))
+    })
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("malformed section id at byte offset 8"));
+}
+
+#[test]
+fn validate_valid_modules() {
+    let text = r#"
+        (module
+            (type $sig (func (param i32) (result i32)))
+            (import "env" "f" (func $imp (param i32) (result i32)))
+            (import "env" "g" (global $imm i32))
+            (table $t 2 funcref)
+            (memory 1)
+            (global $g (mut i64) (i64.const 0))
+            (global $h i32 (global.get $imm))
+            (func $add (param $a i32) (param $b i32) (result i32)
+                (i32.add (local.get $a) (local.get $b)))
+            (func $control (param $x i32) (result i32) (local $y f64)
+                (block $out (result i32)
+                    (loop $top
+                        (br_if $top (i32.eqz (local.get $x)))
+                        (br_table $out $out (i32.const 0) (local.get $x)))
+                    (i32.const 1))
+                (if (result i32) (local.get $x)
+                    (then (i32.const 2))
+                    (else (call $imp (i32.const 3))))
+                i32.add
+                (local.set $y (f64.convert_i32_s (local.get $x)))
+                (drop (select (local.get $y) (f64.const 1) (i32.const 0)))
+                (global.set $g (i64.extend_i32_u (i32.load offset=4 (i32.const 0))))
+                (call_indirect $t (type $sig) (i32.const 5) (i32.const 0))
+                drop)
+            (func $unreachable (result i64)
+                unreachable
+                i32.add
+                drop
+                return)
+            (func $early (result i32)
+                (return (i32.const 1)))
+            (func $memory
+                (memory.fill (i32.const 0) (i32.const 0) (i32.const 1))
+                (drop (memory.grow (memory.size)))
+                (i32.atomic.store (i32.const 0) (i32.const 1))
+                (data.drop 0))
+            (func $simd (result v128)
+                (i32x4.add (v128.const i32x4 1 2 3 4) (i32x4.splat (i32.const 1))))
+            (func $refs (result anyref)
+                (select (result anyref) (ref.null) (ref.null) (i32.const 1)))
+            (func $multi (result i32 i64)
+                (i32.const 1) (i64.const 2))
+            (elem (i32.const 0) $add $control)
+            (data passive "hello")
+            (export "control" (func $control))
+            (start $memory))
+    "#;
+    Parser::new().validate(true).parse_str(text).unwrap();
+}
+
+#[test]
+fn validate_invalid_modules() {
+    let invalid = [
+        ("(module (func (result i32) (i64.const 0)))", "expected i32, found i64"),
+        ("(module (func i32.add))", "stack is empty"),
+        ("(module (func (i32.const 0)))", "values remaining"),
+        ("(module (func (drop (i32.load (i32.const 0)))))", "unknown memory"),
+        ("(module (func (drop (memory.size))))", "unknown memory"),
+        ("(module (global i32 (i32.const 0)) (func (global.set 0 (i32.const 1))))", "immutable"),
+        ("(module (func (block (result i32) (br 0))))", "stack is empty"),
+        (
+            "(module (func (block (result i32) (loop (br_table 0 1 (i32.const 0) (i32.const 0))))))",
+            "different arity",
+        ),
+        ("(module (func (param i32) (if (local.get 0) (then (i32.const 1)))))", "values remaining"),
+        ("(module (func (drop (select (i32.const 0) (i64.const 0) (i32.const 0)))))", "select"),
+        ("(module (func (result i32) (call 0)) (start 0))", "start function"),
+        ("(module (global i32 (i32.add (i32.const 0) (i32.const 1))))", "constant expression"),
+        ("(module (global i32 (i32.const 0)) (global i32 (global.get 0)))", "isn't imported"),
+        (
+            "(module (import \"a\" \"b\" (global (mut i32))) (global i32 (global.get 0)))",
+            "mutable global",
+        ),
+        ("(module (memory 1) (memory 1))", "multiple memories"),
+        ("(module (import \"a\" \"b\" (memory 1)) (memory 1))", "multiple memories"),
+    ];
+    for (text, message) in invalid.iter() {
+        assert!(wat::parse_str(text).is_ok(), "{}", text);
+        let err = Parser::new().validate(true).parse_str(text).unwrap_err();
+        assert!(err.to_string().contains(message), "{}: {}", text, err);
+    }
+}
+
+#[test]
+fn validate_multiple_tables() {
+    let text = "(module (table 1 funcref) (table 1 funcref))";
+    let mut features = WasmFeatures::mvp();
+    assert!(Parser::new().validate(true).parse_str(text).is_ok());
+    let buf = wast::parser::ParseBuffer::new(text).unwrap();
+    let mut module = wast::parser::parse::<wast::Wat>(&buf).unwrap().module;
+    module.resolve().unwrap();
+    let err = module.validate_with(&features).unwrap_err();
+    assert!(err.to_string().contains("multiple tables"), "{}", err);
+    features.reference_types(true);
+    module.validate_with(&features).unwrap();
+}
+
+#[test]
+fn validate_binary_modules() {
+    let as_text = |wasm: &[u8]| {
+        let escaped: String = wasm.iter().map(|b| format!("\\{:02x}", b)).collect();
+        format!("(module binary \"{}\")", escaped)
+    };
+
+    let valid = wat::parse_str("(module (func (result i32) (i32.const 0)))").unwrap();
+    let parsed = Parser::new().validate(true).parse_bytes(&valid).unwrap();
+    assert_eq!(*parsed, *valid);
+    Parser::new()
+        .validate(true)
+        .parse_str(as_text(&valid))
+        .unwrap();
+
+    let invalid = wat::parse_str("(module (func (result i32) (i64.const 0)))").unwrap();
+    let err = Parser::new()
+        .validate(true)
+        .parse_bytes(&invalid)
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("expected i32, found i64"),
+        "{}",
+        err
+    );
+    assert!(err.to_string().contains("at byte offset"), "{}", err);
+    let err = Parser::new()
+        .validate(true)
+        .parse_str(as_text(&invalid))
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("expected i32, found i64"),
+        "{}",
+        err
+    );
+    assert!(err.to_string().contains("at byte offset"), "{}", err);
+
+    let simd = wat::parse_str("(module (func (drop (v128.const i32x4 0 0 0 0))))").unwrap();
+    assert!(Parser::new().parse_bytes(&simd).is_ok());
+    for err in [
+        Parser::new()
+            .features(WasmFeatures::mvp())
+            .parse_bytes(&simd)
+            .unwrap_err(),
+        Parser::new()
+            .features(WasmFeatures::mvp())
+            .parse_str(as_text(&simd))
+            .unwrap_err(),
+    ]
+    .iter()
+    {
+        assert!(err.to_string().contains("simd"), "{}", err);
+    }
+}
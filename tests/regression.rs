@@ -2,3 +2,151 @@
 fn empty_string_fails() {
     assert!(wat::parse_str("").is_err());
 }
+
+#[test]
+fn label_shadowing() {
+    let text = "(module (func (block $l (block $l (br $l)))))";
+    let resolve = |options: &wast::ResolveOptions| {
+        let buf = wast::parser::ParseBuffer::new(text).unwrap();
+        let mut wat = wast::parser::parse::<wast::Wat>(&buf).unwrap();
+        wat.module
+            .resolve_with(options)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    };
+
+    let mut options = wast::ResolveOptions::new();
+    assert!(resolve(&options).is_ok());
+    options.reject_label_shadowing(true);
+    let err = resolve(&options).unwrap_err();
+    assert!(err.contains("duplicate label"), "{}", err);
+    assert!(wat::parse_str(text).is_ok());
+}
+
+#[test]
+fn owned_module_outlives_source() {
+    fn parse(text: String) -> wast::Module<'static> {
+        let buf = wast::parser::ParseBuffer::new(&text).unwrap();
+        let wat = wast::parser::parse::<wast::Wat>(&buf).unwrap();
+        wat.module.into_owned()
+    }
+    let text = r#"
+        (module $m
+            (import "env" "f" (func $f (param i32)))
+            (memory 1)
+            (func (export "run") (call $f (i32.const 1)))
+            (data (i32.const 0) "hello"))
+    "#;
+    let mut module = parse(text.to_string());
+    let expected = wat::parse_str(text).unwrap();
+    assert_eq!(module.encode().unwrap(), expected);
+
+    let wast = "(module (func $f)) (assert_return (invoke \"f\"))";
+    let buf = wast::parser::ParseBuffer::new(wast).unwrap();
+    let owned = wast::parser::parse::<wast::Wast>(&buf)
+        .unwrap()
+        .into_owned();
+    drop(buf);
+    assert_eq!(owned.directives.len(), 2);
+}
+
+#[test]
+fn structural_equality_ignores_spans() {
+    fn parse(text: &str) -> wast::Module<'static> {
+        let buf = wast::parser::ParseBuffer::new(text).unwrap();
+        let wat = wast::parser::parse::<wast::Wat>(&buf).unwrap();
+        wat.module.into_owned()
+    }
+    let a = parse("(module (func $f (export \"f\") (result i32) i32.const 1))");
+    let b = parse("(module\n  (func $f (export \"f\") (result i32)\n    i32.const 1))");
+    assert!(a != b);
+    assert!(a.eq_ignoring_spans(&b));
+
+    let mut c = b.clone();
+    if let wast::ModuleKind::Text(fields) = &mut c.kind {
+        if let wast::ModuleField::Func(f) = &mut fields[0] {
+            if let wast::FuncKind::Inline { expression, .. } = &mut f.kind {
+                expression.instrs[0] = wast::Instruction::I32Const(2);
+            }
+        }
+    }
+    assert!(!a.eq_ignoring_spans(&c));
+    assert!(b.eq_ignoring_spans(&b.clone()));
+}
+
+#[test]
+fn disabled_features_are_rejected() {
+    fn encode(text: &str, features: wast::WasmFeatures) -> Result<Vec<u8>, String> {
+        let buf = wast::parser::ParseBuffer::new(text).unwrap();
+        let mut wat = wast::parser::parse::<wast::Wat>(&buf).unwrap();
+        let mut options = wast::EncodeOptions::new();
+        options.features(features);
+        wat.module.encode_with(&options).map_err(|mut e| {
+            e.set_text(text);
+            e.to_string()
+        })
+    }
+    let mut mvp_sign_ext = wast::WasmFeatures::mvp();
+    mvp_sign_ext.sign_extension(true);
+
+    let ok = "(module (func (result i32) (i32.extend8_s (i32.const 1))))";
+    encode(ok, mvp_sign_ext.clone()).unwrap();
+
+    let simd = "(module\n (func (result v128)\n  v128.const i32x4 0 0 0 0))";
+    let err = encode(simd, mvp_sign_ext.clone()).unwrap_err();
+    assert!(
+        err.contains("the `v128` type requires the simd proposal"),
+        "{}",
+        err
+    );
+    assert!(err.contains(":2:"), "{}", err);
+
+    let tail = "(module (func return_call 0))";
+    let err = encode(tail, mvp_sign_ext.clone()).unwrap_err();
+    assert!(err.contains("`return_call` instruction"), "{}", err);
+    encode(tail, wast::WasmFeatures::new()).unwrap();
+
+    let multi = "(module (func (result i32 i32) i32.const 0 i32.const 0))";
+    let err = encode(multi, mvp_sign_ext.clone()).unwrap_err();
+    assert!(err.contains("multi-value"), "{}", err);
+
+    let indexed = "(module (type $t (func (param i32))) (func (block (type $t) drop)))";
+    let err = encode(indexed, mvp_sign_ext.clone()).unwrap_err();
+    assert!(err.contains("a block with parameters"), "{}", err);
+
+    let passive = "(module (memory 1) (data passive \"\"))";
+    let err = encode(passive, mvp_sign_ext).unwrap_err();
+    assert!(err.contains("bulk memory"), "{}", err);
+}
+
+#[test]
+fn encoding_is_deterministic() {
+    let text = r#"
+        (module $m
+            (type $t (func (param i32) (result i32)))
+            (import "a" "b" (func $imported (param i64)))
+            (func $f (param $x i32) (result i32) (local $y f32)
+                (block $b (result i32)
+                    (call $g (local.get $x))))
+            (func $g (param f64 i32) (result i32) (local.get 1))
+            (func $h (type $t) (local.get 0))
+            (func (export "e") (param i64 i64))
+            (table funcref (elem $f $g $h)))
+    "#;
+    let mut without_names = wast::EncodeOptions::new();
+    without_names.name_section(false);
+    let encode = |options: &wast::EncodeOptions| {
+        let buf = wast::parser::ParseBuffer::new(text).unwrap();
+        let mut wat = wast::parser::parse::<wast::Wat>(&buf).unwrap();
+        wat.module.encode_with(options).unwrap()
+    };
+
+    let with_names = encode(&wast::EncodeOptions::new());
+    let stripped = encode(&without_names);
+    assert!(stripped.len() < with_names.len());
+    assert!(with_names.starts_with(&stripped));
+    for _ in 0..20 {
+        assert_eq!(encode(&wast::EncodeOptions::new()), with_names);
+        assert_eq!(encode(&without_names), stripped);
+    }
+}
@@ -0,0 +1,50 @@
+use wast::parser::{self, ParseBuffer};
+use wast::{Module, Wast, Wat};
+
+const TEXT: &str = r#"
+    (module $m
+        (type $t (func (param i32) (result i32)))
+        (import "env" "f" (func $f (type $t)))
+        (memory 1)
+        (table 1 funcref)
+        (global $g (mut f32) (f32.const 1.5))
+        (func $add (export "add") (param $a i32) (param $b i32) (result i32)
+            (i32.add (local.get $a) (local.get $b)))
+        (func (param i32) (result i32)
+            (block $b (result i32)
+                (call_indirect (type $t) (local.get 0) (i32.const 0))))
+        (elem (i32.const 0) $f)
+        (data (i32.const 0) "hello"))
+"#;
+
+#[test]
+fn roundtrip_module() {
+    let expected = wat::parse_str(TEXT).unwrap();
+    let buf = ParseBuffer::new(TEXT).unwrap();
+    let mut wat = parser::parse::<Wat>(&buf).unwrap();
+
+    let json = serde_json::to_string(&wat.module).unwrap();
+    let mut module: Module<'static> = serde_json::from_str(&json).unwrap();
+    assert!(module.eq_ignoring_spans(&wat.module));
+    assert_eq!(module.encode().unwrap(), expected);
+
+    // Resolved modules can also be serialized and encoded again.
+    wat.module.resolve().unwrap();
+    let json = serde_json::to_string(&wat.module).unwrap();
+    let mut module: Module<'static> = serde_json::from_str(&json).unwrap();
+    assert_eq!(module.encode().unwrap(), expected);
+}
+
+#[test]
+fn roundtrip_wast() {
+    let text = r#"
+        (module (func (export "f") (result f64) f64.const nan))
+        (assert_return (invoke "f") (f64.const nan:canonical))
+        (assert_malformed (module quote "(func)") "error")
+    "#;
+    let buf = ParseBuffer::new(text).unwrap();
+    let wast = parser::parse::<Wast>(&buf).unwrap();
+    let json = serde_json::to_string(&wast).unwrap();
+    let back: Wast<'static> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, wast);
+}
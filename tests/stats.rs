@@ -0,0 +1,98 @@
+use wast::Module;
+
+const TEXT: &str = r#"
+    (module
+        (import "env" "print" (func $print (param i32)))
+        (import "env" "mem" (memory 1))
+        (global $g (export "g") (mut i32) (i32.const 0))
+        (func $main (export "main") (param i32)
+            (if (local.get 0)
+                (then (call $print (i32.const 1))))
+            (global.set $g (i32.add (local.get 0) (i32.const 2))))
+        (func (result i32) i32.const 3))
+"#;
+
+#[test]
+fn text_report() {
+    let wasm = wat::parse_str(TEXT).unwrap();
+    let stats = Module::stats(&wasm).unwrap();
+    assert_eq!(stats.size, wasm.len());
+    let expected = format!(
+        "\
+Size: {} bytes
+Imports: 1 functions, 0 tables, 1 memories, 0 globals
+Exports: 1 functions, 0 tables, 0 memories, 1 globals
+
+Sections:
+ Type                 9 bytes
+ Import              24 bytes
+ Function             3 bytes
+ Global               6 bytes
+ Export              12 bytes
+ Code                25 bytes
+ Custom \"name\"       21 bytes
+
+Functions:
+ func[1] <main>: 18 bytes, 9 instructions
+ func[2]: 4 bytes, 1 instructions
+
+Instructions:
+ i32.const                       3
+ local.get                       2
+ call                            1
+ end                             1
+ global.set                      1
+ i32.add                         1
+ if                              1
+",
+        wasm.len()
+    );
+    assert_eq!(stats.to_string(), expected);
+}
+
+#[test]
+fn section_sizes_add_up() {
+    let wasm = wat::parse_str(TEXT).unwrap();
+    let stats = Module::stats(&wasm).unwrap();
+    // Each section here has a 1-byte id and a 1-byte size, after the 8-byte
+    // header.
+    let sections = stats.sections.iter().map(|s| 2 + s.size).sum::<usize>();
+    assert_eq!(8 + sections, stats.size);
+}
+
+#[test]
+fn json_report() {
+    let wasm = wat::parse_str(TEXT).unwrap();
+    let stats = Module::stats(&wasm).unwrap();
+    let json = serde_json::to_value(&stats).unwrap();
+    assert_eq!(json["funcs"][0]["name"], "main");
+    assert_eq!(json["funcs"][1]["name"], serde_json::Value::Null);
+    assert_eq!(json["instructions"][0]["name"], "i32.const");
+    assert_eq!(json["imports"]["memories"], 1);
+    assert_eq!(json["exports"]["globals"], 1);
+    let roundtrip: wast::Stats = serde_json::from_value(json).unwrap();
+    assert_eq!(roundtrip, stats);
+}
+
+#[test]
+fn malformed_modules_are_errors() {
+    let err = Module::stats(b"\0asm\x01\0\0\0\x01\x05\x01").unwrap_err();
+    assert!(err.to_string().contains("at byte offset"), "{}", err);
+}
+
+#[test]
+fn declarative_segments() {
+    // A function listed in a declarative element segment, which
+    // `Module::decode` can't represent.
+    let wasm = b"\0asm\x01\0\0\0\
+        \x01\x04\x01\x60\0\0\
+        \x03\x02\x01\0\
+        \x09\x05\x01\x03\0\x01\0\
+        \x0a\x04\x01\x02\0\x0b";
+    assert!(Module::decode(wasm).is_err());
+    let stats = Module::stats(wasm).unwrap();
+    assert_eq!(stats.funcs.len(), 1);
+    assert_eq!(stats.funcs[0].size, 2);
+    assert_eq!(stats.funcs[0].instructions, 0);
+    assert_eq!(stats.sections[2].name, "Elem");
+}
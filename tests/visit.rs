@@ -0,0 +1,100 @@
+use wast::parser::{self, ParseBuffer};
+use wast::visit::{IndexSpace, Visit, VisitMut};
+use wast::{Index, Wat};
+
+#[derive(Default)]
+struct Indices(Vec<(IndexSpace, String)>);
+
+impl<'a> Visit<'a> for Indices {
+    fn visit_index(&mut self, index: &Index<'a>, space: IndexSpace) {
+        let name = match index {
+            Index::Num(n) => n.to_string(),
+            Index::Id(id) => id.name().to_string(),
+        };
+        self.0.push((space, name));
+    }
+}
+
+#[test]
+fn index_spaces() {
+    let text = r#"
+        (module
+            (type $t (func))
+            (import "" "f" (func $f (type $t)))
+            (table $tab 1 funcref)
+            (memory $mem 1)
+            (global $g (mut i32) (i32.const 0))
+            (func $main (local $l i32)
+                (block $b
+                    (br_if $b (local.get $l))
+                    (call $f)
+                    (call_indirect $tab (type $t) (global.get $g))))
+            (elem $e (table $tab) (i32.const 0) $main)
+            (data $d (memory $mem) (i32.const 0) "")
+            (export "main" (func $main))
+            (start $main))
+    "#;
+    let buf = ParseBuffer::new(text).unwrap();
+    let wat = parser::parse::<Wat>(&buf).unwrap();
+    let mut indices = Indices::default();
+    indices.visit_module(&wat.module);
+    let expected = [
+        (IndexSpace::Type, "t"),
+        (IndexSpace::Local, "l"),
+        (IndexSpace::Label, "b"),
+        (IndexSpace::Func, "f"),
+        (IndexSpace::Global, "g"),
+        (IndexSpace::Table, "tab"),
+        (IndexSpace::Type, "t"),
+        (IndexSpace::Table, "tab"),
+        (IndexSpace::Func, "main"),
+        (IndexSpace::Memory, "mem"),
+        (IndexSpace::Func, "main"),
+        (IndexSpace::Func, "main"),
+    ];
+    let actual = indices
+        .0
+        .iter()
+        .map(|(space, name)| (*space, name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(actual, expected);
+}
+
+struct Rename;
+
+impl<'a> VisitMut<'a> for Rename {
+    fn visit_index(&mut self, index: &mut Index<'a>, space: IndexSpace) {
+        if space != IndexSpace::Func {
+            return;
+        }
+        if let Index::Id(id) = index {
+            if id.name() == "old" {
+                *index = Index::Id(wast::Id::new("new", id.span()));
+            }
+        }
+    }
+}
+
+#[test]
+fn rename_function_references() {
+    let text = r#"
+        (module
+            (func $old)
+            (func $new (call $old))
+            (export "f" (func $old)))
+    "#;
+    let buf = ParseBuffer::new(text).unwrap();
+    let mut wat = parser::parse::<Wat>(&buf).unwrap();
+    Rename.visit_module(&mut wat.module);
+    let actual = wat.module.encode().unwrap();
+    let expected = wat::parse_str(
+        r#"
+        (module
+            (func $old)
+            (func $new (call $new))
+            (export "f" (func $new)))
+    "#,
+    )
+    .unwrap();
+    assert_eq!(actual, expected);
+}
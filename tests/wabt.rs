@@ -2,7 +2,9 @@
 //!
 //! * First, asserts that we can parse and encode them all to binary.
 //! * Next uses `wat2wasm` to encode to binary.
-//! * Finally, asserts that the two binary encodings are byte-for-byte the same.
+//! * Next, asserts that the two binary encodings are byte-for-byte the same.
+//! * Finally, asserts that decoding our binary and encoding the result again
+//!   produces the same bytes.
 //!
 //! This also has support for handling `*.wast` files from the official test
 //! suite which involve parsing as a wast file and handling assertions. Also has
@@ -63,7 +65,12 @@ fn run_test(test: &Path, contents: &str) -> anyhow::Result<()> {
     if wast {
         return test_wast(test, contents);
     }
-    let binary = wat::parse_file(test)?;
+    // I tried for a bit but honestly couldn't figure out a great way to match
+    // wabt's encoding of the name section, so don't emit one to compare
+    // against wabt's output, which doesn't have one.
+    let binary = wat::Parser::new()
+        .generate_name_section(false)
+        .parse_file(test)?;
 
     // FIXME(#5) fix these tests
     if test.ends_with("invalid-elem-segment-offset.txt")
@@ -75,6 +82,11 @@ fn run_test(test: &Path, contents: &str) -> anyhow::Result<()> {
     if let Some(expected) = wat2wasm(&test, None) {
         binary_compare(&test, 0, &binary, &expected)?;
     }
+    // Binary modules are passed through verbatim, so there's nothing to
+    // round trip, and some of them are intentionally malformed.
+    if !contents.contains("(module binary") {
+        decode_round_trip(&test, 0, &binary)?;
+    }
     Ok(())
 }
 
@@ -110,14 +122,18 @@ fn test_wast(test: &Path, contents: &str) -> anyhow::Result<()> {
         .map(|(directive, modulei)| {
             match directive {
                 WastDirective::Module(mut module) => {
-                    let actual = module.encode().map_err(|e| adjust!(e))?;
+                    // See `run_test` for why the name section is omitted.
+                    let actual = module
+                        .encode_with(EncodeOptions::new().name_section(false))
+                        .map_err(|e| adjust!(e))?;
 
                     match module.kind {
                         ModuleKind::Text(_) => {
+                            let (line, _) = module.span.linecol_in(contents);
                             if let Some(expected) = wat2wasm(&test, Some(modulei)) {
-                                let (line, _) = module.span.linecol_in(contents);
                                 binary_compare(&test, line, &actual, &expected)?;
                             }
+                            decode_round_trip(&test, line, &actual)?;
                         }
                         // Skip these for the same reason we skip
                         // `module/binary-module.txt` in `binary_compare` below.
@@ -155,7 +171,7 @@ fn test_wast(test: &Path, contents: &str) -> anyhow::Result<()> {
                             message,
                         ),
                         Err(e) => {
-                            if error_matches(&e.to_string(), message) {
+                            if error_matches(&e.to_string(), &message) {
                                 return Ok(());
                             }
                             anyhow::bail!(
@@ -240,6 +256,12 @@ fn find_tests() -> Vec<PathBuf> {
     }
 }
 
+fn decode_round_trip(test: &Path, line: usize, binary: &[u8]) -> anyhow::Result<()> {
+    let mut module = Module::decode(binary)?;
+    let actual = module.encode_with(EncodeOptions::new().name_section(false))?;
+    binary_compare(test, line, &actual, binary)
+}
+
 fn binary_compare(
     test: &Path,
     line: usize,
@@ -248,11 +270,6 @@ fn binary_compare(
 ) -> Result<(), anyhow::Error> {
     use wasmparser::*;
 
-    // I tried for a bit but honestly couldn't figure out a great way to match
-    // wabt's encoding of the name section. Just remove it from our asserted
-    // sections and don't compare against wabt's.
-    let actual = remove_name_section(actual);
-
     // We test wabt with `--enable-all`, but this *always* emits a data count
     // section in the binary. We, however, only emit it if necessary. To handle
     // these differences remove wabt's data count section if our binary doesn't
@@ -293,6 +310,15 @@ error: actual wasm differs {pos} from expected wasm
         msg.push_str(&format!("       | + {:#04x}\n", actual[pos]));
     }
 
+    // Describe how the two modules differ section by section, if they can
+    // both be decoded.
+    if let Ok(differences) = wast::Module::diff(&expected, actual) {
+        msg.push('\n');
+        for difference in differences.iter().take(10) {
+            msg.push_str(&format!("       | {}\n", difference));
+        }
+    }
+
     let mut actual_parser = Parser::new(&actual);
     let mut expected_parser = Parser::new(&expected);
 
@@ -333,6 +359,29 @@ error: actual wasm differs {pos} from expected wasm
         differences += 1;
     }
 
+    // Also show where the dumps of the two binaries first differ, which is
+    // usually easier to read than the parser states above.
+    if let (Ok(actual), Ok(expected)) = (wast::Module::dump(actual), wast::Module::dump(&expected))
+    {
+        let actual = actual.lines().collect::<Vec<_>>();
+        let expected = expected.lines().collect::<Vec<_>>();
+        let first = actual
+            .iter()
+            .zip(&expected)
+            .position(|(a, e)| a != e)
+            .unwrap_or_else(|| actual.len().min(expected.len()));
+        msg.push_str("\n\ndump:\n\n");
+        for line in &expected[first.saturating_sub(3)..first] {
+            msg.push_str(&format!("       |   {}\n", line));
+        }
+        for line in expected.iter().skip(first).take(5) {
+            msg.push_str(&format!("       | - {}\n", line));
+        }
+        for line in actual.iter().skip(first).take(5) {
+            msg.push_str(&format!("       | + {}\n", line));
+        }
+    }
+
     anyhow::bail!("{}", msg);
 
     fn read_state<'a, 'b>(parser: &'b mut Parser<'a>) -> Option<String> {
@@ -358,27 +407,6 @@ error: actual wasm differs {pos} from expected wasm
         false
     }
 
-    fn remove_name_section(bytes: &[u8]) -> Vec<u8> {
-        if let Ok(mut r) = ModuleReader::new(bytes) {
-            loop {
-                let start = r.current_position();
-                if let Ok(s) = r.read() {
-                    match s.code {
-                        SectionCode::Custom { name: "name", .. } => {
-                            let mut bytes = bytes.to_vec();
-                            bytes.drain(start..s.range().end);
-                            return bytes;
-                        }
-                        _ => {}
-                    }
-                } else {
-                    break;
-                }
-            }
-        }
-        return bytes.to_vec();
-    }
-
     fn remove_datacount_section(bytes: &[u8]) -> Vec<u8> {
         if let Ok(mut r) = ModuleReader::new(bytes) {
             loop {